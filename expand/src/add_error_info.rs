@@ -0,0 +1,358 @@
+use std::env;
+
+use proc_macro2::{Group, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    fold::{self, Fold},
+    parse_quote,
+    spanned::Spanned,
+    visit::Visit,
+    Block, Error, Expr, ExprAsync, ExprClosure, ExprTry, Generics, Item, ItemFn, Lifetime, Macro,
+    PathArguments, ReturnType, Stmt, Token, Type,
+};
+
+use crate::{expr_map::hash_expr, log_statement::Backend, options::Options};
+
+pub struct AddErrorInfo {
+    /// If backtraces are enabled, the condition for capturing a backtrace at a try expression
+    pub backtrace: Option<TokenStream2>,
+    /// The name of the function, for locations of generated code
+    pub function: String,
+    /// How many try expressions we've seen so far
+    pub sites: u32,
+    /// The lifetime `WrapMatchError`s use. This is `'_` when the body stays in the function we make instead of moving to an inner function, so no
+    /// lifetime parameter is added.
+    pub lifetime: Lifetime,
+    /// Code with try expressions that we couldn't add error info to, and what kind of code it is (for `strict`)
+    pub skipped: Vec<(Span, &'static str)>,
+    /// Whether literals in the captured expressions are replaced with placeholders, so secrets in them aren't logged
+    pub redact_literals: bool,
+    /// Whether a `tracing_error::SpanTrace` is captured at try expressions
+    pub span_trace: bool,
+    /// Whether the captured expressions are replaced with their hashes
+    pub hash_expressions: bool,
+    /// The hashes we've replaced expressions with, and the expressions, for the expression map
+    pub hashed: Vec<(String, String)>,
+}
+
+/// Returns the location of a span, or a logical location (the function and an index) if the span isn't from a file the user wrote, since `line!()` and
+/// friends aren't useful there
+pub fn source_location(span: Span, function: &str, site: u32) -> TokenStream2 {
+    if is_generated(span) {
+        quote!(::wrap_match::__private::SourceLocation::Site {
+            function: #function,
+            index: #site,
+        })
+    } else {
+        // only the macros get the span, since they need it to find the location
+        let file = quote_spanned!(span=> ::core::file!());
+        let line = quote_spanned!(span=> ::core::line!());
+        let column = quote_spanned!(span=> ::core::column!());
+        quote! {
+            ::wrap_match::__private::SourceLocation::File {
+                file: #file,
+                line: #line,
+                column: #column,
+            }
+        }
+    }
+}
+
+/// Returns whether a span comes from code that was generated, either by another macro or by a build script (and then `include!`d from `OUT_DIR`)
+fn is_generated(span: Span) -> bool {
+    // outside of a procedural macro (when the expansion is used on its own) there's no file to look at
+    if !proc_macro::is_available() {
+        return false;
+    }
+    let Some(file) = span.unwrap().local_file() else {
+        return true;
+    };
+    env::var_os("OUT_DIR").is_some_and(|out_dir| file.starts_with(out_dir))
+}
+
+/// Returns whether an expression has a try expression in it
+fn contains_try(expr: &Expr) -> bool {
+    struct FindTry(bool);
+    impl Visit<'_> for FindTry {
+        fn visit_expr_try(&mut self, _: &ExprTry) {
+            self.0 = true;
+        }
+
+        fn visit_macro(&mut self, i: &Macro) {
+            self.0 |= tokens_contain_try(i.tokens.clone());
+        }
+    }
+    let mut find_try = FindTry(false);
+    find_try.visit_expr(expr);
+    find_try.0
+}
+
+/// Returns whether the tokens of a macro invocation have a `?` in them. Since macros can do anything with their tokens, this is only a guess.
+fn tokens_contain_try(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Punct(punct) => punct.as_char() == '?',
+        TokenTree::Group(group) => tokens_contain_try(group.stream()),
+        _ => false,
+    })
+}
+
+/// Replaces the literals in tokens with placeholders that keep the code valid: `"***"` for strings, `'*'` for characters and `_` for numbers
+fn redact_literals(tokens: TokenStream2) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Literal(literal) => {
+                let text = literal.to_string();
+                let text = text.trim_start_matches(['b', 'c', 'r', '#']);
+                let placeholder = if text.starts_with('"') {
+                    quote!("***")
+                } else if text.starts_with('\'') {
+                    quote!('*')
+                } else {
+                    quote!(_)
+                };
+                placeholder.into_iter().next().unwrap()
+            }
+            TokenTree::Group(group) => {
+                let mut redacted = Group::new(group.delimiter(), redact_literals(group.stream()));
+                redacted.set_span(group.span());
+                TokenTree::Group(redacted)
+            }
+            _ => token,
+        })
+        .collect()
+}
+
+/// Turns the tail expression of a block (and of its branches) into `Ok(tail?)`, so an error returned by it gets error info like a try expression. `Ok(...)`,
+/// `Err(...)`, macro invocations and expressions that never produce a value (like `return`) are left alone.
+pub fn add_tail_try(block: &mut Block) {
+    if let Some(Stmt::Expr(expr, None)) = block.stmts.last_mut() {
+        add_tail_try_to_expr(expr);
+    }
+}
+
+fn add_tail_try_to_expr(expr: &mut Expr) {
+    match expr {
+        Expr::Block(e) => add_tail_try(&mut e.block),
+        Expr::Unsafe(e) => add_tail_try(&mut e.block),
+        Expr::If(e) => {
+            add_tail_try(&mut e.then_branch);
+            if let Some((_, else_branch)) = &mut e.else_branch {
+                add_tail_try_to_expr(else_branch);
+            }
+        }
+        Expr::Match(e) => {
+            for arm in &mut e.arms {
+                add_tail_try_to_expr(&mut arm.body);
+            }
+        }
+        Expr::Paren(e) => add_tail_try_to_expr(&mut e.expr),
+        Expr::Call(call) if matches!(&*call.func, Expr::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Ok" || s.ident == "Err")) =>
+            {}
+        Expr::Return(_)
+        | Expr::Break(_)
+        | Expr::Continue(_)
+        | Expr::Loop(_)
+        | Expr::Macro(_)
+        | Expr::Try(_) => {}
+        _ => {
+            // the `?` gets the span of the expression, so conversion errors point at it
+            let question = Token![?](expr.span());
+            let tail = expr.clone();
+            *expr = parse_quote!(::core::result::Result::Ok(#tail #question));
+        }
+    }
+}
+
+/// Adds error info to the try expressions of a function, returning the function and how many try expressions it has. With `inline_body`, the body
+/// stays in the function we make instead of moving to an inner function.
+pub fn add_error_info(
+    options: &Options,
+    mut input: ItemFn,
+    backend: Backend,
+    inline_body: bool,
+) -> syn::Result<(ItemFn, u32)> {
+    // capturing a backtrace is cheap compared to resolving its symbols (which only happens if it's logged), but we still skip it if errors won't be logged.
+    // the level is often only known at runtime, but if the most severe level is disabled, nothing will be logged
+    let capture_backtrace = options.backtrace.then(|| match backend {
+        Backend::Log => quote!(::wrap_match::__log::log::log_enabled!(
+            ::wrap_match::__log::log::Level::Error
+        )),
+        Backend::Tracing => quote!(::wrap_match::__log::tracing::enabled!(
+            ::wrap_match::__log::tracing::Level::ERROR
+        )),
+        Backend::Plain => quote!(true),
+    });
+    let mut add_error_info = AddErrorInfo {
+        backtrace: capture_backtrace,
+        function: input.sig.ident.to_string(),
+        lifetime: if inline_body {
+            parse_quote!('_)
+        } else {
+            parse_quote!('_wrap_match_error)
+        },
+        sites: 0,
+        skipped: vec![],
+        redact_literals: options.redact_literals,
+        span_trace: options.span_trace,
+        hash_expressions: options.hash_expressions,
+        hashed: vec![],
+    };
+    if options.tail_result {
+        add_tail_try(&mut input.block);
+    }
+    let input = add_error_info.fold_item_fn(input);
+    if options.strict {
+        let errors = add_error_info.skipped.iter().map(|(span, kind)| {
+            Error::new(
+                *span,
+                format!("wrap_match: try expressions in {kind} are left alone, so errors from them won't have line and expression info (this is an error because `strict = true`)"),
+            )
+        });
+        if let Some(error) = errors.reduce(|mut errors, error| {
+            errors.combine(error);
+            errors
+        }) {
+            return Err(error);
+        }
+    }
+    Ok((input, add_error_info.sites))
+}
+
+impl Fold for AddErrorInfo {
+    /// Adds error metadata/info (line number and expression that caused it) to try expressions
+    fn fold_expr_try(&mut self, mut i: ExprTry) -> ExprTry {
+        let span = i.span();
+        let expr = *i.expr;
+        let expr_tokens = if self.redact_literals {
+            redact_literals(expr.to_token_stream())
+        } else {
+            expr.to_token_stream()
+        };
+        let expr_str = {
+            // https://github.com/dtolnay/prettyplease/issues/57
+            // https://github.com/dtolnay/prettyplease/issues/5
+            let lines: Vec<_> = prettyplease::unparse(
+                &syn::parse_file(&format!("fn main() {{\n{}\n}}", expr_tokens))
+                    .expect("invalid expression? something made syn fail to parse the file"),
+            )
+            .trim()
+            .lines()
+            .map(|line| {
+                let mut line = line.to_owned();
+                // Remove the first indent, if there is one
+                if line.starts_with("    ") {
+                    line.drain(..4);
+                }
+                line
+            })
+            .collect();
+            lines[1..(lines.len() - 1)].join("\n")
+        };
+        let expr_str = if self.hash_expressions {
+            let hash = hash_expr(&self.function, &expr_str);
+            self.hashed.push((hash.clone(), expr_str));
+            hash
+        } else {
+            expr_str
+        };
+        let backtrace = match &self.backtrace {
+            Some(condition) => quote! {
+                if #condition {
+                    Some(::std::backtrace::Backtrace::force_capture())
+                } else {
+                    None
+                }
+            },
+            None => quote!(None),
+        };
+        // the field only exists with the `span_trace` feature
+        let span_trace = cfg!(feature = "span_trace").then(|| {
+            let capture = if self.span_trace {
+                quote!(Some(
+                    ::wrap_match::__log::tracing_error::SpanTrace::capture()
+                ))
+            } else {
+                quote!(None)
+            };
+            quote!(span_trace: #capture,)
+        });
+        self.sites += 1;
+        let location = source_location(span, &self.function, self.sites);
+        // the glue we add keeps the call site's span, so editors don't show hints for it or mistake it for the user's code. only `line!()` gets the
+        // span of the try expression, and the conversion (which can fail to compile) gets the span of the `?`, like it would without us
+        let line = quote_spanned!(span=> ::core::line!());
+        let into = quote_spanned!(i.question_token.span=> ::wrap_match::__private::QuestionMarkConversion::convert_error(e));
+        i.expr = parse_quote! {
+            #expr.map_err(|e| ::wrap_match::__private::WrapMatchError {
+                    line_and_expr: Some((#line, #expr_str)),
+                    location: Some(#location),
+                    backtrace: #backtrace,
+                    #span_trace
+                    #[allow(clippy::useless_conversion)]
+                    inner: #into
+                }
+            )
+        };
+        fold::fold_expr_try(self, i)
+    }
+
+    /// Closures and async blocks return their own errors, so we leave their try expressions alone
+    fn fold_expr_closure(&mut self, i: ExprClosure) -> ExprClosure {
+        if contains_try(&i.body) {
+            self.skipped.push((i.span(), "closures"));
+        }
+        i
+    }
+
+    fn fold_expr_async(&mut self, i: ExprAsync) -> ExprAsync {
+        if contains_try(&Expr::Async(i.clone())) {
+            self.skipped.push((i.span(), "async blocks"));
+        }
+        i
+    }
+
+    /// We can't see into macro invocations, since they are just tokens until they are expanded
+    fn fold_macro(&mut self, i: Macro) -> Macro {
+        if tokens_contain_try(i.tokens.clone()) {
+            self.skipped.push((i.span(), "macro invocations"));
+        }
+        i
+    }
+
+    /// Items inside the function (such as nested functions) are separate from it
+    fn fold_item(&mut self, i: Item) -> Item {
+        i
+    }
+
+    /// Changes the Result error type to use our special error
+    fn fold_return_type(&mut self, i: ReturnType) -> ReturnType {
+        match i {
+            ReturnType::Default => fold::fold_return_type(self, i),
+            ReturnType::Type(arrow, ty) => {
+                let mut ty = *ty;
+                if let Type::Path(p) = &mut ty {
+                    for segment in &mut p.path.segments {
+                        if segment.ident.to_string().contains("Result") {
+                            if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                                let err_type = args.args.pop().unwrap().value().clone();
+                                let lifetime = &self.lifetime;
+                                args.args.push(parse_quote!(::wrap_match::__private::WrapMatchError<#lifetime, #err_type>));
+                            }
+                        }
+                    }
+                }
+                fold::fold_return_type(self, ReturnType::Type(arrow, Box::new(ty)))
+            }
+        }
+    }
+
+    /// Add the lifetime `WrapMatchError`s will use
+    fn fold_generics(&mut self, mut i: Generics) -> Generics {
+        if self.lifetime.ident != "_" {
+            let lifetime = &self.lifetime;
+            i.params.insert(0, parse_quote!(#lifetime));
+        }
+        fold::fold_generics(self, i)
+    }
+}
@@ -0,0 +1,81 @@
+//! Message catalogs (enabled with the `catalog` feature).
+//!
+//! The catalog is read from the file given with the `catalog` option, the file at `WRAP_MATCH_CATALOG`, or `wrap-match-messages.toml` (all relative to
+//! the crate being compiled). Each key is a table with any of `success_message`, `error_message`, `error_message_without_info` and
+//! `inner_error_message`, or a string, which is used as `error_message`.
+
+use std::{env, fs, path::PathBuf};
+
+use syn::Error;
+
+use crate::options::Options;
+
+/// Replaces the function's messages with the ones from the catalog, if it has a `message_key`
+pub fn apply_catalog(options: &mut Options) -> syn::Result<()> {
+    let Some((key, key_span)) = options.message_key.clone() else {
+        return Ok(());
+    };
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let (path, path_span) = match (&options.catalog, env::var("WRAP_MATCH_CATALOG")) {
+        (Some((path, span)), _) => (manifest_dir.join(path), *span),
+        (None, Ok(path)) => (manifest_dir.join(path), key_span),
+        (None, Err(_)) => (manifest_dir.join("wrap-match-messages.toml"), key_span),
+    };
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        Error::new(
+            path_span,
+            format!("wrap_match: couldn't read {}: {e}", path.display()),
+        )
+    })?;
+    let table: toml::Table = contents.parse().map_err(|e| {
+        Error::new(
+            path_span,
+            format!("wrap_match: failed to parse {}: {e}", path.display()),
+        )
+    })?;
+
+    // keys like `db.sync.failed` can either be written as nested tables or as one quoted key
+    let entry = table.get(&key).or_else(|| {
+        let mut segments = key.split('.');
+        let first = table.get(segments.next()?)?;
+        segments.try_fold(first, |value, segment| value.get(segment))
+    });
+    let invalid = |message: &str| {
+        Error::new(
+            key_span,
+            format!("wrap_match: {message} `{key}` in {}", path.display()),
+        )
+    };
+    match entry {
+        Some(toml::Value::String(message)) => {
+            options.error_message = (message.clone(), key_span);
+        }
+        Some(toml::Value::Table(messages)) => {
+            for (name, message) in messages {
+                let message = message
+                    .as_str()
+                    .ok_or_else(|| invalid(&format!("`{name}` is not a string for")))?;
+                let message = (message.to_owned(), key_span);
+                match name.as_str() {
+                    "success_message" => options.success_message = message,
+                    "error_message" => options.error_message = message,
+                    "error_message_without_info" => {
+                        options.error_message_without_info = message;
+                        options.custom_error_message_without_info = true;
+                    }
+                    "inner_error_message" => options.inner_error_message = message,
+                    _ => return Err(invalid(&format!("unknown message `{name}` for"))),
+                }
+            }
+        }
+        Some(_) => return Err(invalid("expected a string or a table for")),
+        None => return Err(invalid("couldn't find")),
+    }
+
+    // `include_str!` paths are relative to the current file, so we need an absolute path for tracking
+    options
+        .included_files
+        .push(path.canonicalize().unwrap_or(path));
+    Ok(())
+}
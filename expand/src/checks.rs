@@ -0,0 +1,217 @@
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{spanned::Spanned, Error, ItemFn, ReturnType, Type};
+
+use crate::{
+    format_string,
+    log_statement::Backend,
+    options::{ErrorStyleKind, Options},
+    signature::{attribute_name, result_ok_type},
+};
+
+/// Rejects functions that can't be wrapped, and options that can't be used with the function
+pub fn check_function(options: &Options, input: &ItemFn) -> syn::Result<()> {
+    let is_result = |ty: &Type| matches!(ty, Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident.to_string().contains("Result")));
+    let output_span = match &input.sig.output {
+        ReturnType::Type(_, ty) => ty.span(),
+        ReturnType::Default => input.sig.span(),
+    };
+    if !matches!(&input.sig.output, ReturnType::Type(_, ty) if is_result(ty)) {
+        return Err(Error::new(
+            output_span,
+            "wrap_match currently only supports functions that return `Result`s",
+        ));
+    }
+
+    // the return type is assumed to be `Result` or an alias of it whose last generic argument is the error type
+    if let (true, ReturnType::Type(_, ty)) = (options.strict, &input.sig.output) {
+        if let Type::Path(p) = &**ty {
+            if p.path.segments.last().is_some_and(|s| s.ident != "Result") {
+                return Err(Error::new(
+                    ty.span(),
+                    "wrap_match: this is assumed to be an alias of `Result` whose last generic argument is the error type (this is an error because `strict = true`, use `Result` directly instead)",
+                ));
+            }
+        }
+    }
+
+    if let Some(constness) = &input.sig.constness {
+        return Err(Error::new(
+            constness.span(),
+            "wrap_match cannot be used on const functions because the log crate cannot be used in const contexts",
+        ));
+    }
+
+    if let Some(naked) = input
+        .attrs
+        .iter()
+        .find(|attr| attribute_name(attr).as_deref() == Some("naked"))
+    {
+        return Err(Error::new(
+            naked.span(),
+            "wrap_match cannot be used on naked functions because their bodies can only contain inline assembly",
+        ));
+    }
+
+    if let (true, Some(asyncness)) = (options.messages_use("caller"), &input.sig.asyncness) {
+        return Err(Error::new(
+            asyncness.span(),
+            "wrap_match: `{caller}` cannot be used in async functions because `#[track_caller]` doesn't work with them",
+        ));
+    }
+    if let (Some((_, span)), None) = (options.timeout, &input.sig.asyncness) {
+        return Err(Error::new(
+            span,
+            "wrap_match: `timeout` can only be used on async functions, since sync functions can't be stopped while they're running",
+        ));
+    }
+
+    if options.flatten && !result_ok_type(&input.sig.output).is_some_and(is_result) {
+        return Err(Error::new(
+            output_span,
+            "wrap_match: `flatten` needs the function to return a `Result` of a `Result`",
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects options that can't be used together
+pub fn check_options(options: &Options) -> syn::Result<()> {
+    if options.level_fn.is_some() {
+        for (option, span) in [
+            (
+                "error_level",
+                options.error_level.as_ref().map(|(_, span)| *span),
+            ),
+            (
+                "level_for",
+                options.level_for.as_ref().map(|(_, span)| *span),
+            ),
+            ("level_from_error", options.level_from_error),
+        ] {
+            if let Some(span) = span {
+                return Err(Error::new(
+                    span,
+                    format!("wrap_match: `{option}` cannot be used with `level_fn`"),
+                ));
+            }
+        }
+    }
+
+    if let (Some(span), None) = (options.exhaustive, &options.level_for) {
+        return Err(Error::new(
+            span,
+            "wrap_match: `exhaustive` can only be used with `level_for`, whose variants it checks",
+        ));
+    }
+
+    for (placeholder, enabled) in [
+        ("backtrace", options.backtrace),
+        ("span_trace", options.span_trace),
+    ] {
+        if !enabled && format_string::uses_placeholder(&options.error_message.0, placeholder) {
+            return Err(Error::new(
+                options.error_message.1,
+                format!(
+                    "wrap_match: `{{{placeholder}}}` can only be used when `{placeholder} = true`"
+                ),
+            ));
+        }
+    }
+
+    if let (None, Some((_, span))) = (options.duration_format, options.duration_precision) {
+        return Err(Error::new(
+            span,
+            "wrap_match: `duration_precision` can only be used with `duration_format`",
+        ));
+    }
+    if !options.defer_to_outermost {
+        if let Some(span) = options
+            .chain_max_frames
+            .map(|(_, span)| span)
+            .or(options.chain_order.map(|(_, span)| span))
+        {
+            return Err(Error::new(
+                span,
+                "wrap_match: `chain_max_frames` and `chain_order` can only be used when `defer_to_outermost = true`",
+            ));
+        }
+    }
+
+    if let Some((event_name, span)) = &options.event_name {
+        // tracing needs callsite names at compile time, so only placeholders we know at compile time can be used
+        if event_name.contains('{') {
+            return Err(Error::new(
+                *span,
+                "wrap_match: `event_name` can only use the `{function}` and `{owner}` placeholders",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the crate that the function's messages are logged through. In bridged setups, `log` records are forwarded to `tracing` exactly once.
+pub fn select_backend(options: &Options) -> syn::Result<Backend> {
+    match options.logger {
+        Some((backend, span)) if options.bridge_safe && backend != Backend::Log => Err(Error::new(
+            span,
+            "wrap_match: `bridge_safe` always logs through `log`, so it can only be used with `logger = \"log\"`",
+        )),
+        // `logger = "log"` is already rejected when parsing if the `log` feature is disabled
+        Some((backend, _)) => Ok(backend),
+        None if options.bridge_safe && !cfg!(feature = "log") => Err(Error::new(
+            Span::call_site(),
+            "wrap_match: `bridge_safe` logs through `log`, which requires the `log` feature of wrap-match",
+        )),
+        None if options.bridge_safe => Ok(Backend::Log),
+        None => Ok(Backend::default()),
+    }
+}
+
+/// Returns whether errors are added to the log statements as structured fields, with `error_style = "fields"`
+pub fn error_fields(options: &Options, backend: Backend) -> syn::Result<bool> {
+    match options.error_style {
+        Some((ErrorStyleKind::Fields, span)) if backend != Backend::Tracing => Err(Error::new(
+            span,
+            "wrap_match: `error_style = \"fields\"` needs the `tracing` logger, since `log` and plain messages don't have structured fields (`log_kv` adds key-values to `log` records)",
+        )),
+        Some((ErrorStyleKind::Fields, span)) if options.wide_event => Err(Error::new(
+            span,
+            "wrap_match: `error_style = \"fields\"` can't be used with `wide_event`, which already adds the error as fields",
+        )),
+        Some((ErrorStyleKind::Json, span)) if options.wide_event => Err(Error::new(
+            span,
+            "wrap_match: `error_style = \"json\"` can't be used with `wide_event`, which logs its own record",
+        )),
+        Some((style, _)) => Ok(style == ErrorStyleKind::Fields),
+        None => Ok(false),
+    }
+}
+
+/// Returns the warnings for a function. Proc macros can't emit warnings, so we use deprecated items instead.
+pub fn warnings(options: &Options, backend: Backend, name: &Ident, sites: u32) -> TokenStream2 {
+    // `error_message` is only used for try expressions, so warn if there aren't any
+    let unused_error_message = (sites == 0 && options.custom_error_message).then(|| {
+        quote_spanned! {options.error_message.1=>
+            {
+                #[deprecated(note = "wrap_match: `error_message` is never used because this function has no try expressions (`?`), use `error_message_without_info` instead")]
+                #[allow(non_camel_case_types)]
+                struct error_message_is_never_used;
+                let _ = error_message_is_never_used;
+            }
+        }
+    });
+    // without the `log` and `tracing` features, messages silently went to stderr, even for crates that used `log` before the feature existed and had
+    // `default-features = false`
+    let plain_fallback = (options.logger.is_none() && backend == Backend::Plain).then(|| {
+        quote_spanned! {name.span()=>
+            {
+                #[deprecated(note = "wrap_match: neither the `log` nor the `tracing` feature of wrap-match is enabled, so messages are written to stderr with `eprintln!`. Enable one of them, or use `logger = \"plain\"` if that's what you want")]
+                #[allow(non_camel_case_types)]
+                struct no_logger_feature_enabled;
+                let _ = no_logger_feature_enabled;
+            }
+        }
+    });
+    quote!(#unused_error_message #plain_fallback)
+}
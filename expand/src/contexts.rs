@@ -0,0 +1,213 @@
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::Error;
+
+use crate::{
+    format_string,
+    log_statement::{Backend, LogContext},
+    options::Options,
+    parameters::Parameters,
+};
+
+/// The contexts of a function's log statements
+pub struct Contexts {
+    /// The context every log statement starts with, which timeouts, caught panics and the error in `Ok(Err(...))` (with `flatten`) are logged with
+    pub base: LogContext,
+    pub success: LogContext,
+    /// For errors that came from a try expression, which have its line and expression
+    pub error: LogContext,
+    pub error_without_info: LogContext,
+}
+
+impl Contexts {
+    /// Makes the contexts. With `wide_event` and the `log` backend, this adds the fields to the messages instead, since `log` doesn't have structured
+    /// fields (without an unstable feature).
+    pub fn new(
+        options: &mut Options,
+        name: &Ident,
+        arguments: Vec<TokenStream2>,
+        backend: Backend,
+        parameters: &Parameters,
+        error_fields: bool,
+    ) -> syn::Result<Self> {
+        let base = base_context(options, name, arguments, backend)?;
+        let error = match &options.event_name {
+            Some((event_name, _)) => LogContext {
+                name: Some(quote!(#event_name)),
+                ..base.clone()
+            },
+            None => base.clone(),
+        };
+        let mut contexts = Contexts {
+            success: base.clone(),
+            error_without_info: error.clone(),
+            error,
+            base,
+        };
+        if options.wide_event {
+            contexts.add_wide_event(options, parameters);
+        }
+        if error_fields {
+            contexts.error.fields.extend([
+                quote!(error = ?_wrap_match_error.inner),
+                quote!(error.line = _wrap_match_line),
+                quote!(error.expr = _wrap_match_expr),
+            ]);
+            contexts
+                .error_without_info
+                .fields
+                .push(quote!(error = ?_wrap_match_error.inner));
+        }
+        if options.log_kv {
+            contexts.error.kv.extend([
+                quote!(line = _wrap_match_line),
+                quote!(expr = _wrap_match_expr),
+                quote!(error:? = _wrap_match_error.inner),
+            ]);
+            contexts
+                .error_without_info
+                .kv
+                .push(quote!(error:? = _wrap_match_error.inner));
+        }
+        Ok(contexts)
+    }
+
+    /// Puts everything we know about the call in its one event, for `wide_event`
+    fn add_wide_event(&mut self, options: &mut Options, parameters: &Parameters) {
+        let Parameters { duration, args } = parameters;
+        match self.base.backend {
+            Backend::Tracing => {
+                self.success.fields.extend([
+                    quote!(outcome = "success"),
+                    quote!(duration = ?#duration),
+                    quote!(args = ?#args),
+                ]);
+                self.error_without_info.fields.extend([
+                    quote!(outcome = "failure"),
+                    quote!(duration = ?#duration),
+                    quote!(error = ?_wrap_match_error.inner),
+                    quote!(args = ?#args),
+                ]);
+                self.error.fields.extend([
+                    quote!(outcome = "failure"),
+                    quote!(duration = ?#duration),
+                    quote!(error = ?_wrap_match_error.inner),
+                    quote!(line = _wrap_match_line),
+                    quote!(expr = _wrap_match_expr),
+                    quote!(args = ?#args),
+                ]);
+            }
+            Backend::Log | Backend::Plain => {
+                options
+                    .success_message
+                    .0
+                    .push_str(" outcome=success duration={duration:?} args={args:?}");
+                options.error_message.0.push_str(
+                    " outcome=failure duration={duration:?} error={error:?} line={line} expr={expr:?} args={args:?}",
+                );
+                options.error_message_without_info.0.push_str(
+                    " outcome=failure duration={duration:?} error={error:?} args={args:?}",
+                );
+            }
+        }
+    }
+}
+
+/// Returns the context every log statement of the function starts with
+fn base_context(
+    options: &Options,
+    name: &Ident,
+    arguments: Vec<TokenStream2>,
+    backend: Backend,
+) -> syn::Result<LogContext> {
+    let owner = options.owner.as_ref().map(|(owner, _)| owner);
+    // every wrap-match site gets its own callsite name, so tracing filters can target it
+    let name_str = name.to_string();
+    let callsite_name = quote_spanned! {name.span()=>
+        ::core::concat!("wrap_match ", #name_str, ":", ::core::line!())
+    };
+    // the same syntax works for `tracing` fields and `log` key-values
+    let cold_start_fields = match options.cold_start {
+        Some(_) => vec![
+            quote!(invocation = _wrap_match_invocation),
+            quote!(first_call = _wrap_match_first_call),
+        ],
+        None => vec![],
+    };
+    let target = target(options)?;
+    let static_fields = static_fields(options, backend)?;
+    Ok(LogContext {
+        target,
+        name: Some(callsite_name),
+        arguments,
+        extracted: options
+            .extract
+            .iter()
+            .flat_map(|(placeholders, _)| placeholders.iter().map(ToString::to_string))
+            .collect(),
+        constants: options
+            .message_constants
+            .iter()
+            .map(|(placeholder, constant)| (placeholder.clone(), quote!(#constant)))
+            .collect(),
+        fields: owner
+            .map(|owner| quote!(owner = #owner))
+            .into_iter()
+            .chain(cold_start_fields.clone())
+            .chain(static_fields.clone())
+            .collect(),
+        kv: if options.log_kv {
+            [quote!(function = #name_str)]
+                .into_iter()
+                .chain(owner.map(|owner| quote!(owner = #owner)))
+                .chain(cold_start_fields)
+                .chain(static_fields)
+                .collect()
+        } else {
+            static_fields
+        },
+        backend,
+    })
+}
+
+/// Returns the target of the log statements. Targets have to be known at compile time, but `{module}` can be filled in by `module_path!()`.
+fn target(options: &Options) -> syn::Result<Option<TokenStream2>> {
+    let Some((target, span)) = &options.target else {
+        return Ok(None);
+    };
+    let mut parts = vec![];
+    for piece in format_string::parse(target) {
+        match piece {
+            format_string::Piece::Literal(text) => {
+                let text = text.replace("{{", "{").replace("}}", "}");
+                parts.push(quote!(#text));
+            }
+            format_string::Piece::Placeholder {
+                argument: "module",
+                spec: "",
+            } => parts.push(quote!(::core::module_path!())),
+            format_string::Piece::Placeholder { .. } => {
+                return Err(Error::new(
+                    *span,
+                    "wrap_match: `target` can only use the `{function}`, `{owner}` and `{module}` placeholders",
+                ));
+            }
+        }
+    }
+    Ok(Some(quote!(::core::concat!(#(#parts),*))))
+}
+
+/// Returns the fields added with `fields`. `log` records only have key-values with its `kv` feature.
+fn static_fields(options: &Options, backend: Backend) -> syn::Result<Vec<TokenStream2>> {
+    match &options.fields {
+        Some((_, span)) if backend == Backend::Log && !cfg!(feature = "kv") => Err(Error::new(
+            *span,
+            "wrap_match: `fields` requires the `kv` feature of wrap-match when logging through `log`",
+        )),
+        Some((fields, _)) => Ok(fields
+            .iter()
+            .map(|(key, value)| quote!(#key = #value))
+            .collect()),
+        None => Ok(vec![]),
+    }
+}
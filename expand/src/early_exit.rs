@@ -0,0 +1,125 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+
+use crate::{options::Options, parameters::Parameters};
+
+/// The messages of timeouts and caught panics. They're converted into errors that go through the same pipeline as the function's own errors, but are
+/// logged with their own messages.
+pub struct EarlyExits<'a> {
+    exits: Vec<EarlyExit<'a>>,
+}
+
+struct EarlyExit<'a> {
+    /// The pattern that matches the `wrap_match::__private::EarlyExit` of this kind
+    pattern: TokenStream2,
+    message: &'a (String, Span),
+    parameters: Vec<(&'static str, TokenStream2)>,
+}
+
+impl<'a> EarlyExits<'a> {
+    pub fn new(options: &'a Options, parameters: &Parameters) -> Self {
+        let timeout = options
+            .timeout
+            .and(options.timeout_message.as_ref())
+            .map(|message| EarlyExit {
+                pattern: quote!(::wrap_match::__private::EarlyExit::TimedOut(
+                    _wrap_match_timeout
+                )),
+                message,
+                parameters: parameters.early_exit(("timeout", quote!(_wrap_match_timeout))),
+            });
+        let panic = options
+            .catch_panics
+            .then_some(options.panic_message.as_ref())
+            .flatten()
+            .map(|message| EarlyExit {
+                pattern: quote!(::wrap_match::__private::EarlyExit::Panicked(
+                    _wrap_match_panic
+                )),
+                message,
+                parameters: parameters.early_exit(("panic", quote!(_wrap_match_panic))),
+            });
+        EarlyExits {
+            exits: timeout.into_iter().chain(panic).collect(),
+        }
+    }
+
+    /// Picks the statement for the message of the early exit if the error came from one, which is made from the message and its parameters
+    pub fn select(
+        &self,
+        statement: TokenStream2,
+        early_exit_statement: impl Fn(&(String, Span), &[(&'static str, TokenStream2)]) -> TokenStream2,
+    ) -> TokenStream2 {
+        if self.exits.is_empty() {
+            return statement;
+        }
+        let patterns = self.exits.iter().map(|exit| &exit.pattern);
+        let early_exit_statements = self
+            .exits
+            .iter()
+            .map(|exit| early_exit_statement(exit.message, &exit.parameters));
+        quote! {
+            match &_wrap_match_early_exit {
+                #(::core::option::Option::Some(#patterns) => { #early_exit_statements })*
+                _ => { #statement }
+            }
+        }
+    }
+
+    /// Returns the declaration of the variable that remembers what happened, if there are early exits
+    pub fn declare(&self) -> Option<TokenStream2> {
+        (!self.exits.is_empty()).then(|| {
+            quote!(let mut _wrap_match_early_exit: ::core::option::Option<::wrap_match::__private::EarlyExit> = ::core::option::Option::None;)
+        })
+    }
+}
+
+/// Wraps the call of the inner function so timeouts and panics become errors of the function, remembering what happened so they're logged with
+/// their own messages. This also awaits the call of async functions.
+pub fn catch_early_exits(
+    options: &Options,
+    function: &str,
+    call: TokenStream2,
+    is_async: bool,
+) -> TokenStream2 {
+    // panics are caught inside the timeout, so they're handled after it
+    let call = match (options.catch_panics, is_async) {
+        // like the inline body, the closure returns the inner function's result, so its size doesn't matter
+        (true, false) => quote! {{
+            #[allow(clippy::result_large_err)]
+            let _wrap_match_call = || #call;
+            ::wrap_match::__private::catch_panic(#function, _wrap_match_call)
+        }},
+        (true, true) => quote!(::wrap_match::__private::catch_panic_async(#function, #call)),
+        (false, _) => call,
+    };
+    let call = match options.timeout {
+        Some((millis, _)) => quote! {
+            match ::wrap_match::__private::timeout(#function, ::core::time::Duration::from_millis(#millis), #call).await {
+                Ok(_wrap_match_result) => _wrap_match_result,
+                Err(_wrap_match_timed_out) => {
+                    _wrap_match_early_exit = ::core::option::Option::Some(
+                        ::wrap_match::__private::EarlyExit::TimedOut(_wrap_match_timed_out.after()),
+                    );
+                    Err(::wrap_match::__private::early_exit_error(_wrap_match_timed_out))
+                }
+            }
+        },
+        None if is_async => quote!(#call.await),
+        None => call,
+    };
+    if !options.catch_panics {
+        return call;
+    }
+    quote! {
+        match #call {
+            Ok(_wrap_match_result) => _wrap_match_result,
+            Err(_wrap_match_panicked) => {
+                _wrap_match_early_exit = ::core::option::Option::Some(
+                    ::wrap_match::__private::EarlyExit::Panicked(_wrap_match_panicked.message().to_owned()),
+                );
+                Err(::wrap_match::__private::early_exit_error(_wrap_match_panicked))
+            }
+        }
+    }
+}
@@ -0,0 +1,535 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::Expr;
+
+use crate::{
+    early_exit::EarlyExits,
+    emit_in_order, format_string,
+    log_statement::{build_log_statement, build_panic_statement, custom_placeholders, LogContext},
+    options::{Options, SentryReport},
+    parameters::{self, Parameters},
+    probes::ErrorProbes,
+    Wrapper,
+};
+
+/// The code that handles the function's errors
+pub struct ErrorHandling {
+    /// Checks that extractors are registered for the custom placeholders of the error messages, with `self_check`
+    pub self_check: Option<TokenStream2>,
+    /// Enters the function's frame, with `defer_to_outermost`
+    pub enter_frame: Option<TokenStream2>,
+    /// The match arm for errors of the call
+    pub err_arm: TokenStream2,
+}
+
+/// One way an error can be logged: with the line and expression of the try expression it came from, or without them
+struct ErrorBranch<'a> {
+    message: (String, Span),
+    parameters: Vec<(&'static str, TokenStream2)>,
+    context: &'a LogContext,
+    /// The compact message that is also logged with `dual_output`
+    machine_message: String,
+    /// The message without its `{?chain}` sections, for errors that didn't propagate through other functions (with `defer_to_outermost`)
+    message_without_chain: Option<(String, Span)>,
+}
+
+/// Returns the code that handles the function's errors. Both ways an error can be logged go through the same pipeline, so every error-path feature
+/// applies to both of them.
+pub fn handle_error(
+    wrapper: &Wrapper,
+    early_exits: &EarlyExits,
+    enter_span_for_logs: Option<&TokenStream2>,
+) -> ErrorHandling {
+    let options = &wrapper.options;
+    let function = wrapper.name.to_string();
+    let branches = error_branches(wrapper);
+    let self_check = options
+        .self_check
+        .then(|| self_check(&function, &branches))
+        .flatten();
+    let panic_on_error = options
+        .panic_on_error
+        .then(|| panic_on_error(wrapper, &branches, early_exits));
+    let log_error = log_error(wrapper, branches, early_exits);
+    let (enter_frame, log_error) = defer(options, &function, log_error);
+    let log_error = (!options.log_to_reporter_only()).then_some(log_error);
+
+    let err_arm = if wrapper.infallible {
+        quote! {
+            Err(_wrap_match_error) => match _wrap_match_error.inner {}
+        }
+    } else {
+        let error = quote!(&_wrap_match_error.inner);
+        let probes = &wrapper.probes;
+        // the metrics counter is incremented whether or not the error is logged
+        let count_error = options.metrics.then(|| {
+            let error_type = probes.type_name(&error);
+            quote!(::wrap_match::__private::count_error(#function, #error_type);)
+        });
+        let report_error = options.reporter.is_some().then(|| {
+            report_error(
+                wrapper,
+                &error,
+                probes,
+                quote!(_wrap_match_error.line_and_expr),
+                quote!(_wrap_match_error.location),
+            )
+        });
+        let record_otel_error = record_otel_error(wrapper);
+        let report_sentry_error = report_sentry_error(wrapper);
+        let emit_error = emit_in_order(
+            options.emit_order.as_ref().map(|(order, _)| order),
+            &log_error,
+            &count_error,
+            &quote!(#record_otel_error #report_sentry_error #report_error),
+        );
+        let err = error_value(wrapper, panic_on_error);
+        quote! {
+            Err(_wrap_match_error) => {
+                #enter_span_for_logs
+                #emit_error
+                #err
+            }
+        }
+    };
+    ErrorHandling {
+        self_check,
+        enter_frame,
+        err_arm,
+    }
+}
+
+/// Returns the statement that gives an error to the reporter set with `wrap_match::set_reporter`, if the function opted in
+pub fn report_error(
+    wrapper: &Wrapper,
+    error: &TokenStream2,
+    probes: &ErrorProbes,
+    line_and_expr: TokenStream2,
+    location: TokenStream2,
+) -> TokenStream2 {
+    let function = wrapper.name.to_string();
+    let error_probes = probes.imports();
+    let error_type = probes.type_name(error);
+    let duration = &wrapper.parameters.duration;
+    quote! {{
+        #[allow(unused_imports)]
+        use ::wrap_match::__private::{#error_probes};
+        ::wrap_match::__private::report_error(
+            #function,
+            ::core::module_path!(),
+            #error_type,
+            (&::wrap_match::__private::ErrorProbe(#error)).dyn_error(),
+            (&::wrap_match::__private::ErrorProbe(#error)).debug_string(),
+            #line_and_expr,
+            #location,
+            #duration,
+        );
+    }}
+}
+
+/// Returns the record for `error_style = "json"`, which is the whole error message
+pub fn json_record(
+    wrapper: &Wrapper,
+    error: &TokenStream2,
+    probes: &ErrorProbes,
+    location: TokenStream2,
+    expr: TokenStream2,
+    chain: TokenStream2,
+) -> TokenStream2 {
+    let function = wrapper.name.to_string();
+    let error_probes = probes.imports();
+    let error_type = probes.type_name(error);
+    quote! {{
+        #[allow(unused_imports)]
+        use ::wrap_match::__private::{#error_probes};
+        ::wrap_match::__private::JsonRecord {
+            function: #function,
+            module: ::core::module_path!(),
+            location: #location,
+            expr: #expr,
+            error_type: #error_type,
+            error: (&::wrap_match::__private::ErrorProbe(#error)).debug_string(),
+            display: (&::wrap_match::__private::ErrorProbe(#error))
+                .dyn_error()
+                .map(::std::string::ToString::to_string),
+            chain: #chain,
+        }
+    }}
+}
+
+fn error_branches(wrapper: &Wrapper) -> [ErrorBranch<'_>; 2] {
+    let options = &wrapper.options;
+    let function = &wrapper.name;
+    let parameters = error_parameters(wrapper);
+    let parameters_with_info = [
+        ("line", quote!(_wrap_match_line)),
+        ("expr", quote!(_wrap_match_expr)),
+        (
+            "backtrace",
+            quote!(::wrap_match::__private::CapturedBacktrace(
+                _wrap_match_error.backtrace.as_ref()
+            )),
+        ),
+    ]
+    .into_iter()
+    // the field only exists with the `span_trace` feature, which `span_trace = true` requires
+    .chain(options.span_trace.then(|| {
+        (
+            "span_trace",
+            quote!(::wrap_match::__private::CapturedSpanTrace(
+                _wrap_match_error.span_trace.as_ref()
+            )),
+        )
+    }))
+    .chain(parameters.clone())
+    .collect();
+    let mut branches = [
+        ErrorBranch {
+            message: options.error_message.clone(),
+            parameters: parameters_with_info,
+            context: &wrapper.contexts.error,
+            machine_message: format!(
+                "function={function} line={{line}} expr={{expr:?}} error={{error:?}}"
+            ),
+            message_without_chain: None,
+        },
+        ErrorBranch {
+            message: options.error_message_without_info.clone(),
+            parameters,
+            context: &wrapper.contexts.error_without_info,
+            machine_message: format!("function={function} error={{error:?}}"),
+            message_without_chain: None,
+        },
+    ];
+    if options.defer_to_outermost {
+        for branch in &mut branches {
+            add_chain(branch, options.json_errors());
+        }
+    }
+    branches
+}
+
+/// Returns the parameters of the error messages, besides the line and expression info
+fn error_parameters(wrapper: &Wrapper) -> Vec<(&'static str, TokenStream2)> {
+    let options = &wrapper.options;
+    let Parameters { args, .. } = &wrapper.parameters;
+    let probes = &wrapper.probes;
+    let error = quote!(&_wrap_match_error.inner);
+    let dyn_error = probes.dyn_error(&error);
+    let function_location = &wrapper.function_location;
+    let location = quote!(_wrap_match_error.location.unwrap_or(#function_location));
+    let json_record = options.json_errors().then(|| {
+        (
+            "json",
+            json_record(
+                wrapper,
+                &error,
+                probes,
+                location.clone(),
+                quote!(_wrap_match_error.line_and_expr.map(|(_, expr)| expr)),
+                // the chain goes in the record, since appending it to the message would make it invalid JSON
+                if options.defer_to_outermost {
+                    quote!(Some(&_wrap_match_chain))
+                } else {
+                    quote!(None)
+                },
+            ),
+        )
+    });
+    [
+        ("error", quote!(_wrap_match_error.inner)),
+        ("error_type", probes.type_name(&error)),
+        (
+            "variant",
+            quote!(::wrap_match::VariantName::variant_name(
+                &_wrap_match_error.inner
+            )),
+        ),
+    ]
+    .into_iter()
+    .chain(wrapper.parameters.call())
+    .chain([
+        ("location", location),
+        (
+            "sources",
+            quote!(::wrap_match::__private::Sources(#dyn_error)),
+        ),
+        (
+            "root_cause",
+            quote!(::wrap_match::__private::root_cause(#dyn_error)),
+        ),
+        ("args", args.clone()),
+    ])
+    .chain(parameters::context())
+    .chain(json_record)
+    .collect()
+}
+
+/// Adds the functions the error propagated through to a branch, with `defer_to_outermost`
+fn add_chain(branch: &mut ErrorBranch, json_errors: bool) {
+    // only `{?chain}` sections are left, which are left out when the error didn't propagate through other functions
+    if format_string::has_sections(&branch.message.0) {
+        let (message, span) = &branch.message;
+        let resolve = |keep: bool| {
+            format_string::resolve_sections(message, |_| keep)
+                .expect("sections are checked when the other sections are resolved")
+        };
+        branch.message_without_chain = Some((resolve(false), *span));
+        branch.message.0 = resolve(true);
+    }
+    branch.parameters.extend([
+        ("chain", quote!(_wrap_match_chain)),
+        (
+            "_wrap_match_chain",
+            quote!(::wrap_match::__private::ChainSuffix(&_wrap_match_chain)),
+        ),
+    ]);
+    // the consolidated message mentions the functions the error propagated through, unless the message already uses `{chain}` or is a JSON record,
+    // which has the chain as a key
+    if !json_errors && !format_string::uses_placeholder(&branch.message.0, "chain") {
+        branch.message.0.push_str("{_wrap_match_chain}");
+    }
+}
+
+/// Returns the check for `self_check`. Messages are checked when compiling, except for custom placeholders, which need an extractor to be
+/// registered at runtime.
+fn self_check(function: &str, branches: &[ErrorBranch]) -> Option<TokenStream2> {
+    let mut placeholders = vec![];
+    for branch in branches {
+        for placeholder in
+            custom_placeholders(&branch.message.0, &branch.parameters, branch.context)
+        {
+            if !placeholders.contains(&placeholder) {
+                placeholders.push(placeholder);
+            }
+        }
+    }
+    (!placeholders.is_empty()).then(|| {
+        quote! {
+            #[cfg(debug_assertions)]
+            {
+                static CHECKED: ::wrap_match::runtime::Flag = ::wrap_match::runtime::Flag::new();
+                if CHECKED.set() {
+                    ::wrap_match::__private::check_extractors(#function, &[#(#placeholders),*]);
+                }
+            }
+        }
+    })
+}
+
+/// Returns the statement for `panic_on_error`, which makes the function panic with the message it logged instead of returning the error
+fn panic_on_error(
+    wrapper: &Wrapper,
+    branches: &[ErrorBranch; 2],
+    early_exits: &EarlyExits,
+) -> TokenStream2 {
+    let [panic_with_info, panic_without_info] = branches.each_ref().map(|branch| {
+        build_panic_statement(
+            &branch.message,
+            &branch.parameters,
+            Some(quote!(_wrap_match_error.inner)),
+            branch.context,
+        )
+    });
+    early_exits.select(
+        quote! {
+            if let Some((_wrap_match_line, _wrap_match_expr)) = _wrap_match_error.line_and_expr {
+                #panic_with_info
+            } else {
+                #panic_without_info
+            }
+        },
+        |message, parameters| {
+            build_panic_statement(message, parameters, None, &wrapper.contexts.base)
+        },
+    )
+}
+
+fn log_error(
+    wrapper: &Wrapper,
+    branches: [ErrorBranch; 2],
+    early_exits: &EarlyExits,
+) -> TokenStream2 {
+    let error_level = &wrapper.error_level;
+    let [log_error_with_info, log_error_without_info] = branches.map(|branch| {
+        let log_message = |message| {
+            build_log_statement(
+                message,
+                &branch.parameters,
+                Some(quote!(_wrap_match_error.inner)),
+                error_level,
+                branch.context,
+            )
+        };
+        let mut log_error = log_message(&branch.message);
+        if let Some(message_without_chain) = &branch.message_without_chain {
+            let log_error_without_chain = log_message(message_without_chain);
+            log_error = quote! {
+                if _wrap_match_chain.is_empty() {
+                    #log_error_without_chain
+                } else {
+                    #log_error
+                }
+            };
+        }
+        if wrapper.options.dual_output {
+            // a compact line that is easy for machines to parse, in addition to the normal message
+            log_error.extend(build_log_statement(
+                &(branch.machine_message, Span::call_site()),
+                &branch.parameters,
+                None,
+                error_level,
+                &LogContext {
+                    target: Some(quote!("wrap_match::machine")),
+                    ..branch.context.clone()
+                },
+            ));
+        }
+        log_error
+    });
+    early_exits.select(
+        quote! {
+            if let Some((_wrap_match_line, _wrap_match_expr)) = _wrap_match_error.line_and_expr {
+                #log_error_with_info
+            } else {
+                #log_error_without_info
+            }
+        },
+        |message, parameters| {
+            build_log_statement(
+                message,
+                parameters,
+                None,
+                error_level,
+                &wrapper.contexts.base,
+            )
+        },
+    )
+}
+
+/// Returns the statement that enters the function's frame and the statement that logs the error with `defer_to_outermost`, which only logs it in
+/// the outermost function, with the functions it propagated through
+fn defer(
+    options: &Options,
+    function: &str,
+    log_error: TokenStream2,
+) -> (Option<TokenStream2>, TokenStream2) {
+    if !options.defer_to_outermost {
+        return (None, log_error);
+    }
+    let max_frames = match options.chain_max_frames {
+        Some((max_frames, _)) => quote!(Some(#max_frames)),
+        None => quote!(None),
+    };
+    let outermost_first = options
+        .chain_order
+        .is_some_and(|(outermost_first, _)| outermost_first);
+    (
+        Some(quote!(let _wrap_match_frame = ::wrap_match::__private::DeferFrame::enter();)),
+        quote! {
+            if _wrap_match_frame.is_outermost() {
+                let _wrap_match_chain = _wrap_match_frame.take_chain(#max_frames, #outermost_first);
+                #log_error
+            } else {
+                _wrap_match_frame.defer(#function, _wrap_match_error.line_and_expr.map(|(line, _)| line));
+            }
+        },
+    )
+}
+
+/// Returns what the function returns (or does) instead of the error
+fn error_value(wrapper: &Wrapper, panic_on_error: Option<TokenStream2>) -> Option<TokenStream2> {
+    let options = &wrapper.options;
+    if options.panic_on_error {
+        panic_on_error
+    } else if let Some(or_else) = &options.or_else {
+        Some(fallback(or_else))
+    } else if let Some((code, _)) = options.exit_on_error {
+        let exit_status = exit_status(wrapper);
+        Some(quote! {
+            ::wrap_match::__private::exit(#exit_status.map_or(#code, ::core::convert::From::from))
+        })
+    } else if options.exit_code {
+        let exit_status = exit_status(wrapper);
+        Some(quote! {
+            #exit_status.map_or(::std::process::ExitCode::FAILURE, ::std::process::ExitCode::from)
+        })
+    } else if options.ok_or_default {
+        Some(quote!(::core::default::Default::default()))
+    } else if options.ok_or_none {
+        Some(quote!(None))
+    } else if !options.disregard_result {
+        Some(quote!(Err(_wrap_match_error.inner)))
+    } else {
+        None
+    }
+}
+
+/// Returns the fallback value that replaces the error with `or_else`, which closures make from the error
+fn fallback(or_else: &Expr) -> TokenStream2 {
+    match or_else {
+        Expr::Closure(closure) if closure.inputs.is_empty() => quote!(Ok((#closure)())),
+        Expr::Closure(closure) => {
+            quote!(Ok(::wrap_match::__private::or_else(_wrap_match_error.inner, #closure)))
+        }
+        value => quote!(Ok(#value)),
+    }
+}
+
+/// Returns the status code the process exits with, which error types can choose by implementing `ExitStatus`
+fn exit_status(wrapper: &Wrapper) -> TokenStream2 {
+    if wrapper.probes.is_type_param {
+        quote!(::core::option::Option::<u8>::None)
+    } else {
+        quote! {{
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{ExitStatusProbe as _, NoExitStatusProbe as _};
+            (&::wrap_match::__private::ErrorProbe(&_wrap_match_error.inner)).exit_status()
+        }}
+    }
+}
+
+/// Returns the statement that records the error on the active OpenTelemetry span, whether or not it's logged
+fn record_otel_error(wrapper: &Wrapper) -> Option<TokenStream2> {
+    cfg!(feature = "otel").then(|| {
+        let function = wrapper.name.to_string();
+        let error_type = wrapper.probes.type_name(&quote!(&_wrap_match_error.inner));
+        let debug_probe = if wrapper.probes.is_type_param {
+            quote!(NoDebugProbe as _)
+        } else {
+            quote!(DebugProbe as _, NoDebugProbe as _)
+        };
+        quote! {{
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{#debug_probe};
+            ::wrap_match::__private::record_otel_error(
+                #function,
+                #error_type,
+                (&::wrap_match::__private::ErrorProbe(&_wrap_match_error.inner)).debug_string(),
+                _wrap_match_error.line_and_expr,
+            );
+        }}
+    })
+}
+
+/// Returns the statement that reports the error to Sentry, with `sentry`
+fn report_sentry_error(wrapper: &Wrapper) -> Option<TokenStream2> {
+    wrapper.options.sentry.map(|report| {
+        let function = wrapper.name.to_string();
+        let error_probes = wrapper.probes.imports();
+        let error_type = wrapper.probes.type_name(&quote!(&_wrap_match_error.inner));
+        let breadcrumb = report == SentryReport::Breadcrumb;
+        quote! {{
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{#error_probes};
+            ::wrap_match::__private::report_sentry_error(
+                #function,
+                #error_type,
+                (&::wrap_match::__private::ErrorProbe(&_wrap_match_error.inner)).dyn_error(),
+                (&::wrap_match::__private::ErrorProbe(&_wrap_match_error.inner)).debug_string(),
+                _wrap_match_error.line_and_expr,
+                #breadcrumb,
+            );
+        }}
+    })
+}
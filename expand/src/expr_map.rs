@@ -0,0 +1,161 @@
+//! Hashed expressions, for `expr_privacy = "hash"`.
+//!
+//! The macros don't write anything while compiling, so the map from hashes back to expressions is made separately by [`expr_map`], which finds the
+//! functions that hash their expressions in the source files and hashes their expressions the same way. Each line of the map is the hash, the function
+//! and the expression (with newlines escaped), separated by tabs.
+
+use std::{fs, io, path::Path};
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+use syn::{fold::Fold, parse_quote, visit::Visit, Attribute, ImplItemFn, ItemFn, Lit, TraitItemFn};
+
+use crate::add_error_info::{add_tail_try, AddErrorInfo};
+
+/// Returns a short hash of an expression that stays the same across builds (unlike `std`'s hashers, which aren't guaranteed to be stable)
+pub fn hash_expr(function: &str, expr: &str) -> String {
+    // FNV-1a
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in function.bytes().chain([0]).chain(expr.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:08x}", (hash >> 32) ^ (hash & 0xffff_ffff))
+}
+
+/// Returns the map from hashes back to expressions for the functions with `expr_privacy = "hash"` in the Rust files in a directory (and its
+/// subdirectories), as tab-separated lines: the hash, the function and the expression. This is meant to be called from a build script, like this in
+/// its `main`:
+///
+/// ```no_run
+/// println!("cargo:rerun-if-changed=src");
+/// let map = wrap_match_expand::expr_map("src").unwrap();
+/// std::fs::write("wrap-match-expr-map.tsv", map).unwrap();
+/// ```
+///
+/// Only functions that are written in the files are found, not ones that other macros generate. Files that aren't valid Rust are an error.
+pub fn expr_map(dir: impl AsRef<Path>) -> io::Result<String> {
+    let mut map = String::new();
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    // the order of `read_dir` depends on the platform, and the map shouldn't
+    entries.sort_by_key(fs::DirEntry::path);
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            map.push_str(&expr_map(&path)?);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            let file = syn::parse_file(&fs::read_to_string(&path)?).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?;
+            let mut visitor = FindHashedFunctions(&mut map);
+            visitor.visit_file(&file);
+        }
+    }
+    Ok(map)
+}
+
+/// Adds the hashed expressions of the functions it visits to the map
+struct FindHashedFunctions<'a>(&'a mut String);
+
+impl FindHashedFunctions<'_> {
+    fn add(&mut self, attrs: &[Attribute], function: impl ToTokens) {
+        let Some(args) = attrs.iter().find_map(wrap_match_args) else {
+            return;
+        };
+        let (hash_expressions, redact_literals, tail_result) = hashing_options(args);
+        if !hash_expressions {
+            return;
+        }
+        let Ok(mut function) = syn::parse2::<ItemFn>(function.into_token_stream()) else {
+            return;
+        };
+        // the same as the macro, apart from what doesn't change the expressions' text
+        let mut add_error_info = AddErrorInfo {
+            backtrace: None,
+            function: function.sig.ident.to_string(),
+            lifetime: parse_quote!('_),
+            sites: 0,
+            skipped: vec![],
+            redact_literals,
+            span_trace: false,
+            hash_expressions,
+            hashed: vec![],
+        };
+        if tail_result {
+            add_tail_try(&mut function.block);
+        }
+        add_error_info.fold_item_fn(function);
+        for (hash, expr) in add_error_info.hashed {
+            self.0.push_str(&format!(
+                "{hash}\t{}\t{}\n",
+                add_error_info.function,
+                expr.replace('\n', "\\n")
+            ));
+        }
+    }
+}
+
+impl Visit<'_> for FindHashedFunctions<'_> {
+    fn visit_item_fn(&mut self, i: &ItemFn) {
+        self.add(&i.attrs, i);
+        syn::visit::visit_item_fn(self, i);
+    }
+
+    fn visit_impl_item_fn(&mut self, i: &ImplItemFn) {
+        self.add(&i.attrs, i);
+        syn::visit::visit_impl_item_fn(self, i);
+    }
+
+    fn visit_trait_item_fn(&mut self, i: &TraitItemFn) {
+        if i.default.is_some() {
+            self.add(&i.attrs, i);
+        }
+        syn::visit::visit_trait_item_fn(self, i);
+    }
+}
+
+/// Returns the options of a `wrap_match` attribute
+fn wrap_match_args(attr: &Attribute) -> Option<TokenStream2> {
+    let is_wrap_match = attr
+        .path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "wrap_match");
+    match &attr.meta {
+        syn::Meta::List(list) if is_wrap_match => Some(list.tokens.clone()),
+        syn::Meta::Path(_) if is_wrap_match => Some(TokenStream2::new()),
+        _ => None,
+    }
+}
+
+/// Returns the options that change how expressions are hashed: `expr_privacy = "hash"`, `redact_literals` and `tail_result`. They are looked up on
+/// their own instead of parsing all options, since some options only parse with features of this crate that a build script might not enable.
+fn hashing_options(args: TokenStream2) -> (bool, bool, bool) {
+    let (mut hash_expressions, mut redact_literals, mut tail_result) = (false, false, false);
+    let tokens: Vec<_> = args.into_iter().collect();
+    for option in
+        tokens.split(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','))
+    {
+        let [TokenTree::Ident(name), TokenTree::Punct(eq), value] = option else {
+            continue;
+        };
+        if eq.as_char() != '=' {
+            continue;
+        }
+        // `true` and `false` are identifiers, not literals
+        let is_true = matches!(value, TokenTree::Ident(value) if value == "true");
+        match (name.to_string().as_str(), value) {
+            ("expr_privacy", TokenTree::Literal(value)) => {
+                hash_expressions =
+                    matches!(Lit::new(value.clone()), Lit::Str(value) if value.value() == "hash");
+            }
+            ("redact_literals", _) => redact_literals = is_true,
+            ("tail_result", _) => tail_result = is_true,
+            _ => {}
+        }
+    }
+    (hash_expressions, redact_literals, tail_result)
+}
@@ -0,0 +1,241 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, Attribute, Data, DeriveInput, Error, LitBool, LitStr, Path};
+
+use crate::{
+    log_statement::{Backend, Level},
+    options::Options,
+};
+
+/// Implements `wrap_match::WrapMatchLevel` for an enum
+pub fn derive_wrap_match_level(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "wrap_match: `WrapMatchLevel` can only be derived for enums",
+        ));
+    };
+
+    // variants without a level use the enum's level, unless every variant has to have its own
+    let attributes = parse_attributes(&input.attrs, true)?;
+    let default_level = attributes.level.unwrap_or_else(|| quote!(Error));
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut variants = vec![];
+    let mut missing: Option<Error> = None;
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let level = match parse_attributes(&variant.attrs, false)?.level {
+            Some(level) => level,
+            None if attributes.exhaustive => {
+                let error = Error::new(
+                    ident.span(),
+                    "wrap_match: this variant needs a level, like `#[wrap_match(level = \"error\")]`, since the enum has `#[wrap_match(exhaustive = true)]`",
+                );
+                match &mut missing {
+                    Some(errors) => errors.combine(error),
+                    None => missing = Some(error),
+                }
+                continue;
+            }
+            None => default_level.clone(),
+        };
+        variants.push(quote!(Self::#ident { .. } => ::wrap_match::Level::#level));
+    }
+    if let Some(errors) = missing {
+        return Err(errors);
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::wrap_match::WrapMatchLevel for #name #ty_generics #where_clause {
+            fn level(&self) -> ::wrap_match::Level {
+                match *self {
+                    #(#variants,)*
+                }
+            }
+        }
+    })
+}
+
+/// The arguments of `#[wrap_match(...)]` on an enum or one of its variants
+struct Attributes {
+    /// The name of the `wrap_match::Level` variant, from `level = "warn"`
+    level: Option<TokenStream2>,
+    /// Whether every variant needs its own level, from `exhaustive = true` (only on the enum)
+    exhaustive: bool,
+}
+
+/// Parses `#[wrap_match(level = "warn")]`, and `#[wrap_match(exhaustive = true)]` if the attributes are the enum's
+fn parse_attributes(attrs: &[Attribute], on_enum: bool) -> syn::Result<Attributes> {
+    let mut attributes = Attributes {
+        level: None,
+        exhaustive: false,
+    };
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("wrap_match"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if on_enum && meta.path.is_ident("exhaustive") {
+                let value: LitBool = meta.value()?.parse()?;
+                attributes.exhaustive = value.value();
+                return Ok(());
+            }
+            if !meta.path.is_ident("level") {
+                return Err(meta.error(if on_enum {
+                    "wrap_match: unknown attribute (expected `level` or `exhaustive`)"
+                } else {
+                    "wrap_match: unknown attribute (expected `level`)"
+                }));
+            }
+            let value: LitStr = meta.value()?.parse()?;
+            let variant = match value.value().as_str() {
+                "trace" => "Trace",
+                "debug" => "Debug",
+                "info" => "Info",
+                "warn" => "Warn",
+                "error" => "Error",
+                _ => {
+                    return Err(Error::new(
+                        value.span(),
+                        "wrap_match: expected `trace`, `debug`, `info`, `warn` or `error`",
+                    ))
+                }
+            };
+            let variant = format_ident!("{variant}");
+            attributes.level = Some(quote!(#variant));
+            Ok(())
+        })?;
+    }
+    Ok(attributes)
+}
+
+/// Returns the level errors are logged at. With `level_from_error`, error types can choose their own level by implementing `WrapMatchLevel`, otherwise
+/// `error_level` is used. This is opt-in since a level that's only known at runtime needs a `tracing` callsite for every level.
+pub fn error_level(
+    options: &Options,
+    backend: Backend,
+    error_type: Option<&Path>,
+) -> syn::Result<Level> {
+    if backend == Backend::Plain {
+        reject_levels(options)?;
+        return Ok(Level::Static(quote!(error)));
+    }
+    let default_level = default_error_level(options, backend);
+    Ok(match (&options.level_fn, &options.level_for) {
+        (Some(level_fn), _) => Level::Dynamic(quote!(#level_fn(&_wrap_match_error.inner))),
+        (None, Some((levels, span))) => level_for(
+            levels,
+            *span,
+            options.exhaustive,
+            error_type,
+            backend,
+            &default_level,
+        )?,
+        (None, None) if options.level_from_error.is_some() => Level::Dynamic(default_level),
+        (None, None) => {
+            Level::Static(format_ident!("{}", options.error_level_name()).into_token_stream())
+        }
+    })
+}
+
+/// Rejects the options that pick levels when logging plain messages, since they don't have levels
+fn reject_levels(options: &Options) -> syn::Result<()> {
+    for (option, span) in [
+        ("level_fn", options.level_fn.as_ref().map(Spanned::span)),
+        (
+            "level_for",
+            options.level_for.as_ref().map(|(_, span)| *span),
+        ),
+        ("level_from_error", options.level_from_error),
+    ] {
+        if let Some(span) = span {
+            return Err(Error::new(
+                span,
+                format!("wrap_match: `{option}` can't be used with `logger = \"plain\"`, since plain messages don't have levels"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the level of errors that `level_for` doesn't pick a level for
+fn default_error_level(options: &Options, backend: Backend) -> TokenStream2 {
+    let configured_level = level_value(options.error_level_name(), backend);
+    if options.level_from_error.is_none() {
+        return configured_level;
+    }
+    let [error, warn, info, debug, trace] =
+        ["error", "warn", "info", "debug", "trace"].map(|level| level_value(level, backend));
+    quote! {{
+        #[allow(unused_imports)]
+        use ::wrap_match::__private::{LevelProbe as _, NoLevelProbe as _};
+        match (&::wrap_match::__private::ErrorProbe(&_wrap_match_error.inner)).wrap_match_level() {
+            ::core::option::Option::Some(::wrap_match::Level::Error) => #error,
+            ::core::option::Option::Some(::wrap_match::Level::Warn) => #warn,
+            ::core::option::Option::Some(::wrap_match::Level::Info) => #info,
+            ::core::option::Option::Some(::wrap_match::Level::Debug) => #debug,
+            ::core::option::Option::Some(::wrap_match::Level::Trace) => #trace,
+            ::core::option::Option::None => #configured_level,
+        }
+    }}
+}
+
+/// Returns the level picked by matching the error against the variants given with `level_for`. With `exhaustive = true`, there's no arm for the other
+/// variants, so the match doesn't compile if a variant is missing.
+fn level_for(
+    levels: &[(Path, String)],
+    span: Span,
+    exhaustive: Option<Span>,
+    error_type: Option<&Path>,
+    backend: Backend,
+    default_level: &TokenStream2,
+) -> syn::Result<Level> {
+    let mut arms = vec![];
+    for (variant, level) in levels {
+        let pattern = match (error_type, variant.get_ident()) {
+            (Some(error_type), Some(variant)) => quote!(#error_type::#variant),
+            (None, Some(_)) => {
+                return Err(Error::new(
+                    variant.span(),
+                    "wrap_match: the error type couldn't be determined, so `level_for` needs the full path of the variant",
+                ));
+            }
+            (_, None) => quote!(#variant),
+        };
+        let level = level_value(level, backend);
+        arms.push(quote_spanned!(span=> #pattern { .. } => #level,));
+    }
+    let (error, other_variants) = match exhaustive {
+        // the error about the missing variants points at the option
+        Some(span) => (quote_spanned!(span=> &_wrap_match_error.inner), None),
+        None => (
+            quote!(&_wrap_match_error.inner),
+            Some(quote!(_ => #default_level,)),
+        ),
+    };
+    Ok(Level::Dynamic(quote! {{
+        // the patterns have to work for all kinds of variants
+        #[allow(unreachable_patterns, unknown_lints, clippy::unneeded_struct_pattern)]
+        let level = match #error {
+            #(#arms)*
+            #other_variants
+        };
+        level
+    }}))
+}
+
+/// Returns the level type of the backend (`log::Level` or `tracing::Level`) for the name of a level's macro, like `warn`, for dynamic levels
+fn level_value(level: &str, backend: Backend) -> TokenStream2 {
+    match backend {
+        Backend::Tracing => {
+            let level = format_ident!("{}", level.to_uppercase());
+            quote!(::wrap_match::__log::tracing::Level::#level)
+        }
+        // plain messages don't have levels, so this is never used
+        Backend::Log | Backend::Plain => {
+            let level = format_ident!("{}{}", level[..1].to_uppercase(), &level[1..]);
+            quote!(::wrap_match::__log::log::Level::#level)
+        }
+    }
+}
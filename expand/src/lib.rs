@@ -0,0 +1,426 @@
+//! The expansion of the `wrap-match` macros. `wrap-match-impl` calls these from its procedural macros, but they only use `proc_macro2`, so they can also
+//! be called outside of a procedural macro, for example to snapshot test the expansion:
+//!
+//! ```
+//! let expanded = wrap_match_expand::wrap_match(
+//!     quote::quote!(log_success = false),
+//!     quote::quote! {
+//!         fn my_function() -> Result<(), ()> {
+//!             Ok(())
+//!         }
+//!     },
+//! );
+//! assert!(expanded.to_string().contains("fn my_function"));
+//! ```
+//!
+//! The output is the same as the macros', including compile errors (as `compile_error!` invocations). Locations of try expressions are always file
+//! locations, since there is no source file to check outside of a procedural macro. The version of this crate always matches `wrap-match`.
+//!
+//! Please see <https://docs.rs/wrap-match> for how to use the macros.
+
+#![allow(
+    clippy::enum_glob_use,
+    clippy::match_bool,
+    clippy::if_not_else,
+    clippy::module_name_repetitions,
+    clippy::needless_pass_by_value,
+    clippy::implicit_clone
+)]
+
+extern crate proc_macro;
+
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::{DeriveInput, Error, ItemFn, ReturnType};
+
+mod add_error_info;
+#[cfg(feature = "catalog")]
+mod catalog;
+use self::add_error_info::{add_error_info, source_location};
+
+mod checks;
+
+mod contexts;
+use self::contexts::Contexts;
+
+mod early_exit;
+use self::early_exit::{catch_early_exits, EarlyExits};
+
+mod error;
+use self::error::handle_error;
+
+mod expr_map;
+pub use self::expr_map::expr_map;
+
+use wrap_match_format as format_string;
+
+mod locale;
+
+mod messages;
+use self::messages::prepare_messages;
+
+mod options;
+use self::options::{Emit, Options};
+
+mod log_statement;
+use self::log_statement::Level;
+
+mod parameters;
+use self::parameters::Parameters;
+
+#[cfg(feature = "policy")]
+mod policy;
+
+mod probes;
+use self::probes::ErrorProbes;
+
+mod level;
+use self::level::{derive_wrap_match_level, error_level};
+
+mod signature;
+use self::signature::{
+    error_type_path, is_infallible, make_inner_function, outer_signature, result_error_type,
+    result_ok_type, result_type_error, Arguments,
+};
+
+mod success;
+use self::success::{handle_ok, ok_value};
+
+mod variant_name;
+use self::variant_name::derive_variant_name;
+
+/// Expands `#[derive(VariantName)]`
+pub fn variant_name(input: TokenStream2) -> TokenStream2 {
+    syn::parse2::<DeriveInput>(input)
+        .and_then(derive_variant_name)
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// Expands `#[derive(WrapMatchLevel)]`
+pub fn wrap_match_level(input: TokenStream2) -> TokenStream2 {
+    syn::parse2::<DeriveInput>(input)
+        .and_then(derive_wrap_match_level)
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// Expands `#[wrap_match]`, given the attribute's arguments and the function it is on
+pub fn wrap_match(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+    expand_wrap_match(args, input).unwrap_or_else(syn::Error::into_compile_error)
+}
+
+fn expand_wrap_match(args: TokenStream2, input: TokenStream2) -> syn::Result<TokenStream2> {
+    let mut options = syn::parse2::<Options>(args)?;
+    let input = syn::parse2::<ItemFn>(input)?;
+    // with `enabled_when`, the original function is kept for when the predicate is false
+    let (cfg_enabled, original) = match &options.enabled_when {
+        Some(predicate) => (
+            Some(quote!(#[cfg(#predicate)])),
+            Some(quote!(#[cfg(not(#predicate))] #input)),
+        ),
+        None => (None, None),
+    };
+    let tracking = prepare(&mut options, &input)?;
+
+    let arguments = Arguments::new(&input.sig);
+    let probes = ErrorProbes::new(result_error_type(&input.sig.output), &input.sig.generics);
+    if let (true, Some(span)) = (probes.is_type_param, options.level_from_error) {
+        return Err(Error::new(
+            span,
+            "wrap_match: `level_from_error` needs the error type to be known when the macro is expanded, so it can't be a type parameter",
+        ));
+    }
+    let error_trait_check = probes::error_trait_check(&options, &input.sig.output, &probes);
+    let attrs = input.attrs.clone();
+    let vis = input.vis.clone();
+    let sig = outer_signature(&options, &input.sig)?;
+    let span = instrument_span(&options, &input.sig.ident, &arguments.idents)?;
+    let (track_caller, get_caller) = parameters::caller(&options);
+
+    let (wrapper, input, warnings) = Wrapper::new(options, input, probes, &arguments)?;
+    let Statements {
+        outer_input,
+        self_check,
+        enter_span,
+        inner_input,
+        body,
+    } = wrapper.statements(input, &arguments, span, cfg_enabled.as_ref());
+
+    Ok(quote! {
+        #original
+        #outer_input
+
+        #cfg_enabled
+        #(#attrs)* #track_caller #vis #sig {
+            #get_caller
+            #self_check
+            #enter_span
+            #inner_input
+            #error_trait_check
+            #tracking
+            #warnings
+            #body
+        }
+    })
+}
+
+/// Gets the options ready for the function and checks them, returning the items that make the function recompile when a file it depends on changes
+fn prepare(options: &mut Options, input: &ItemFn) -> syn::Result<TokenStream2> {
+    #[cfg(feature = "catalog")]
+    catalog::apply_catalog(options)?;
+    #[cfg(not(feature = "catalog"))]
+    if let Some((_, span)) = &options.message_key {
+        return Err(Error::new(
+            *span,
+            "wrap_match: `message_key` requires the `catalog` feature",
+        ));
+    }
+    prepare_messages(options, &input.sig.ident.to_string())?;
+    checks::check_function(options, input)?;
+    checks::check_options(options)?;
+
+    #[cfg(feature = "policy")]
+    let policy_tracking = policy::check_policy(options, input)?;
+    #[cfg(not(feature = "policy"))]
+    let policy_tracking = quote!();
+    // recompile the function when a message file changes
+    let included_files = options
+        .included_files
+        .iter()
+        .filter_map(|path| path.to_str())
+        .map(|path| {
+            quote!(
+                const _: &str = ::core::include_str!(#path);
+            )
+        });
+    Ok(quote!(#policy_tracking #(#included_files)*))
+}
+
+/// Returns the span that `instrument` enters, named after the function like `#[tracing::instrument]` does
+fn instrument_span(
+    options: &Options,
+    name: &Ident,
+    arguments: &[Ident],
+) -> syn::Result<Option<TokenStream2>> {
+    let Some((fields, _)) = &options.instrument else {
+        return Ok(None);
+    };
+    if let Some(field) = fields.iter().find(|field| !arguments.contains(field)) {
+        return Err(Error::new(
+            field.span(),
+            "wrap_match: `instrument` can only record the function's arguments",
+        ));
+    }
+    let name = name.to_string();
+    Ok(Some(quote! {
+        ::wrap_match::__log::tracing::info_span!(#name, #(#fields = ?#fields),*)
+    }))
+}
+
+/// What the statements of the function we make are built from
+struct Wrapper {
+    options: Options,
+    /// The name of the function
+    name: Ident,
+    /// Probes for the function's error, and for the error in `Ok(Err(...))` with `flatten`
+    probes: ErrorProbes,
+    inner_probes: ErrorProbes,
+    parameters: Parameters,
+    contexts: Contexts,
+    error_level: Level,
+    /// Whether errors are added to the log statements as structured fields, with `error_style = "fields"`
+    error_fields: bool,
+    /// The location of the function, for errors that weren't caused by `?` and don't have a location
+    function_location: TokenStream2,
+    /// Whether the error type can't have values (`Infallible` or `!`), so there's nothing to log
+    infallible: bool,
+    /// Whether the body stays in the function we make instead of moving to an inner function. Async methods keep their body as an async block, since
+    /// an inner method next to them wouldn't be a member of the trait in trait implementations. async blocks also capture generics and `Self` from the
+    /// implementation without any changes. `inline` does the same for every function, with a closure for sync functions.
+    inline_body: bool,
+}
+
+/// The statements of the function we make
+struct Statements {
+    /// The inner function, if it goes next to the function we make
+    outer_input: Option<TokenStream2>,
+    self_check: Option<TokenStream2>,
+    enter_span: Option<TokenStream2>,
+    /// The inner function, if it goes inside the function we make
+    inner_input: Option<ItemFn>,
+    body: TokenStream2,
+}
+
+impl Wrapper {
+    /// Makes the wrapper for a function, returning it with the function (whose try expressions now add error info) and the function's warnings
+    fn new(
+        mut options: Options,
+        input: ItemFn,
+        probes: ErrorProbes,
+        arguments: &Arguments,
+    ) -> syn::Result<(Self, ItemFn, TokenStream2)> {
+        let name = input.sig.ident.clone();
+        // with `flatten`, the error in `Ok(Err(...))` is probed the same way
+        let inner_probes = ErrorProbes::new(
+            result_ok_type(&input.sig.output).and_then(result_type_error),
+            &input.sig.generics,
+        );
+        let error_type = error_type_path(&input.sig.output, probes.is_type_param);
+        let infallible = result_error_type(&input.sig.output).is_some_and(is_infallible);
+        let backend = checks::select_backend(&options)?;
+        let error_fields = checks::error_fields(&options, backend)?;
+        let inline_body = options.inline || arguments.has_self && input.sig.asyncness.is_some();
+        let (input, sites) = add_error_info(&options, input, backend, inline_body)?;
+        let warnings = checks::warnings(&options, backend, &name, sites);
+
+        let parameters = Parameters::new(&options, &arguments.idents);
+        let contexts = Contexts::new(
+            &mut options,
+            &name,
+            arguments.without_types_including_self.clone(),
+            backend,
+            &parameters,
+            error_fields,
+        )?;
+        let error_level = error_level(&options, backend, error_type.as_ref())?;
+        let function_location = source_location(name.span(), &name.to_string(), 0);
+        let wrapper = Wrapper {
+            options,
+            name,
+            probes,
+            inner_probes,
+            parameters,
+            contexts,
+            error_level,
+            error_fields,
+            function_location,
+            infallible,
+            inline_body,
+        };
+        Ok((wrapper, input, warnings))
+    }
+
+    /// Builds the statements of the function we make, which call the inner function (or run the body) and handle the result
+    fn statements(
+        &self,
+        input: ItemFn,
+        arguments: &Arguments,
+        span: Option<TokenStream2>,
+        cfg_enabled: Option<&TokenStream2>,
+    ) -> Statements {
+        let options = &self.options;
+        let is_async = input.sig.asyncness.is_some();
+        let (outer_input, inner_input, call) = self.call(input, arguments, cfg_enabled);
+        // a span can't stay entered across `.await`, so async functions instrument the future and only enter the span for the log statements
+        let (enter_span, enter_span_for_logs, call) = match (span, is_async) {
+            (Some(span), false) => (
+                Some(quote!(let _wrap_match_span = #span.entered();)),
+                None,
+                call,
+            ),
+            (Some(span), true) => (
+                Some(quote!(let _wrap_match_span = #span;)),
+                Some(quote!(let _wrap_match_entered = _wrap_match_span.enter();)),
+                quote!(::wrap_match::__log::tracing::Instrument::instrument(#call, _wrap_match_span.clone())),
+            ),
+            (None, _) => (None, None, call),
+        };
+        let call = catch_early_exits(options, &self.name.to_string(), call, is_async);
+        let early_exits = EarlyExits::new(options, &self.parameters);
+        let declare_early_exit = early_exits.declare();
+        let errors = handle_error(self, &early_exits, enter_span_for_logs.as_ref());
+        let enter_frame = errors.enter_frame;
+        let err_arm = errors.err_arm;
+        let log_ok = handle_ok(self);
+        let ok = ok_value(options);
+        let start_timer = parameters::start_timer(options);
+        let count_invocation = parameters::count_invocation(options);
+
+        let body = quote! {
+            #start_timer
+            #count_invocation
+            #enter_frame
+            #declare_early_exit
+
+            #[allow(deprecated)]
+            match #call {
+                Ok(_wrap_match_ok) => {
+                    #enter_span_for_logs
+                    #log_ok
+                    #ok
+                }
+                #err_arm
+            }
+        };
+        // async functions keep track of the functions that deferred to them per task instead of per thread, since they can move between threads
+        let body = if is_async && options.defer_to_outermost {
+            quote!(::wrap_match::__private::DeferScope::new(async move { #body }).await)
+        } else {
+            body
+        };
+        Statements {
+            outer_input,
+            self_check: errors.self_check,
+            enter_span,
+            inner_input,
+            body,
+        }
+    }
+
+    /// Returns the inner function if it goes next to the function we make or inside it, and the call of the inner function (or the body, if it stays
+    /// in the function we make). Methods need the inner function to be outside of the function we make, since we don't know what type self is.
+    fn call(
+        &self,
+        mut input: ItemFn,
+        arguments: &Arguments,
+        cfg_enabled: Option<&TokenStream2>,
+    ) -> (Option<TokenStream2>, Option<ItemFn>, TokenStream2) {
+        if self.inline_body {
+            let ReturnType::Type(_, output) = &input.sig.output else {
+                unreachable!("functions that don't return a `Result` are rejected above")
+            };
+            let body = &input.block;
+            // the output type has to be known for `?` to work in the async block
+            let call = if input.sig.asyncness.is_some() {
+                quote!(::wrap_match::__private::typed_future::<#output, _>(async move #body))
+            } else {
+                // like the inner function, the size of `WrapMatchError` doesn't matter here. the closure is `FnMut` if the body mutates an argument
+                quote! {{
+                    #[allow(unused_mut, clippy::result_large_err)]
+                    let mut _wrap_match_body = || -> #output #body;
+                    _wrap_match_body()
+                }}
+            };
+            return (None, None, call);
+        }
+        // the inner function doesn't get the span of the original name, so editors only see one definition
+        let inner_name = format_ident!("_wrap_match_inner_{}", self.name, span = Span::call_site());
+        make_inner_function(&mut input, inner_name.clone());
+        let self_dot = arguments.has_self.then(|| quote!(self.));
+        let arguments_without_types = &arguments.without_types;
+        let call = quote!(#self_dot #inner_name(#(#arguments_without_types),*));
+        if arguments.has_self {
+            (Some(quote!(#cfg_enabled #input)), None, call)
+        } else {
+            (None, Some(input), call)
+        }
+    }
+}
+
+/// Puts the log statements, metrics and hooks for a result in the order given with `emit_order`, which is metrics, then hooks, then log statements by
+/// default
+fn emit_in_order(
+    order: Option<&[Emit; 3]>,
+    log: &impl ToTokens,
+    metrics: &impl ToTokens,
+    hook: &impl ToTokens,
+) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    for emit in order.unwrap_or(&[Emit::Metrics, Emit::Hook, Emit::Log]) {
+        match emit {
+            Emit::Log => log.to_tokens(&mut tokens),
+            Emit::Metrics => metrics.to_tokens(&mut tokens),
+            Emit::Hook => hook.to_tokens(&mut tokens),
+        }
+    }
+    tokens
+}
@@ -0,0 +1,69 @@
+//! Translations of the default messages, for the `locale` option. To add a locale, add an entry to `LOCALES` and list it in the docs.
+
+pub struct DefaultMessages {
+    pub success_message: &'static str,
+    pub error_message: &'static str,
+    pub error_message_without_info: &'static str,
+    pub inner_error_message: &'static str,
+    /// Used instead of `error_message` and `error_message_without_info` with `error_style = "fields"`, since the error is in the fields
+    pub fields_error_message: &'static str,
+    /// Used instead of `inner_error_message` with `error_style = "fields"`
+    pub fields_inner_error_message: &'static str,
+}
+
+pub const LOCALES: &[(&str, DefaultMessages)] = &[
+    (
+        "en",
+        DefaultMessages {
+            success_message: "Successfully ran {function}",
+            error_message: "An error occurred when running {function} (caused by `{expr}` on line {line}): {error:?}",
+            error_message_without_info: "An error occurred when running {function}: {error:?}",
+            inner_error_message: "{function} ran, but returned an inner error: {error:?}",
+            fields_error_message: "An error occurred when running {function}",
+            fields_inner_error_message: "{function} ran, but returned an inner error",
+        },
+    ),
+    (
+        "de",
+        DefaultMessages {
+            success_message: "{function} erfolgreich ausgeführt",
+            error_message: "Fehler beim Ausführen von {function} (verursacht durch `{expr}` in Zeile {line}): {error:?}",
+            error_message_without_info: "Fehler beim Ausführen von {function}: {error:?}",
+            inner_error_message: "{function} wurde ausgeführt, hat aber einen inneren Fehler zurückgegeben: {error:?}",
+            fields_error_message: "Fehler beim Ausführen von {function}",
+            fields_inner_error_message: "{function} wurde ausgeführt, hat aber einen inneren Fehler zurückgegeben",
+        },
+    ),
+    (
+        "es",
+        DefaultMessages {
+            success_message: "{function} se ejecutó correctamente",
+            error_message: "Se produjo un error al ejecutar {function} (causado por `{expr}` en la línea {line}): {error:?}",
+            error_message_without_info: "Se produjo un error al ejecutar {function}: {error:?}",
+            inner_error_message: "{function} se ejecutó, pero devolvió un error interno: {error:?}",
+            fields_error_message: "Se produjo un error al ejecutar {function}",
+            fields_inner_error_message: "{function} se ejecutó, pero devolvió un error interno",
+        },
+    ),
+    (
+        "fr",
+        DefaultMessages {
+            success_message: "{function} exécutée avec succès",
+            error_message: "Une erreur s'est produite lors de l'exécution de {function} (causée par `{expr}` à la ligne {line}) : {error:?}",
+            error_message_without_info: "Une erreur s'est produite lors de l'exécution de {function} : {error:?}",
+            inner_error_message: "{function} a été exécutée, mais a renvoyé une erreur interne : {error:?}",
+            fields_error_message: "Une erreur s'est produite lors de l'exécution de {function}",
+            fields_inner_error_message: "{function} a été exécutée, mais a renvoyé une erreur interne",
+        },
+    ),
+];
+
+pub const ENGLISH: &DefaultMessages = &LOCALES[0].1;
+
+/// Finds the default messages for a locale, such as `de`
+pub fn default_messages(locale: &str) -> Option<&'static DefaultMessages> {
+    LOCALES
+        .iter()
+        .find(|(name, _)| *name == locale)
+        .map(|(_, messages)| messages)
+}
@@ -0,0 +1,280 @@
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::Member;
+
+use crate::format_string::{self, Piece};
+
+/// Prefix for the names we give to placeholders that access fields, since format strings only support identifiers
+const FIELD_ACCESS_PREFIX: &str = "_wrap_match_field_";
+
+/// The level of a log statement
+pub enum Level {
+    /// The name of the level's macro (`trace`, `debug`, `info`, `warn` or `error`)
+    Static(TokenStream2),
+    /// An expression that evaluates to the logging crate's level type (`log::Level` or `tracing::Level`) at runtime
+    Dynamic(TokenStream2),
+}
+
+/// The crate that log statements are emitted through
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Log,
+    Tracing,
+    /// `eprintln!`, for crates that don't want to depend on a logging crate. Levels, targets and structured fields are ignored.
+    Plain,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(feature = "tracing") {
+            Backend::Tracing
+        } else if cfg!(feature = "log") {
+            Backend::Log
+        } else {
+            Backend::Plain
+        }
+    }
+}
+
+/// Things that are the same for every log statement of a function
+#[derive(Clone)]
+pub struct LogContext {
+    /// The function's arguments (without types), which can be used in messages
+    pub arguments: Vec<TokenStream2>,
+    /// The custom placeholders declared with `extract`, which are filled in by extractors at runtime
+    pub extracted: Vec<String>,
+    /// The constants that messages are, with the placeholders that stand in for them
+    pub constants: Vec<(String, TokenStream2)>,
+    /// Structured fields that are added to every log statement (ignored when the backend is `log`)
+    pub fields: Vec<TokenStream2>,
+    /// Key-values that are added to every log statement when the backend is `log`, in `log`'s syntax (like `error:? = value`). This is only filled in
+    /// with `log_kv` and `fields`, since they need `log`'s `kv` feature.
+    pub kv: Vec<TokenStream2>,
+    /// The target of the log statements, if it shouldn't be the module path
+    pub target: Option<TokenStream2>,
+    /// The name of the log statements' callsites (ignored when the backend is `log`)
+    pub name: Option<TokenStream2>,
+    /// The crate that the log statements are emitted through
+    pub backend: Backend,
+}
+
+/// Returns the placeholders of a message that were declared with `extract` and aren't builtin parameters or arguments, which are filled in by
+/// extractors at runtime. Other unknown placeholders are left to `format_args!`, so typos are still compile errors.
+pub fn custom_placeholders<'a>(
+    input: &'a str,
+    builtin_parameters: &[(&'static str, TokenStream2)],
+    context: &LogContext,
+) -> Vec<&'a str> {
+    format_string::named_arguments(input)
+        .into_iter()
+        .filter(|&parameter_name| {
+            context.extracted.iter().any(|name| name == parameter_name)
+                && !builtin_parameters
+                    .iter()
+                    .any(|(name, _)| *name == parameter_name)
+                && !context
+                    .arguments
+                    .iter()
+                    .any(|name| name.to_string() == parameter_name)
+        })
+        .collect()
+}
+
+/// Returns the format string for a message and the parameters it uses
+fn format_arguments(
+    input: &str,
+    builtin_parameters: &[(&'static str, TokenStream2)],
+    extractor_input: Option<TokenStream2>,
+    context: &LogContext,
+) -> (String, Vec<TokenStream2>) {
+    let other_parameters = &context.arguments;
+
+    // builtin parameters win over arguments with the same name, but arguments can always be accessed with `{args.name}`
+    // this includes `self`, so methods can use `{self.field}`
+    let field_access_roots: Vec<_> = other_parameters
+        .iter()
+        .map(|name| (format!("args.{name}"), name.clone()))
+        .chain(
+            builtin_parameters
+                .iter()
+                .map(|(name, var)| ((*name).to_owned(), var.clone())),
+        )
+        .chain(
+            other_parameters
+                .iter()
+                .map(|name| (name.to_string(), name.clone())),
+        )
+        .collect();
+    let (input, mut parameters) = replace_field_access(input, &field_access_roots);
+    let used_arguments = format_string::named_arguments(&input);
+
+    for (parameter_name, parameter_var_name) in builtin_parameters {
+        if used_arguments.contains(parameter_name) {
+            let parameter_name = Ident::new(parameter_name, Span::call_site());
+            parameters.push(quote!(#parameter_name = #parameter_var_name));
+        }
+    }
+
+    for parameter_name in other_parameters {
+        let parameter_name = parameter_name.to_string();
+        if used_arguments.contains(&parameter_name.as_str())
+            && !builtin_parameters
+                .iter()
+                .any(|(name, _)| *name == parameter_name)
+        {
+            let parameter_name = Ident::new(&parameter_name, Span::call_site());
+            parameters.push(quote!(#parameter_name = #parameter_name));
+        }
+    }
+
+    for (parameter_name, constant) in &context.constants {
+        if used_arguments.contains(&parameter_name.as_str()) {
+            let parameter_name = Ident::new(parameter_name, Span::call_site());
+            parameters.push(quote!(#parameter_name = #constant));
+        }
+    }
+
+    // placeholders declared with `extract` will be filled in by an extractor at runtime
+    if let Some(extractor_input) = extractor_input {
+        for parameter_name in custom_placeholders(&input, builtin_parameters, context) {
+            let parameter_ident = Ident::new(parameter_name, Span::call_site());
+            parameters.push(quote!(#parameter_ident = ::wrap_match::__private::Extracted::new(#parameter_name, &#extractor_input)));
+        }
+    }
+
+    (input, parameters)
+}
+
+pub fn build_log_statement(
+    (input, input_span): &(String, Span),
+    builtin_parameters: &[(&'static str, TokenStream2)],
+    extractor_input: Option<TokenStream2>,
+    level: &Level,
+    context: &LogContext,
+) -> TokenStream2 {
+    let (input, parameters) = format_arguments(input, builtin_parameters, extractor_input, context);
+
+    let logging_crate = match context.backend {
+        Backend::Log => quote!(log),
+        Backend::Tracing => quote!(tracing),
+        // plain statements don't go through a logging crate
+        Backend::Plain => TokenStream2::new(),
+    };
+
+    let fields = match context.backend {
+        Backend::Tracing => {
+            let fields = &context.fields;
+            quote!(#(#fields,)*)
+        }
+        // `log` separates key-values from the message with a `;`
+        Backend::Log if !context.kv.is_empty() => {
+            let kv = &context.kv;
+            quote!(#(#kv),*;)
+        }
+        Backend::Log | Backend::Plain => quote!(),
+    };
+
+    let target = context
+        .target
+        .as_ref()
+        .map(|target| quote!(target: #target,));
+    let name = match context.backend {
+        Backend::Tracing => context.name.as_ref().map(|name| quote!(name: #name,)),
+        Backend::Log | Backend::Plain => None,
+    };
+
+    let statement = |level: TokenStream2| {
+        quote_spanned! {input_span.to_owned()=>
+            ::wrap_match::__log::#logging_crate::#level!(#name #target #fields #input, #(#parameters),*);
+        }
+    };
+
+    match (level, context.backend) {
+        (_, Backend::Plain) => quote_spanned! {input_span.to_owned()=>
+            ::std::eprintln!(#input, #(#parameters),*);
+        },
+        (Level::Static(level), _) => statement(level.clone()),
+        (Level::Dynamic(level), Backend::Log) => quote_spanned! {input_span.to_owned()=>
+            ::wrap_match::__log::log::log!(#target #level, #fields #input, #(#parameters),*);
+        },
+        // tracing needs to know the level at compile time, so we have to use a different statement for each level
+        (Level::Dynamic(level), Backend::Tracing) => {
+            let [error, warn, info, debug, trace] = [
+                quote!(error),
+                quote!(warn),
+                quote!(info),
+                quote!(debug),
+                quote!(trace),
+            ]
+            .map(statement);
+            quote! {
+                match #level {
+                    level if level == ::wrap_match::__log::tracing::Level::ERROR => { #error }
+                    level if level == ::wrap_match::__log::tracing::Level::WARN => { #warn }
+                    level if level == ::wrap_match::__log::tracing::Level::INFO => { #info }
+                    level if level == ::wrap_match::__log::tracing::Level::DEBUG => { #debug }
+                    _ => { #trace }
+                }
+            }
+        }
+    }
+}
+
+/// Panics with a message, for `panic_on_error`. The message has the same parameters as a log statement with them.
+pub fn build_panic_statement(
+    (input, input_span): &(String, Span),
+    builtin_parameters: &[(&'static str, TokenStream2)],
+    extractor_input: Option<TokenStream2>,
+    context: &LogContext,
+) -> TokenStream2 {
+    let (input, parameters) = format_arguments(input, builtin_parameters, extractor_input, context);
+    quote_spanned! {input_span.to_owned()=>
+        ::core::panic!(#input, #(#parameters),*);
+    }
+}
+
+/// Replaces placeholders that access fields (such as `{ok.id}` or `{request.user_id:?}`) or arguments (such as `{args.error}`) with generated names, returning the new input and the parameters
+/// for the generated names
+fn replace_field_access(
+    input: &str,
+    roots: &[(String, TokenStream2)],
+) -> (String, Vec<TokenStream2>) {
+    let field_access = |argument: &str| {
+        let (root, var, rest) = roots.iter().find_map(|(name, var)| {
+            let rest = argument.strip_prefix(name.as_str())?;
+            (rest.is_empty() || rest.starts_with('.')).then_some((name, var, rest))
+        })?;
+        let members = rest
+            .split('.')
+            .skip(1)
+            .map(syn::parse_str::<Member>)
+            .collect::<syn::Result<Vec<_>>>()
+            .ok()?;
+        // plain placeholders don't need to be replaced, unless they're `{args.name}`
+        if members.is_empty() && !root.contains('.') {
+            return None;
+        }
+        Some(quote!(#var #(.#members)*))
+    };
+
+    let mut output = String::with_capacity(input.len());
+    let mut field_parameters = vec![];
+    for piece in format_string::parse(input) {
+        match piece {
+            Piece::Placeholder { argument, spec } => match field_access(argument) {
+                Some(value) => {
+                    let name = format_ident!("{}{}", FIELD_ACCESS_PREFIX, field_parameters.len());
+                    field_parameters.push(quote!(#name = #value));
+                    Piece::Placeholder {
+                        argument: &name.to_string(),
+                        spec,
+                    }
+                    .write_to(&mut output);
+                }
+                None => piece.write_to(&mut output),
+            },
+            Piece::Literal(_) => piece.write_to(&mut output),
+        }
+    }
+    (output, field_parameters)
+}
@@ -0,0 +1,142 @@
+use syn::Error;
+
+use crate::{format_string, options::Options};
+
+/// The parameters that only `error_message` has, since they come from the try expression that caused the error
+const INFO_PARAMETERS: [&str; 4] = ["line", "expr", "backtrace", "span_trace"];
+
+/// Gets the messages ready to be logged: resolves their sections, adds what goes around every message and fills in the placeholders that are known at
+/// compile time
+pub fn prepare_messages(options: &mut Options, function: &str) -> syn::Result<()> {
+    resolve_sections(options)?;
+
+    if options.derive_without_info {
+        let (error_message, span) = &options.error_message;
+        let Some(message) = format_string::remove_clauses(error_message, &["expr", "line"]) else {
+            return Err(Error::new(
+                *span,
+                "wrap_match: `derive_without_info` needs `{expr}` and `{line}` to be inside parentheses or brackets in `error_message`, so it knows what to remove",
+            ));
+        };
+        options.error_message_without_info = (message, *span);
+    }
+
+    add_prefix_and_suffix(options);
+    // `{return}` is another name for `{ok}`, since `return` can't be the name of a format argument
+    options.success_message.0 = format_string::rename(&options.success_message.0, "return", "ok");
+    // the other messages don't have an `{ok}` to rename it to
+    for (message, span) in [
+        &options.error_message,
+        &options.error_message_without_info,
+        &options.inner_error_message,
+    ]
+    .into_iter()
+    .chain(options.timeout_message.as_ref())
+    .chain(options.panic_message.as_ref())
+    {
+        if format_string::uses_placeholder(message, "return") {
+            return Err(Error::new(
+                *span,
+                "wrap_match: `{return}` can only be used in `success_message`, where it's another name for `{ok}`",
+            ));
+        }
+    }
+    add_context(options);
+
+    options.replace_in_messages("function", function);
+    if let Some((owner, _)) = options.owner.clone() {
+        options.replace_in_messages("owner", &owner);
+    }
+    Ok(())
+}
+
+/// Resolves the conditional sections of the messages (like `{?line}(on line {line}){/line}`), so one `error_message` can be used with and without line
+/// and expression info
+fn resolve_sections(options: &mut Options) -> syn::Result<()> {
+    if format_string::has_sections(&options.error_message.0)
+        && !options.custom_error_message_without_info
+    {
+        options.error_message_without_info = options.error_message.clone();
+        // it's also used for errors that didn't come from a try expression now
+        options.custom_error_message = false;
+    }
+    // `{?chain}` sections are left for later, since whether the chain is empty is only known at runtime
+    let runtime_sections: &[&str] = if options.defer_to_outermost {
+        &["chain"]
+    } else {
+        &[]
+    };
+    for (message, with_info, except) in [
+        (&mut options.success_message, false, &[][..]),
+        (&mut options.error_message, true, runtime_sections),
+        (
+            &mut options.error_message_without_info,
+            false,
+            runtime_sections,
+        ),
+        (&mut options.inner_error_message, false, &[]),
+    ] {
+        let resolved = format_string::resolve_sections_except(
+            &message.0,
+            |name| with_info || !INFO_PARAMETERS.contains(&name),
+            except,
+        );
+        match resolved {
+            Ok(resolved) => message.0 = resolved,
+            Err(e) => return Err(Error::new(message.1, format!("wrap_match: {e}"))),
+        }
+    }
+    Ok(())
+}
+
+/// Puts the prefix and suffix around every message, including the defaults and messages from the catalog
+fn add_prefix_and_suffix(options: &mut Options) {
+    let prefix = options
+        .message_prefix
+        .as_ref()
+        .map_or("", |(prefix, _)| prefix);
+    let suffix = options
+        .message_suffix
+        .as_ref()
+        .map_or("", |(suffix, _)| suffix);
+    for (message, _) in [
+        &mut options.success_message,
+        &mut options.error_message,
+        &mut options.error_message_without_info,
+        &mut options.inner_error_message,
+    ]
+    .into_iter()
+    .chain(options.timeout_message.as_mut())
+    .chain(options.panic_message.as_mut())
+    {
+        *message = format!("{prefix}{message}{suffix}");
+    }
+}
+
+/// Makes error messages end with the contexts added with `wrap_match::context`, unless they put them somewhere else
+fn add_context(options: &mut Options) {
+    // the JSON record is the whole error message, so it isn't prefixed or replaced by the catalog
+    if options.json_errors() {
+        for (message, _) in [
+            &mut options.error_message,
+            &mut options.error_message_without_info,
+            &mut options.inner_error_message,
+        ] {
+            *message = "{json}".to_owned();
+        }
+        return;
+    }
+    for (message, _) in [
+        &mut options.error_message,
+        &mut options.error_message_without_info,
+        &mut options.inner_error_message,
+    ]
+    .into_iter()
+    .chain(options.timeout_message.as_mut())
+    .chain(options.panic_message.as_mut())
+    {
+        if !format_string::uses_placeholder(message, "context") {
+            message.push_str("{_wrap_match_context}");
+        }
+    }
+}
@@ -0,0 +1,1211 @@
+use std::path::PathBuf;
+
+use proc_macro2::{Ident, Span};
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token, Error, Expr, ExprLit, ExprMacro, Lit, LitBool, LitInt, LitStr, Meta, Path, Token,
+};
+
+use crate::{format_string, locale, log_statement::Backend};
+
+/// The names of every option, for suggesting the closest one when an option is unknown
+const OPTION_NAMES: &[&str] = &[
+    "success_message",
+    "error_message",
+    "error_message_without_info",
+    "inner_error_message",
+    "message_prefix",
+    "message_suffix",
+    "owner",
+    "event_name",
+    "target",
+    "locale",
+    "message_key",
+    "catalog",
+    "level_fn",
+    "success_level",
+    "error_level",
+    "level_for",
+    "level_from_error",
+    "exhaustive",
+    "fields",
+    "extract",
+    "chain_max_frames",
+    "cold_start",
+    "chain_order",
+    "error_style",
+    "duration_format",
+    "duration_precision",
+    "timeout",
+    "timeout_message",
+    "panic_message",
+    "exit_on_error",
+    "or_else",
+    "expr_privacy",
+    "logger",
+    "instrument",
+    "enabled_when",
+    "sentry",
+    "reporter",
+    "log_success_if",
+    "emit_order",
+    "log_success",
+    "disregard_result",
+    "exit_code",
+    "ok_or_default",
+    "ok_or_none",
+    "require_error_trait",
+    "dual_output",
+    "bridge_safe",
+    "backtrace",
+    "span_trace",
+    "wide_event",
+    "defer_to_outermost",
+    "derive_without_info",
+    "redact_literals",
+    "log_kv",
+    "flatten",
+    "tail_result",
+    "inline",
+    "metrics",
+    "catch_panics",
+    "panic_on_error",
+    "self_check",
+    "strict",
+];
+
+/// How errors are logged, for `error_style`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ErrorStyleKind {
+    /// In the message
+    Interpolated,
+    /// As structured fields (with `tracing`)
+    Fields,
+    /// As a JSON record, which is the whole message
+    Json,
+}
+
+/// How `{duration}` is formatted, for `duration_format`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// Milliseconds, as a number
+    Millis,
+    /// Seconds, as a number
+    Seconds,
+    /// With a unit that fits the duration, like `1.5s` or `2m 30s`
+    Human,
+}
+
+/// How errors are reported to Sentry
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SentryReport {
+    /// `sentry_core::capture_error`, or a message event if the error doesn't implement `Error`
+    Capture,
+    /// A breadcrumb, so the error shows up in the next event that is captured
+    Breadcrumb,
+}
+
+/// What is emitted for a result, for `emit_order`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// The log statements
+    Log,
+    /// The counters for the `metrics` facade
+    Metrics,
+    /// The reporter, Sentry and OpenTelemetry
+    Hook,
+}
+
+pub struct Options {
+    pub success_message: (String, Span),
+    pub error_message: (String, Span),
+    pub error_message_without_info: (String, Span),
+    /// Used when the function returns `Ok(Err(...))` with `flatten`
+    pub inner_error_message: (String, Span),
+    /// Whether `error_message` was given (instead of being the default), for the warning when it can never be used
+    pub custom_error_message: bool,
+    /// Whether `error_message_without_info` was given, so it isn't replaced by an `error_message` with conditional sections
+    pub custom_error_message_without_info: bool,
+    /// Added to the start of every message
+    pub message_prefix: Option<(String, Span)>,
+    /// Added to the end of every message
+    pub message_suffix: Option<(String, Span)>,
+    pub owner: Option<(String, Span)>,
+    pub event_name: Option<(String, Span)>,
+    /// The target of the log statements, instead of the module path
+    pub target: Option<(String, Span)>,
+    pub message_key: Option<(String, Span)>,
+    /// The path of the message catalog, relative to the crate root
+    pub catalog: Option<(String, Span)>,
+    pub level_fn: Option<Path>,
+    /// The name of the level's macro (`trace`, `debug`, `info`, `warn` or `error`)
+    pub success_level: Option<(String, Span)>,
+    pub error_level: Option<(String, Span)>,
+    /// Levels for specific error variants, like `level_for(NotFound = "warn")`
+    pub level_for: Option<(Vec<(Path, String)>, Span)>,
+    /// Whether the error type's `WrapMatchLevel` implementation chooses the level, if it has one
+    pub level_from_error: Option<Span>,
+    /// Whether `level_for` has to list every variant of the error type, so a new variant without a level is a compile error
+    pub exhaustive: Option<Span>,
+    /// Constant key-values that are added to every log statement, like `fields(component = "importer")`
+    pub fields: Option<(Vec<(Ident, Lit)>, Span)>,
+    /// The custom placeholders that are filled in by extractors registered at runtime
+    pub extract: Option<(Vec<Ident>, Span)>,
+    pub chain_max_frames: Option<(usize, Span)>,
+    /// How many calls of the function are tagged as first calls
+    pub cold_start: Option<(u64, Span)>,
+    /// Whether the chain is ordered outermost first (instead of innermost first)
+    pub chain_order: Option<(bool, Span)>,
+    /// Whether the error is logged as structured fields (instead of being interpolated into the messages)
+    pub error_style: Option<(ErrorStyleKind, Span)>,
+    pub duration_format: Option<(DurationFormat, Span)>,
+    /// How many digits `{duration}` has after the decimal point with `duration_format`
+    pub duration_precision: Option<(usize, Span)>,
+    /// How long async functions can run for before they time out, in milliseconds
+    pub timeout: Option<(u64, Span)>,
+    /// Logged when a function times out, instead of the default message
+    pub timeout_message: Option<(String, Span)>,
+    /// Logged when a function panics with `catch_panics`, instead of the default message
+    pub panic_message: Option<(String, Span)>,
+    /// The status code the process exits with after an error is logged, with `disregard_result`
+    pub exit_on_error: Option<(i32, Span)>,
+    /// The value the function returns in `Ok` after an error is logged, or a closure that makes it from the error
+    pub or_else: Option<Expr>,
+    /// The predicate over the `Ok` value that has to be true for the success to be logged
+    pub log_success_if: Option<Expr>,
+    /// The order that log statements, metrics and hooks are emitted in
+    pub emit_order: Option<([Emit; 3], Span)>,
+    /// The crate to log through (or `eprintln!`), instead of the one picked by the enabled features
+    pub logger: Option<(Backend, Span)>,
+    /// The arguments to record in the function's span, if wrap-match should enter one (`instrument = true` records none)
+    pub instrument: Option<(Vec<Ident>, Span)>,
+    /// Whether results are sent to the reporter set with `wrap_match::set_reporter`, and if so, whether they are sent instead of being logged
+    pub reporter: Option<(bool, Span)>,
+    /// How errors are reported to Sentry, if they are (with the `sentry` feature, they are captured as events by default)
+    pub sentry: Option<SentryReport>,
+    /// The `cfg` predicate (of the crate using wrap-match) that has to be true for the function to be wrapped, like `feature = "observability"`
+    pub enabled_when: Option<Meta>,
+
+    pub log_success: bool,
+    pub disregard_result: bool,
+    /// Whether the resulting function returns a `std::process::ExitCode` instead of the `Result`, for `main` functions
+    pub exit_code: bool,
+    /// Whether the resulting function returns the `Ok` type instead of the `Result`, with its default value after an error is logged
+    pub ok_or_default: bool,
+    /// Whether the resulting function returns an `Option` of the `Ok` type instead of the `Result`, with `None` after an error is logged
+    pub ok_or_none: bool,
+    pub require_error_trait: bool,
+    pub dual_output: bool,
+    pub bridge_safe: bool,
+    pub backtrace: bool,
+    pub wide_event: bool,
+    pub defer_to_outermost: bool,
+    pub derive_without_info: bool,
+    /// Whether literals in `{expr}` are replaced with placeholders
+    pub redact_literals: bool,
+    /// Whether `{expr}` is a hash of the expression (with `expr_privacy = "hash"`)
+    pub hash_expressions: bool,
+    /// Whether a `tracing_error::SpanTrace` is captured at try expressions, for `{span_trace}`
+    pub span_trace: bool,
+    /// Whether `log` records get the function, line, expression and error as key-values
+    pub log_kv: bool,
+    /// Whether `Ok(Err(...))` is logged as an error, for functions that return a `Result` of a `Result`
+    pub flatten: bool,
+    /// Whether the tail expression is treated like a try expression
+    pub tail_result: bool,
+    /// Whether the body stays in the function we make (as a closure or async block) instead of being moved to an inner function
+    pub inline: bool,
+    /// Whether results are counted with the `metrics` facade
+    pub metrics: bool,
+    /// Whether panics in the function are caught and turned into errors
+    pub catch_panics: bool,
+    /// Whether the function panics with the error message after logging it, instead of returning the error
+    pub panic_on_error: bool,
+    /// Whether debug builds check that the custom placeholders in messages have extractors, on the function's first call
+    pub self_check: bool,
+    /// Turns silent fallbacks (such as try expressions we can't add error info to) into compile errors
+    pub strict: bool,
+
+    /// Files that messages were read from with `include_str!`, so the function can be recompiled when they change
+    pub included_files: Vec<PathBuf>,
+    /// Constants that messages are, like `error_message = messages::FAILED`, with the placeholders that stand in for them
+    pub message_constants: Vec<(String, Path)>,
+}
+
+impl Options {
+    #[rustfmt::skip]
+    /// Replaces a placeholder (such as `function` for `{function}`) in the messages with a value at compile time
+    pub fn replace_in_messages(&mut self, placeholder: &str, value: &str) {
+        self.success_message.0 = format_string::substitute(&self.success_message.0, placeholder, value);
+        self.error_message.0 = format_string::substitute(&self.error_message.0, placeholder, value);
+        self.error_message_without_info.0 = format_string::substitute(&self.error_message_without_info.0, placeholder, value);
+        self.inner_error_message.0 = format_string::substitute(&self.inner_error_message.0, placeholder, value);
+        if let Some((timeout_message, _)) = &mut self.timeout_message {
+            *timeout_message = format_string::substitute(timeout_message, placeholder, value);
+        }
+        if let Some((panic_message, _)) = &mut self.panic_message {
+            *panic_message = format_string::substitute(panic_message, placeholder, value);
+        }
+        if let Some((event_name, _)) = &mut self.event_name {
+            *event_name = format_string::substitute(event_name, placeholder, value);
+        }
+        if let Some((target, _)) = &mut self.target {
+            *target = format_string::substitute(target, placeholder, value);
+        }
+    }
+
+    /// Returns whether one of the messages about the function's result uses a placeholder
+    pub fn messages_use(&self, placeholder: &str) -> bool {
+        [
+            &self.success_message,
+            &self.error_message,
+            &self.error_message_without_info,
+            &self.inner_error_message,
+        ]
+        .iter()
+        .any(|(message, _)| format_string::uses_placeholder(message, placeholder))
+    }
+
+    /// Returns whether errors are logged as JSON records, with `error_style = "json"`
+    pub fn json_errors(&self) -> bool {
+        matches!(self.error_style, Some((ErrorStyleKind::Json, _)))
+    }
+
+    /// Returns whether the reporter replaces the log statements, with `reporter = "instead"`
+    pub fn log_to_reporter_only(&self) -> bool {
+        matches!(self.reporter, Some((true, _)))
+    }
+
+    /// Returns the name of the macro of the level that errors are logged at, unless the level is picked at runtime
+    pub fn error_level_name(&self) -> &str {
+        self.error_level
+            .as_ref()
+            .map_or("error", |(level, _)| level.as_str())
+    }
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options {
+            success_message: (
+                locale::ENGLISH.success_message.to_owned(),
+                Span::call_site(),
+            ),
+            error_message: (locale::ENGLISH.error_message.to_owned(), Span::call_site()),
+            error_message_without_info: (
+                locale::ENGLISH.error_message_without_info.to_owned(),
+                Span::call_site(),
+            ),
+            inner_error_message: (
+                locale::ENGLISH.inner_error_message.to_owned(),
+                Span::call_site(),
+            ),
+            custom_error_message: false,
+            custom_error_message_without_info: false,
+            message_prefix: None,
+            message_suffix: None,
+            owner: None,
+            event_name: None,
+            target: None,
+            message_key: None,
+            catalog: None,
+            level_fn: None,
+            success_level: None,
+            error_level: None,
+            level_for: None,
+            level_from_error: None,
+            exhaustive: None,
+            fields: None,
+            extract: None,
+            chain_max_frames: None,
+            cold_start: None,
+            chain_order: None,
+            error_style: None,
+            duration_format: None,
+            duration_precision: None,
+            timeout: None,
+            timeout_message: None,
+            panic_message: None,
+            exit_on_error: None,
+            or_else: None,
+            log_success_if: None,
+            emit_order: None,
+            logger: None,
+            instrument: None,
+            enabled_when: None,
+            reporter: None,
+            sentry: cfg!(feature = "sentry").then_some(SentryReport::Capture),
+
+            log_success: true,
+            disregard_result: false,
+            exit_code: false,
+            ok_or_default: false,
+            ok_or_none: false,
+            require_error_trait: false,
+            dual_output: false,
+            bridge_safe: false,
+            backtrace: false,
+            wide_event: false,
+            defer_to_outermost: false,
+            derive_without_info: false,
+            redact_literals: false,
+            hash_expressions: false,
+            span_trace: false,
+            log_kv: false,
+            flatten: false,
+            tail_result: false,
+            inline: false,
+            metrics: false,
+            catch_panics: false,
+            panic_on_error: false,
+            self_check: false,
+            strict: false,
+
+            included_files: vec![],
+            message_constants: vec![],
+        };
+        let mut locale_messages = None;
+        // which messages were given, so the locale only changes the other ones
+        let (
+            mut custom_success,
+            mut custom_error,
+            mut custom_error_without_info,
+            mut custom_inner_error,
+        ) = (false, false, false, false);
+        // `panic_on_error` can't be used with `defer_to_outermost`, and `exit_code` can't be used with other ways of handling the result, whichever
+        // comes first
+        let mut panic_on_error_span = None;
+        let mut exit_code_span = None;
+        let mut ok_or_default_span = None;
+        let mut ok_or_none_span = None;
+
+        while input.peek(Ident::peek_any) {
+            enum OptionName {
+                SuccessMessage,
+                ErrorMessage,
+                ErrorMessageWithoutInfo,
+                InnerErrorMessage,
+                MessagePrefix,
+                MessageSuffix,
+                Owner,
+                EventName,
+                Target,
+                Locale,
+                MessageKey,
+                Catalog,
+                LevelFn,
+                SuccessLevel,
+                ErrorLevel,
+                LevelFor,
+                LevelFromError,
+                Exhaustive,
+                Fields,
+                Extract,
+                ChainMaxFrames,
+                ColdStart,
+                ChainOrder,
+                ErrorStyle,
+                DurationFormat,
+                DurationPrecision,
+                Timeout,
+                TimeoutMessage,
+                PanicMessage,
+                ExitOnError,
+                OrElse,
+                ExprPrivacy,
+                Logger,
+                Instrument,
+                EnabledWhen,
+                Sentry,
+                Reporter,
+                LogSuccessIf,
+                EmitOrder,
+
+                LogSuccess,
+                DisregardResult,
+                ExitCode,
+                OkOrDefault,
+                OkOrNone,
+                RequireErrorTrait,
+                DualOutput,
+                BridgeSafe,
+                Backtrace,
+                SpanTrace,
+                WideEvent,
+                DeferToOutermost,
+                DeriveWithoutInfo,
+                RedactLiterals,
+                LogKv,
+                Flatten,
+                TailResult,
+                Inline,
+                Metrics,
+                CatchPanics,
+                PanicOnError,
+                SelfCheck,
+                Strict,
+            }
+            use OptionName::*;
+
+            let name: Ident = input.parse()?;
+
+            let option = match name.to_string().as_str() {
+                "success_message" => SuccessMessage,
+                "error_message" => ErrorMessage,
+                "error_message_without_info" => ErrorMessageWithoutInfo,
+                "inner_error_message" => InnerErrorMessage,
+                "message_prefix" => MessagePrefix,
+                "message_suffix" => MessageSuffix,
+                "owner" => Owner,
+                "event_name" => EventName,
+                "target" => Target,
+                "locale" => Locale,
+                "message_key" => MessageKey,
+                "catalog" => Catalog,
+                "level_fn" => LevelFn,
+                "success_level" => SuccessLevel,
+                "error_level" => ErrorLevel,
+                "level_for" => LevelFor,
+                "level_from_error" => LevelFromError,
+                "exhaustive" => Exhaustive,
+                "fields" => Fields,
+                "extract" => Extract,
+                "chain_max_frames" => ChainMaxFrames,
+                "cold_start" => ColdStart,
+                "chain_order" => ChainOrder,
+                "error_style" => ErrorStyle,
+                "duration_format" => DurationFormat,
+                "duration_precision" => DurationPrecision,
+                "timeout" => Timeout,
+                "timeout_message" => TimeoutMessage,
+                "panic_message" => PanicMessage,
+                "exit_on_error" => ExitOnError,
+                "or_else" => OrElse,
+                "expr_privacy" => ExprPrivacy,
+                "logger" => Logger,
+                "instrument" => Instrument,
+                "enabled_when" => EnabledWhen,
+                "sentry" => Sentry,
+                "reporter" => Reporter,
+                "log_success_if" => LogSuccessIf,
+                "emit_order" => EmitOrder,
+
+                "log_success" => LogSuccess,
+                "disregard_result" => DisregardResult,
+                "exit_code" => ExitCode,
+                "ok_or_default" => OkOrDefault,
+                "ok_or_none" => OkOrNone,
+                "require_error_trait" => RequireErrorTrait,
+                "dual_output" => DualOutput,
+                "bridge_safe" => BridgeSafe,
+                "backtrace" => Backtrace,
+                "span_trace" => SpanTrace,
+                "wide_event" => WideEvent,
+                "defer_to_outermost" => DeferToOutermost,
+                "derive_without_info" => DeriveWithoutInfo,
+                "redact_literals" => RedactLiterals,
+                "log_kv" => LogKv,
+                "flatten" => Flatten,
+                "tail_result" => TailResult,
+                "inline" => Inline,
+                "metrics" => Metrics,
+                "catch_panics" => CatchPanics,
+                "panic_on_error" => PanicOnError,
+                "self_check" => SelfCheck,
+                "strict" => Strict,
+
+                unknown => {
+                    let message = match closest_option(unknown) {
+                        Some(closest) => format!("wrap_match: unknown configuration option `{unknown}` (did you mean `{closest}`?)"),
+                        None => format!("wrap_match: unknown configuration option `{unknown}`"),
+                    };
+                    return Err(Error::new(name.span(), message));
+                }
+            };
+
+            // `level_for`, `fields`, `extract` and `emit_order` take a list instead of a value, and so can `instrument`
+            let takes_list = matches!(option, LevelFor | Fields | Extract | EmitOrder)
+                || matches!(option, Instrument) && input.peek(token::Paren);
+            if !takes_list {
+                let _: Token![=] = input.parse()?;
+            }
+
+            match option {
+                SuccessMessage
+                | ErrorMessage
+                | ErrorMessageWithoutInfo
+                | InnerErrorMessage
+                | TimeoutMessage
+                | PanicMessage
+                | MessagePrefix
+                | MessageSuffix => {
+                    let value: Expr = input.parse()?;
+                    let message = match &value {
+                        // the value of a constant isn't known to the macro, so it's written with `{}` and its placeholders aren't filled in
+                        Expr::Path(path) if path.qself.is_none() => {
+                            let placeholder =
+                                format!("_wrap_match_const_{}", options.message_constants.len());
+                            options
+                                .message_constants
+                                .push((placeholder.clone(), path.path.clone()));
+                            format!("{{{placeholder}}}")
+                        }
+                        // other literals can only be parts of `concat!`
+                        Expr::Lit(ExprLit { lit, .. }) if !matches!(lit, Lit::Str(_)) => {
+                            return Err(Error::new(lit.span(), "expected string literal"))
+                        }
+                        _ => evaluate_message(&value, &mut options.included_files)?,
+                    };
+                    let value = (message, value.span());
+
+                    match option {
+                        SuccessMessage => {
+                            options.success_message = value;
+                            custom_success = true;
+                        }
+                        ErrorMessage => {
+                            options.error_message = value;
+                            custom_error = true;
+                        }
+                        ErrorMessageWithoutInfo => {
+                            options.error_message_without_info = value;
+                            custom_error_without_info = true;
+                        }
+                        InnerErrorMessage => {
+                            options.inner_error_message = value;
+                            custom_inner_error = true;
+                        }
+                        TimeoutMessage => options.timeout_message = Some(value),
+                        PanicMessage => options.panic_message = Some(value),
+                        MessagePrefix => options.message_prefix = Some(value),
+                        MessageSuffix => options.message_suffix = Some(value),
+                        _ => unreachable!(),
+                    }
+                }
+                Owner | EventName | Target | MessageKey | Catalog => {
+                    let value: LitStr = input.parse()?;
+                    let value = (value.value(), value.span());
+
+                    match option {
+                        Owner => options.owner = Some(value),
+                        EventName => options.event_name = Some(value),
+                        Target => options.target = Some(value),
+                        MessageKey => options.message_key = Some(value),
+                        Catalog => options.catalog = Some(value),
+                        _ => unreachable!(),
+                    }
+                }
+                Locale => {
+                    let value: LitStr = input.parse()?;
+                    let Some(messages) = locale::default_messages(&value.value()) else {
+                        let available: Vec<_> = locale::LOCALES
+                            .iter()
+                            .map(|(name, _)| format!("`{name}`"))
+                            .collect();
+                        return Err(Error::new(
+                            value.span(),
+                            format!(
+                                "wrap_match: unknown locale (expected one of {})",
+                                available.join(", ")
+                            ),
+                        ));
+                    };
+                    locale_messages = Some(messages);
+                }
+                LevelFn => options.level_fn = Some(input.parse()?),
+                LevelFromError => {
+                    let value: LitBool = input.parse()?;
+                    options.level_from_error = value.value().then(|| value.span());
+                }
+                Exhaustive => {
+                    let value: LitBool = input.parse()?;
+                    options.exhaustive = value.value().then(|| value.span());
+                }
+                LevelFor => {
+                    let content;
+                    parenthesized!(content in input);
+                    let mut levels = vec![];
+                    while !content.is_empty() {
+                        let path: Path = content.parse()?;
+                        let _: Token![=] = content.parse()?;
+                        levels.push((path, parse_level(&content)?.0));
+                        if !content.is_empty() {
+                            let _: Token![,] = content.parse()?;
+                        }
+                    }
+                    options.level_for = Some((levels, name.span()));
+                }
+                Fields => {
+                    let content;
+                    parenthesized!(content in input);
+                    let mut fields = vec![];
+                    while !content.is_empty() {
+                        let key: Ident = content.parse()?;
+                        let _: Token![=] = content.parse()?;
+                        fields.push((key, content.parse()?));
+                        if !content.is_empty() {
+                            let _: Token![,] = content.parse()?;
+                        }
+                    }
+                    options.fields = Some((fields, name.span()));
+                }
+                Extract => {
+                    let content;
+                    parenthesized!(content in input);
+                    let placeholders = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                    options.extract = Some((placeholders.into_iter().collect(), name.span()));
+                }
+                SuccessLevel | ErrorLevel => {
+                    let value = parse_level(input)?;
+
+                    match option {
+                        SuccessLevel => options.success_level = Some(value),
+                        ErrorLevel => options.error_level = Some(value),
+                        _ => unreachable!(),
+                    }
+                }
+                ChainMaxFrames => {
+                    let value: LitInt = input.parse()?;
+                    options.chain_max_frames = Some((value.base10_parse()?, value.span()));
+                }
+                ColdStart => {
+                    let value: LitInt = input.parse()?;
+                    options.cold_start = Some((value.base10_parse()?, value.span()));
+                }
+                ExitOnError => {
+                    let value: LitInt = input.parse()?;
+                    options.exit_on_error = Some((value.base10_parse()?, value.span()));
+                }
+                ChainOrder => {
+                    let value: LitStr = input.parse()?;
+                    let outermost_first = match value.value().as_str() {
+                        "innermost_first" => false,
+                        "outermost_first" => true,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `innermost_first` or `outermost_first`",
+                            ))
+                        }
+                    };
+                    options.chain_order = Some((outermost_first, value.span()));
+                }
+                ErrorStyle => {
+                    let value: LitStr = input.parse()?;
+                    let style = match value.value().as_str() {
+                        "interpolated" => ErrorStyleKind::Interpolated,
+                        "fields" => ErrorStyleKind::Fields,
+                        "json" => ErrorStyleKind::Json,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `interpolated`, `fields` or `json`",
+                            ))
+                        }
+                    };
+                    options.error_style = Some((style, value.span()));
+                }
+                DurationFormat => {
+                    let value: LitStr = input.parse()?;
+                    let format = match value.value().as_str() {
+                        "ms" => self::DurationFormat::Millis,
+                        "s" => self::DurationFormat::Seconds,
+                        "human" => self::DurationFormat::Human,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `ms`, `s` or `human`",
+                            ))
+                        }
+                    };
+                    options.duration_format = Some((format, value.span()));
+                }
+                DurationPrecision => {
+                    let value: LitInt = input.parse()?;
+                    options.duration_precision = Some((value.base10_parse()?, value.span()));
+                }
+                Timeout => {
+                    let value: LitStr = input.parse()?;
+                    if !cfg!(feature = "tokio") {
+                        return Err(Error::new(
+                            value.span(),
+                            "wrap_match: `timeout` requires the `tokio` feature of wrap-match",
+                        ));
+                    }
+                    options.timeout = Some((parse_duration(&value)?, value.span()));
+                }
+                ExprPrivacy => {
+                    let value: LitStr = input.parse()?;
+                    options.hash_expressions = match value.value().as_str() {
+                        "plain" => false,
+                        "hash" => true,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `plain` or `hash`",
+                            ))
+                        }
+                    };
+                }
+                Logger => {
+                    let value: LitStr = input.parse()?;
+                    let backend = match value.value().as_str() {
+                        "log" if cfg!(feature = "log") => Backend::Log,
+                        "log" => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: `logger = \"log\"` requires the `log` feature of wrap-match",
+                            ))
+                        }
+                        "tracing" if cfg!(feature = "tracing") => Backend::Tracing,
+                        "plain" => Backend::Plain,
+                        "tracing" => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: `logger = \"tracing\"` requires the `tracing` feature of wrap-match",
+                            ))
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `log`, `tracing` or `plain`",
+                            ))
+                        }
+                    };
+                    options.logger = Some((backend, value.span()));
+                }
+                EnabledWhen => options.enabled_when = Some(input.parse()?),
+                OrElse => options.or_else = Some(input.parse()?),
+                LogSuccessIf => options.log_success_if = Some(input.parse()?),
+                EmitOrder => {
+                    let content;
+                    parenthesized!(content in input);
+                    let mut order = vec![];
+                    for kind in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                        let emit = match kind.to_string().as_str() {
+                            "log" => Emit::Log,
+                            "metrics" => Emit::Metrics,
+                            "hook" => Emit::Hook,
+                            _ => {
+                                return Err(Error::new(
+                                    kind.span(),
+                                    "wrap_match: expected `log`, `metrics` or `hook`",
+                                ))
+                            }
+                        };
+                        if order.contains(&emit) {
+                            return Err(Error::new(
+                                kind.span(),
+                                format!("wrap_match: `{kind}` is already in `emit_order`"),
+                            ));
+                        }
+                        order.push(emit);
+                    }
+                    let Ok(order) = order.try_into() else {
+                        return Err(Error::new(
+                            name.span(),
+                            "wrap_match: `emit_order` has to list each of `log`, `metrics` and `hook`",
+                        ));
+                    };
+                    options.emit_order = Some((order, name.span()));
+                }
+                Reporter => {
+                    let value: LitStr = input.parse()?;
+                    let instead = match value.value().as_str() {
+                        "also" => false,
+                        "instead" => true,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `also` or `instead`",
+                            ))
+                        }
+                    };
+                    options.reporter = Some((instead, value.span()));
+                }
+                Sentry => {
+                    let value: LitStr = input.parse()?;
+                    if !cfg!(feature = "sentry") {
+                        return Err(Error::new(
+                            value.span(),
+                            "wrap_match: `sentry` requires the `sentry` feature of wrap-match",
+                        ));
+                    }
+                    options.sentry = match value.value().as_str() {
+                        "capture" => Some(SentryReport::Capture),
+                        "breadcrumb" => Some(SentryReport::Breadcrumb),
+                        "off" => None,
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "wrap_match: expected `capture`, `breadcrumb` or `off`",
+                            ))
+                        }
+                    };
+                }
+                Instrument => {
+                    let (fields, span) = if input.peek(token::Paren) {
+                        let content;
+                        parenthesized!(content in input);
+                        let fields = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                        (Some(fields.into_iter().collect()), name.span())
+                    } else {
+                        let value: LitBool = input.parse()?;
+                        (value.value().then(Vec::new), value.span())
+                    };
+                    if fields.is_some() && !cfg!(feature = "tracing") {
+                        return Err(Error::new(
+                            span,
+                            "wrap_match: `instrument` requires the `tracing` feature of wrap-match",
+                        ));
+                    }
+                    options.instrument = fields.map(|fields| (fields, span));
+                }
+                LogSuccess | DisregardResult | ExitCode | OkOrDefault | OkOrNone
+                | RequireErrorTrait | DualOutput | BridgeSafe | Backtrace | SpanTrace
+                | WideEvent | DeferToOutermost | DeriveWithoutInfo | RedactLiterals | LogKv
+                | Flatten | TailResult | Inline | Metrics | CatchPanics | PanicOnError
+                | SelfCheck | Strict => {
+                    let value: LitBool = input.parse()?;
+                    if matches!(option, LogKv) && value.value() && !cfg!(feature = "kv") {
+                        return Err(Error::new(
+                            value.span(),
+                            "wrap_match: `log_kv` requires the `kv` feature of wrap-match",
+                        ));
+                    }
+                    if matches!(option, SpanTrace) && value.value() && !cfg!(feature = "span_trace")
+                    {
+                        return Err(Error::new(
+                            value.span(),
+                            "wrap_match: `span_trace` requires the `span_trace` feature of wrap-match",
+                        ));
+                    }
+                    if matches!(option, Metrics) && value.value() && !cfg!(feature = "metrics") {
+                        return Err(Error::new(
+                            value.span(),
+                            "wrap_match: `metrics` requires the `metrics` feature of wrap-match",
+                        ));
+                    }
+                    if matches!(option, PanicOnError) && value.value() {
+                        panic_on_error_span = Some(value.span());
+                    }
+                    if matches!(option, ExitCode) && value.value() {
+                        exit_code_span = Some(value.span());
+                    }
+                    if matches!(option, OkOrDefault) && value.value() {
+                        ok_or_default_span = Some(value.span());
+                    }
+                    if matches!(option, OkOrNone) && value.value() {
+                        ok_or_none_span = Some(value.span());
+                    }
+                    let value = value.value();
+
+                    match option {
+                        LogSuccess => options.log_success = value,
+                        DisregardResult => options.disregard_result = value,
+                        ExitCode => options.exit_code = value,
+                        OkOrDefault => options.ok_or_default = value,
+                        OkOrNone => options.ok_or_none = value,
+                        RequireErrorTrait => options.require_error_trait = value,
+                        DualOutput => options.dual_output = value,
+                        BridgeSafe => options.bridge_safe = value,
+                        Backtrace => options.backtrace = value,
+                        SpanTrace => options.span_trace = value,
+                        WideEvent => options.wide_event = value,
+                        DeferToOutermost => options.defer_to_outermost = value,
+                        DeriveWithoutInfo => options.derive_without_info = value,
+                        RedactLiterals => options.redact_literals = value,
+                        LogKv => options.log_kv = value,
+                        Flatten => options.flatten = value,
+                        TailResult => options.tail_result = value,
+                        Inline => options.inline = value,
+                        Metrics => options.metrics = value,
+                        CatchPanics => options.catch_panics = value,
+                        PanicOnError => options.panic_on_error = value,
+                        SelfCheck => options.self_check = value,
+                        Strict => options.strict = value,
+                        _ => unreachable!(),
+                    }
+                }
+            }
+
+            // remove the next comma so we can parse an ident
+            if input.peek(Token![,]) {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        if let Some(messages) = locale_messages {
+            if !custom_success {
+                options.success_message.0 = messages.success_message.to_owned();
+            }
+            if !custom_error {
+                options.error_message.0 = messages.error_message.to_owned();
+            }
+            if !custom_error_without_info {
+                options.error_message_without_info.0 =
+                    messages.error_message_without_info.to_owned();
+            }
+            if !custom_inner_error {
+                options.inner_error_message.0 = messages.inner_error_message.to_owned();
+            }
+        }
+        // the error is already in the fields, so the default messages don't repeat it
+        if let Some((ErrorStyleKind::Fields, _)) = options.error_style {
+            let messages = locale_messages.unwrap_or(locale::ENGLISH);
+            if !custom_error {
+                options.error_message.0 = messages.fields_error_message.to_owned();
+            }
+            if !custom_error_without_info {
+                options.error_message_without_info.0 = messages.fields_error_message.to_owned();
+            }
+            if !custom_inner_error {
+                options.inner_error_message.0 = messages.fields_inner_error_message.to_owned();
+            }
+        }
+        if let Some((ErrorStyleKind::Json, span)) = options.error_style {
+            if custom_error || custom_error_without_info || custom_inner_error {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `error_style = \"json\"` replaces the error messages with the JSON record, so they can't be customized",
+                ));
+            }
+        }
+        match (&options.timeout, &options.timeout_message) {
+            (Some(_), None) => {
+                options.timeout_message = Some((
+                    "{function} timed out after {timeout:?}".to_owned(),
+                    Span::call_site(),
+                ));
+            }
+            (None, Some((_, span))) => {
+                return Err(Error::new(
+                    *span,
+                    "wrap_match: `timeout_message` can only be used with `timeout`",
+                ));
+            }
+            _ => {}
+        }
+        if let (Some(span), true) = (panic_on_error_span, options.defer_to_outermost) {
+            return Err(Error::new(
+                span,
+                "wrap_match: `panic_on_error` can't be used with `defer_to_outermost`, since the functions that defer to the outermost one don't log their errors",
+            ));
+        }
+        if let Some(span) = exit_code_span {
+            if options.disregard_result || options.panic_on_error {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `exit_code` can't be used with `disregard_result` or `panic_on_error`",
+                ));
+            }
+        }
+        if let (Some(predicate), false) = (&options.log_success_if, options.log_success) {
+            return Err(Error::new(
+                predicate.span(),
+                "wrap_match: `log_success_if` can't be used with `log_success = false`",
+            ));
+        }
+        if let Some(or_else) = &options.or_else {
+            if options.disregard_result
+                || options.exit_code
+                || options.panic_on_error
+                || options.defer_to_outermost
+            {
+                return Err(Error::new(
+                    or_else.span(),
+                    "wrap_match: `or_else` can't be used with `disregard_result`, `exit_code`, `panic_on_error` or `defer_to_outermost`",
+                ));
+            }
+        }
+        if let Some(span) = ok_or_default_span {
+            if options.disregard_result
+                || options.exit_code
+                || options.panic_on_error
+                || options.or_else.is_some()
+                || options.defer_to_outermost
+            {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `ok_or_default` can't be used with `disregard_result`, `exit_code`, `panic_on_error`, `or_else` or `defer_to_outermost`",
+                ));
+            }
+        }
+        if let Some(span) = ok_or_none_span {
+            if options.disregard_result
+                || options.exit_code
+                || options.panic_on_error
+                || options.or_else.is_some()
+                || options.ok_or_default
+                || options.defer_to_outermost
+            {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `ok_or_none` can't be used with `disregard_result`, `exit_code`, `panic_on_error`, `or_else`, `ok_or_default` or `defer_to_outermost`",
+                ));
+            }
+        }
+        if let Some((_, span)) = options.exit_on_error {
+            if !options.disregard_result {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `exit_on_error` can only be used with `disregard_result = true`",
+                ));
+            }
+            if options.defer_to_outermost {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `exit_on_error` can't be used with `defer_to_outermost`, since the functions that defer to the outermost one don't log their errors",
+                ));
+            }
+            if options.panic_on_error {
+                return Err(Error::new(
+                    span,
+                    "wrap_match: `exit_on_error` can't be used with `panic_on_error`",
+                ));
+            }
+        }
+        match (options.catch_panics, &options.panic_message) {
+            (true, None) => {
+                options.panic_message =
+                    Some(("{function} panicked: {panic}".to_owned(), Span::call_site()));
+            }
+            (false, Some((_, span))) => {
+                return Err(Error::new(
+                    *span,
+                    "wrap_match: `panic_message` can only be used with `catch_panics = true`",
+                ));
+            }
+            _ => {}
+        }
+        options.custom_error_message = custom_error;
+        options.custom_error_message_without_info = custom_error_without_info;
+
+        Ok(options)
+    }
+}
+
+/// Parses the name of a level's macro (`trace`, `debug`, `info`, `warn` or `error`)
+/// Returns the option that's closest to an unknown one, if it's close enough to be a typo
+fn closest_option(unknown: &str) -> Option<&'static str> {
+    let max_distance = (unknown.len() / 3).max(1);
+    OPTION_NAMES
+        .iter()
+        .map(|&name| (edit_distance(unknown, name), name))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, name)| name)
+}
+
+/// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn parse_level(input: ParseStream) -> syn::Result<(String, Span)> {
+    let value: LitStr = input.parse()?;
+    if !matches!(
+        value.value().as_str(),
+        "trace" | "debug" | "info" | "warn" | "error"
+    ) {
+        return Err(Error::new(
+            value.span(),
+            "wrap_match: expected `trace`, `debug`, `info`, `warn` or `error`",
+        ));
+    }
+    Ok((value.value(), value.span()))
+}
+
+/// Parses a duration like `500ms`, `30s`, `5m` or `1h`, returning the number of milliseconds
+fn parse_duration(value: &LitStr) -> syn::Result<u64> {
+    let text = value.value();
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(digits);
+    let millis_per_unit = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => 0,
+    };
+    match amount.parse::<u64>() {
+        Ok(amount) if millis_per_unit != 0 => amount
+            .checked_mul(millis_per_unit)
+            .ok_or_else(|| Error::new(value.span(), "wrap_match: the duration is too long")),
+        _ => Err(Error::new(
+            value.span(),
+            "wrap_match: expected a duration like `500ms`, `30s`, `5m` or `1h`",
+        )),
+    }
+}
+
+/// Evaluates a message at compile time, since format strings have to be known at compile time. Messages can be string literals, or `concat!`,
+/// `include_str!` and `stringify!` of them (so messages can be kept in one place).
+fn evaluate_message(expr: &Expr, included_files: &mut Vec<PathBuf>) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(lit) => Ok(lit.value()),
+            Lit::Char(lit) => Ok(lit.value().to_string()),
+            Lit::Int(lit) => Ok(lit.base10_digits().to_owned()),
+            Lit::Float(lit) => Ok(lit.base10_digits().to_owned()),
+            Lit::Bool(lit) => Ok(lit.value().to_string()),
+            _ => Err(Error::new(lit.span(), "wrap_match: expected a string literal")),
+        },
+        Expr::Group(group) => evaluate_message(&group.expr, included_files),
+        Expr::Macro(ExprMacro { mac, .. }) => {
+            let name = mac.path.segments.last().map(|s| s.ident.to_string());
+            match name.as_deref() {
+                Some("concat") => {
+                    let parts = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+                    parts
+                        .iter()
+                        .map(|part| evaluate_message(part, included_files))
+                        .collect()
+                }
+                Some("stringify") => Ok(mac.tokens.to_string()),
+                Some("include_str") => {
+                    let path: Expr = mac.parse_body()?;
+                    let path = evaluate_message(&path, included_files)?;
+                    // like `include_str!`, relative paths are relative to the file the macro is used in
+                    let file = proc_macro::is_available()
+                        .then(|| mac.path.span().unwrap().local_file())
+                        .flatten();
+                    let path = match file {
+                        Some(file) => file.parent().map(|dir| dir.join(&path)).unwrap_or(path.into()),
+                        None => PathBuf::from(path),
+                    };
+                    let message = std::fs::read_to_string(&path).map_err(|e| {
+                        Error::new(mac.span(), format!("wrap_match: couldn't read `{}`: {e}", path.display()))
+                    })?;
+                    // `include_str!` paths are relative to the current file, so we need an absolute path for tracking
+                    included_files.push(path.canonicalize().unwrap_or(path));
+                    Ok(message)
+                }
+                _ => Err(Error::new(
+                    mac.span(),
+                    "wrap_match: messages must be known at compile time, so only `concat!`, `include_str!` and `stringify!` can be used",
+                )),
+            }
+        }
+        _ => Err(Error::new(
+            expr.span(),
+            "wrap_match: messages must be known at compile time (expected a string literal, a constant, `concat!`, `include_str!` or `stringify!`)",
+        )),
+    }
+}
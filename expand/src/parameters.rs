@@ -0,0 +1,135 @@
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+
+use crate::options::{DurationFormat, Options};
+
+/// The builtin parameters of the messages, besides the ones that come from the result
+pub struct Parameters {
+    /// How long the call took, formatted with `duration_format`
+    pub duration: TokenStream2,
+    /// All of the arguments, for `{args:?}`
+    pub args: TokenStream2,
+}
+
+impl Parameters {
+    pub fn new(options: &Options, arguments: &[Ident]) -> Self {
+        let duration = match options.duration_format {
+            Some((format, _)) => {
+                let format = match format {
+                    DurationFormat::Millis => quote!(Millis),
+                    DurationFormat::Seconds => quote!(Seconds),
+                    DurationFormat::Human => quote!(Human),
+                };
+                let precision = match options.duration_precision {
+                    Some((precision, _)) => quote!(Some(#precision)),
+                    None => quote!(None),
+                };
+                quote!(::wrap_match::__private::FormattedDuration::new(
+                    _wrap_match_start.elapsed(),
+                    ::wrap_match::__private::DurationFormat::#format,
+                    #precision,
+                ))
+            }
+            None => quote!(_wrap_match_start.elapsed()),
+        };
+        let names = arguments.iter().map(ToString::to_string);
+        let args = quote! {
+            ::wrap_match::__private::Args(&[#((#names, &#arguments as &dyn ::core::fmt::Debug)),*])
+        };
+        Parameters { duration, args }
+    }
+
+    /// Returns the parameters of the call, which messages about its result have after the `Ok` value or the error
+    pub fn call(&self) -> [(&'static str, TokenStream2); 7] {
+        [
+            ("duration", self.duration.clone()),
+            ("timestamp", timestamp()),
+            ("thread", thread()),
+            ("request_id", request_id()),
+            ("caller", quote!(_wrap_match_caller)),
+            ("invocation", quote!(_wrap_match_invocation)),
+            ("first_call", quote!(_wrap_match_first_call)),
+        ]
+    }
+
+    /// Returns the parameters of a timeout or a caught panic, given the parameter for the timeout or the panic message. They happen before there's an
+    /// error, so their messages only have the parameters that don't need one (and the timeout or the panic message, which are taken out before
+    /// they're converted into the error).
+    pub fn early_exit(
+        &self,
+        exit: (&'static str, TokenStream2),
+    ) -> Vec<(&'static str, TokenStream2)> {
+        [
+            exit,
+            ("timestamp", timestamp()),
+            ("thread", thread()),
+            ("request_id", request_id()),
+            ("args", self.args.clone()),
+        ]
+        .into_iter()
+        .chain(context())
+        .collect()
+    }
+}
+
+/// Returns the parameters for the contexts added with `wrap_match::context`: `{context}`, and the suffix that error messages end with
+pub fn context() -> [(&'static str, TokenStream2); 2] {
+    [
+        (
+            "context",
+            quote!(::wrap_match::__private::ActiveContext::get()),
+        ),
+        (
+            "_wrap_match_context",
+            quote!(::wrap_match::__private::ActiveContext::suffix()),
+        ),
+    ]
+}
+
+fn timestamp() -> TokenStream2 {
+    quote!(::wrap_match::__private::Timestamp::now())
+}
+
+fn thread() -> TokenStream2 {
+    quote!(::wrap_match::__private::ThreadName(::std::thread::current()))
+}
+
+fn request_id() -> TokenStream2 {
+    quote!(::wrap_match::__private::CurrentRequestId::get())
+}
+
+/// Returns the statement that starts timing the function, if a message uses `{duration}` (or the reporter gets it)
+pub fn start_timer(options: &Options) -> Option<TokenStream2> {
+    (options.wide_event || options.reporter.is_some() || options.messages_use("duration"))
+        .then(|| quote!(let _wrap_match_start = ::wrap_match::__private::Timer::start();))
+}
+
+/// Returns the statement that counts the call, if `cold_start` is set or a message needs the count
+pub fn count_invocation(options: &Options) -> Option<TokenStream2> {
+    let count_invocations = options.cold_start.is_some()
+        || options.messages_use("invocation")
+        || options.messages_use("first_call");
+    count_invocations.then(|| {
+        let cold_calls = options.cold_start.map_or(1, |(calls, _)| calls);
+        quote! {
+            let _wrap_match_invocation = {
+                static INVOCATIONS: ::wrap_match::runtime::Counter = ::wrap_match::runtime::Counter::new();
+                INVOCATIONS.increment()
+            };
+            let _wrap_match_first_call = _wrap_match_invocation <= #cold_calls;
+        }
+    })
+}
+
+/// Returns the attribute and the statement that get the caller for `{caller}`. The function is only marked `#[track_caller]` if a message actually
+/// uses it, since it changes the function's ABI.
+pub fn caller(options: &Options) -> (Option<TokenStream2>, Option<TokenStream2>) {
+    if options.messages_use("caller") {
+        (
+            Some(quote!(#[track_caller])),
+            Some(quote!(let _wrap_match_caller = ::core::panic::Location::caller();)),
+        )
+    } else {
+        (None, None)
+    }
+}
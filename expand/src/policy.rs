@@ -0,0 +1,146 @@
+//! Compile-time policy enforcement (enabled with the `policy` feature).
+//!
+//! The policy is read from the file at `WRAP_MATCH_POLICY` (relative to the crate being compiled), or `wrap-match-policy.toml` in the root of the
+//! crate being compiled if the environment variable isn't set. Every rule is denied by default, even if there is no policy file.
+
+use std::{env, fs, path::PathBuf};
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{spanned::Spanned, Error, FnArg, ItemFn, Pat};
+
+use crate::{format_string, options::Options};
+
+const DEFAULT_SENSITIVE_ARGUMENTS: &[&str] = &["password", "secret", "token"];
+
+struct Policy {
+    /// The path of the policy file, if there is one
+    path: Option<PathBuf>,
+    deny_log_success_in_libraries: bool,
+    deny_sensitive_arguments: bool,
+    sensitive_arguments: Vec<String>,
+}
+
+impl Policy {
+    fn load() -> Result<Self, String> {
+        let mut policy = Policy {
+            path: None,
+            deny_log_success_in_libraries: true,
+            deny_sensitive_arguments: true,
+            sensitive_arguments: DEFAULT_SENSITIVE_ARGUMENTS
+                .iter()
+                .map(|&s| s.to_owned())
+                .collect(),
+        };
+
+        let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+        let path = match env::var("WRAP_MATCH_POLICY") {
+            Ok(path) => manifest_dir.join(path),
+            Err(_) => manifest_dir.join("wrap-match-policy.toml"),
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Ok(policy);
+        };
+        let table: toml::Table = contents
+            .parse()
+            .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+
+        for (key, value) in &table {
+            let invalid = || format!("invalid value for `{key}` in {}", path.display());
+            match key.as_str() {
+                "log_success_in_libraries" => {
+                    policy.deny_log_success_in_libraries = parse_level(value).ok_or_else(invalid)?
+                }
+                "sensitive_arguments" => {
+                    policy.deny_sensitive_arguments = parse_level(value).ok_or_else(invalid)?
+                }
+                "sensitive_argument_names" => {
+                    policy.sensitive_arguments = value
+                        .as_array()
+                        .and_then(|names| {
+                            names
+                                .iter()
+                                .map(|name| name.as_str().map(ToOwned::to_owned))
+                                .collect()
+                        })
+                        .ok_or_else(invalid)?;
+                }
+                _ => return Err(format!("unknown rule `{key}` in {}", path.display())),
+            }
+        }
+
+        policy.path = Some(path);
+        Ok(policy)
+    }
+}
+
+/// Returns whether the rule is denied
+fn parse_level(value: &toml::Value) -> Option<bool> {
+    match value.as_str()? {
+        "deny" => Some(true),
+        "allow" => Some(false),
+        _ => None,
+    }
+}
+
+/// Checks the function and its options against the policy. On success, returns tokens that make sure the crate is recompiled when the policy file
+/// changes.
+pub fn check_policy(options: &Options, input: &ItemFn) -> syn::Result<TokenStream2> {
+    let policy = Policy::load()
+        .map_err(|e| Error::new(input.sig.ident.span(), format!("wrap_match: {e}")))?;
+
+    // binaries have `CARGO_BIN_NAME` set, and rustdoc sets `UNSTABLE_RUSTDOC_TEST_PATH` when compiling doctests
+    let is_library = env::var_os("CARGO_BIN_NAME").is_none()
+        && env::var_os("UNSTABLE_RUSTDOC_TEST_PATH").is_none()
+        && env::var("CARGO_CRATE_NAME").ok()
+            == env::var("CARGO_PKG_NAME")
+                .ok()
+                .map(|name| name.replace('-', "_"));
+    if policy.deny_log_success_in_libraries && is_library && options.log_success {
+        return Err(Error::new(
+            input.sig.ident.span(),
+            "wrap_match: policy does not allow logging on success in libraries (hint: set `log_success = false`)",
+        ));
+    }
+
+    if policy.deny_sensitive_arguments {
+        let messages = [
+            &options.success_message,
+            &options.error_message,
+            &options.error_message_without_info,
+            &options.inner_error_message,
+        ];
+        for arg in &input.sig.inputs {
+            let FnArg::Typed(arg) = arg else { continue };
+            let Pat::Ident(pat) = &*arg.pat else { continue };
+            let name = pat.ident.to_string();
+            if !policy
+                .sensitive_arguments
+                .iter()
+                .any(|sensitive| name.contains(sensitive.as_str()))
+            {
+                continue;
+            }
+            if let Some((_, span)) = messages.iter().find(|(message, _)| {
+                format_string::uses_placeholder(message, &name)
+                    || format_string::uses_placeholder(message, "args")
+            }) {
+                let mut error = Error::new(
+                    arg.span(),
+                    format!(
+                        "wrap_match: policy does not allow logging sensitive argument `{name}`"
+                    ),
+                );
+                error.combine(Error::new(*span, "the argument is logged by this message"));
+                return Err(error);
+            }
+        }
+    }
+
+    Ok(match policy.path.as_ref().and_then(|path| path.to_str()) {
+        Some(path) => quote!(
+            const _: &[u8] = include_bytes!(#path);
+        ),
+        None => quote!(),
+    })
+}
@@ -0,0 +1,110 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    spanned::Spanned, GenericArgument, Generics, PathArguments, ReturnType, Type, TypeParamBound,
+};
+
+use crate::{options::Options, signature::result_error_type};
+
+/// How the traits an error type implements are found, since types can't be resolved in a macro.
+///
+/// The caller picks the error type if it's one of the function's type parameters, so probes can't see which traits it implements. Only the fallbacks
+/// are used for it, since the compiler would pick a specialized implementation and then fail because the type parameter might not implement the trait.
+/// `Box<dyn Error>` doesn't implement `Error`, so it's probed separately (except for type parameters). Boxes with a lifetime other than `'static` get a
+/// probe that doesn't need it, since picking the other one would require the lifetime to be `'static`.
+pub struct ErrorProbes {
+    /// Whether the error type is one of the function's type parameters
+    pub is_type_param: bool,
+    /// The probe for boxed errors that fits the error type
+    pub boxed: Option<TokenStream2>,
+}
+
+impl ErrorProbes {
+    pub fn new(error_type: Option<&Type>, generics: &Generics) -> Self {
+        let is_type_param = matches!(
+            error_type,
+            Some(Type::Path(ty)) if ty.qself.is_none() && generics.type_params().any(|param| ty.path.is_ident(&param.ident))
+        );
+        let boxed = if is_type_param {
+            None
+        } else if error_type.is_some_and(is_borrowed_trait_object) {
+            Some(quote!(BorrowedBoxedErrorProbe as _,))
+        } else {
+            Some(quote!(BoxedErrorProbe as _,))
+        };
+        ErrorProbes {
+            is_type_param,
+            boxed,
+        }
+    }
+
+    /// Returns the probes for the error and its `Debug` output, if its type has them
+    pub fn imports(&self) -> TokenStream2 {
+        let boxed = &self.boxed;
+        if self.is_type_param {
+            quote!(NoDebugProbe as _, NoDynErrorProbe as _)
+        } else {
+            quote!(#boxed DebugProbe as _, NoDebugProbe as _, DynErrorProbe as _, NoDynErrorProbe as _)
+        }
+    }
+
+    /// Returns the name of the error's type, which is found by probing when the error is a `Box<dyn Error>`
+    pub fn type_name(&self, error: &TokenStream2) -> TokenStream2 {
+        let boxed = &self.boxed;
+        quote! {{
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{#boxed TypeNameProbe as _};
+            (&::wrap_match::__private::ErrorProbe(#error)).error_type_name()
+        }}
+    }
+
+    /// Returns the error as a `&dyn Error`, for placeholders that need `std::error::Error`
+    pub fn dyn_error(&self, error: &TokenStream2) -> TokenStream2 {
+        let boxed = &self.boxed;
+        quote! {{
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{#boxed ErrorTraitProbe as _};
+            (&::wrap_match::__private::ErrorProbe(#error)).as_dyn_error()
+        }}
+    }
+}
+
+/// Returns the check for `require_error_trait`
+pub fn error_trait_check(
+    options: &Options,
+    output: &ReturnType,
+    probes: &ErrorProbes,
+) -> Option<TokenStream2> {
+    let error_type = result_error_type(output).filter(|_| options.require_error_trait)?;
+    let boxed = &probes.boxed;
+    Some(quote_spanned! {error_type.span()=>
+        // never called; this only makes sure the error type implements `std::error::Error` (or is a boxed `dyn Error`)
+        let _ = |probe: ::wrap_match::__private::ErrorProbe<'_, #error_type>| {
+            #[allow(unused_imports)]
+            use ::wrap_match::__private::{#boxed ErrorTraitProbe as _};
+            let _ = (&probe).as_dyn_error();
+        };
+    })
+}
+
+/// Returns whether a type is a boxed trait object with a lifetime other than `'static`, like `Box<dyn Error + Send + Sync + 'a>`
+fn is_borrowed_trait_object(ty: &Type) -> bool {
+    let Type::Path(p) = ty else {
+        return false;
+    };
+    let Some(segment) = p.path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return false;
+    };
+    segment.ident == "Box"
+        && args.args.iter().any(|arg| match arg {
+            GenericArgument::Type(Type::TraitObject(object)) => {
+                object.bounds.iter().any(|bound| {
+                    matches!(bound, TypeParamBound::Lifetime(lifetime) if lifetime.ident != "static")
+                })
+            }
+            _ => false,
+        })
+}
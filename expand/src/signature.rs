@@ -0,0 +1,213 @@
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, spanned::Spanned, Attribute, Error, FnArg, GenericArgument, ItemFn, Meta, Pat,
+    Path, PathArguments, ReturnType, Signature, Type, Visibility,
+};
+
+use crate::options::Options;
+
+/// The arguments of a function, for calling the inner function and for messages
+pub struct Arguments {
+    /// Whether the function takes `self`
+    pub has_self: bool,
+    /// The arguments without their types, for calling the inner function
+    pub without_types: Vec<TokenStream2>,
+    /// The same, with `self`, for messages
+    pub without_types_including_self: Vec<TokenStream2>,
+    /// The names of the arguments that are identifiers, for `{args:?}` and `instrument`
+    pub idents: Vec<Ident>,
+}
+
+impl Arguments {
+    pub fn new(sig: &Signature) -> Self {
+        let mut arguments = Arguments {
+            has_self: false,
+            without_types: vec![],
+            without_types_including_self: vec![],
+            idents: vec![],
+        };
+        for arg in &sig.inputs {
+            match arg {
+                FnArg::Receiver(_) => {
+                    arguments.has_self = true;
+                    arguments.without_types_including_self.push(quote!(self));
+                }
+                FnArg::Typed(arg) => {
+                    let tokens = if let Pat::Ident(mut a) = *arg.pat.clone() {
+                        arguments.idents.push(a.ident.clone());
+                        a.attrs.clear();
+                        a.mutability = None;
+                        a.into_token_stream()
+                    } else {
+                        arg.pat.clone().into_token_stream()
+                    };
+                    arguments.without_types.push(tokens.clone());
+                    arguments.without_types_including_self.push(tokens);
+                }
+            }
+        }
+        arguments
+    }
+}
+
+/// Returns the signature of the function we make, whose return type depends on what happens to the result
+pub fn outer_signature(options: &Options, sig: &Signature) -> syn::Result<Signature> {
+    let mut outer = sig.clone();
+    let ok_type = |option: &str| {
+        result_ok_type(&sig.output).ok_or_else(|| {
+            Error::new(
+                sig.output.span(),
+                format!("wrap_match: `{option}` needs the `Ok` type to be written out, like `Result<T, E>`"),
+            )
+        })
+    };
+    if options.disregard_result {
+        outer.output = ReturnType::Default;
+    } else if options.exit_code {
+        outer.output = parse_quote!(-> ::std::process::ExitCode);
+    } else if options.ok_or_default {
+        let ok_type = ok_type("ok_or_default")?;
+        outer.output = parse_quote!(-> #ok_type);
+    } else if options.ok_or_none {
+        let ok_type = ok_type("ok_or_none")?;
+        outer.output = parse_quote!(-> ::core::option::Option<#ok_type>);
+    }
+    Ok(outer)
+}
+
+/// Turns the original function into the inner function, which the function we make calls
+pub fn make_inner_function(input: &mut ItemFn, inner_name: Ident) {
+    input.sig.ident = inner_name;
+    input.vis = Visibility::Inherited; // make sure the inner function isn't leaked to the public
+    input.sig.abi = None; // the inner function is only called from Rust, and `Result`s aren't FFI-safe
+    let body_attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| attribute_placement(attr) == AttributePlacement::Both)
+        .cloned()
+        .collect();
+    // `#[inline(always)]` can't be used with `#[target_feature]`
+    let inline = if body_attrs
+        .iter()
+        .any(|attr| attribute_name(attr).as_deref() == Some("target_feature"))
+    {
+        parse_quote!(#[inline])
+    } else {
+        parse_quote!(#[inline(always)]) // let's make sure we don't produce more overhead than we need to, the output should produce similar assembly to the input (besides the end)
+    };
+    input.attrs = vec![
+        // we will put the original attributes on the function we make
+        // we also don't want the inner function to appear in docs or autocomplete (if they do, they should be deprecated and give a warning if they are used)
+        parse_quote!(#[doc(hidden)]),
+        parse_quote!(#[deprecated = "inner function for wrap-match. Please do not use!"]),
+        inline,
+        // `WrapMatchError` carries location info along with the error, but the inner function is inlined so its size doesn't matter
+        parse_quote!(#[allow(clippy::result_large_err)]),
+    ];
+    input.attrs.extend(body_attrs);
+}
+
+/// Where an attribute of the original function goes
+#[derive(PartialEq, Eq)]
+enum AttributePlacement {
+    /// Only the function we make, which keeps the original function's name, signature and ABI. This is for docs and attributes that affect the
+    /// function's symbol (`no_mangle`, `export_name`, `link_section`, ...), which would conflict if they were on both functions.
+    Outer,
+    /// Both functions, since the body is in the inner function: codegen attributes that affect the body, `cfg` and lint levels. `target_feature` also
+    /// stays on the function we make, so it can call the inner function safely.
+    Both,
+}
+
+fn attribute_placement(attr: &Attribute) -> AttributePlacement {
+    match attribute_name(attr).as_deref() {
+        Some(
+            "target_feature" | "track_caller" | "optimize" | "instruction_set" | "cfg" | "allow"
+            | "warn" | "deny" | "forbid",
+        ) => AttributePlacement::Both,
+        // `expect` stays on the function we make, since it would be unfulfilled on one of them
+        _ => AttributePlacement::Outer,
+    }
+}
+
+/// Returns the name of an attribute, looking inside `#[unsafe(...)]`
+pub fn attribute_name(attr: &Attribute) -> Option<String> {
+    let path = attr.path();
+    if path.is_ident("unsafe") {
+        let inner: Meta = attr.parse_args().ok()?;
+        return inner.path().get_ident().map(ToString::to_string);
+    }
+    path.get_ident().map(ToString::to_string)
+}
+
+/// Returns the success type of a function that returns a `Result`, if it can be determined
+pub fn result_ok_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(p) = &**ty else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(GenericArgument::Type(ty)) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the error type of a function that returns a `Result`, if it can be determined
+pub fn result_error_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    result_type_error(ty)
+}
+
+/// Returns the error type of a `Result` type, if it can be determined
+pub fn result_type_error(ty: &Type) -> Option<&Type> {
+    let Type::Path(p) = ty else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 2 => match args.args.last() {
+            Some(GenericArgument::Type(ty)) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the path of the error type without generics, so `level_for` variants can be written without the error type (like `NotFound`)
+pub fn error_type_path(output: &ReturnType, is_type_param: bool) -> Option<Path> {
+    match result_error_type(output) {
+        Some(Type::Path(ty)) if ty.qself.is_none() && !is_type_param => {
+            let mut path = ty.path.clone();
+            if let Some(last) = path.segments.last_mut() {
+                last.arguments = PathArguments::None;
+            }
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether a type is `Infallible` or `!`, going by its name since types can't be resolved in a macro
+pub fn is_infallible(ty: &Type) -> bool {
+    match ty {
+        Type::Never(_) => true,
+        Type::Path(p) => {
+            p.qself.is_none()
+                && p.path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "Infallible")
+        }
+        Type::Paren(p) => is_infallible(&p.elem),
+        Type::Group(g) => is_infallible(&g.elem),
+        _ => false,
+    }
+}
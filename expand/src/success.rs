@@ -0,0 +1,169 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::{
+    emit_in_order,
+    error::{json_record, report_error},
+    log_statement::{build_log_statement, Level, LogContext},
+    options::Options,
+    parameters, Wrapper,
+};
+
+/// Returns the statements for when the call returns `Ok`, which log, count and report the success. With `flatten`, `Ok(Err(...))` is logged as an
+/// error instead.
+pub fn handle_ok(wrapper: &Wrapper) -> TokenStream2 {
+    let options = &wrapper.options;
+    let function = wrapper.name.to_string();
+    let emit_order = options.emit_order.as_ref().map(|(order, _)| order);
+    // the metrics counters are incremented whether or not the result is logged
+    let count_success = options
+        .metrics
+        .then(|| quote!(::wrap_match::__private::count_success(#function);));
+    let report_success = options.reporter.is_some().then(|| {
+        let duration = &wrapper.parameters.duration;
+        quote! {
+            ::wrap_match::__private::report_success(#function, ::core::module_path!(), #duration);
+        }
+    });
+    let emit_success = emit_in_order(
+        emit_order,
+        &log_success(wrapper),
+        &count_success,
+        &report_success,
+    );
+    if !options.flatten {
+        return emit_success;
+    }
+
+    let inner_error = quote!(_wrap_match_inner_error);
+    let probes = &wrapper.inner_probes;
+    let count_inner_error = options.metrics.then(|| {
+        let error_type = probes.type_name(&inner_error);
+        quote! {
+            ::wrap_match::__private::count_error(#function, #error_type);
+        }
+    });
+    let report_inner_error = options
+        .reporter
+        .is_some()
+        .then(|| report_error(wrapper, &inner_error, probes, quote!(None), quote!(None)));
+    let emit_inner_error = emit_in_order(
+        emit_order,
+        &log_inner_error(wrapper),
+        &count_inner_error,
+        &report_inner_error,
+    );
+    quote! {
+        match &_wrap_match_ok {
+            Ok(_wrap_match_ok) => { #emit_success }
+            Err(_wrap_match_inner_error) => { #emit_inner_error }
+        }
+    }
+}
+
+/// Returns what the function returns for the `Ok` value
+pub fn ok_value(options: &Options) -> TokenStream2 {
+    if options.exit_code {
+        quote!(::std::process::ExitCode::SUCCESS)
+    } else if options.ok_or_default {
+        quote!(_wrap_match_ok)
+    } else if options.ok_or_none {
+        quote!(Some(_wrap_match_ok))
+    } else if !options.disregard_result {
+        quote!(Ok(_wrap_match_ok))
+    } else {
+        quote!()
+    }
+}
+
+fn log_success(wrapper: &Wrapper) -> Option<TokenStream2> {
+    let options = &wrapper.options;
+    if !options.log_success || options.log_to_reporter_only() {
+        return None;
+    }
+    let level = match &options.success_level {
+        Some((level, _)) => {
+            let level = format_ident!("{level}");
+            quote!(#level)
+        }
+        None => quote!(info),
+    };
+    let log_success = build_log_statement(
+        &options.success_message,
+        &[("ok", quote!(_wrap_match_ok))]
+            .into_iter()
+            .chain(wrapper.parameters.call())
+            .chain([("args", wrapper.parameters.args.clone())])
+            .collect::<Vec<_>>(),
+        None,
+        &Level::Static(level),
+        &wrapper.contexts.success,
+    );
+    // with `log_success_if`, the success is only logged if the predicate is true for the `Ok` value, which is borrowed (with `flatten`, it's
+    // already a reference to the inner `Ok` value)
+    Some(match &options.log_success_if {
+        Some(predicate) => {
+            let ok = if options.flatten {
+                quote!(_wrap_match_ok)
+            } else {
+                quote!(&_wrap_match_ok)
+            };
+            quote! {
+                if ::wrap_match::__private::log_success_if(#ok, #predicate) {
+                    #log_success
+                }
+            }
+        }
+        None => log_success,
+    })
+}
+
+fn log_inner_error(wrapper: &Wrapper) -> Option<TokenStream2> {
+    let options = &wrapper.options;
+    if options.log_to_reporter_only() {
+        return None;
+    }
+    let inner_error = quote!(_wrap_match_inner_error);
+    let json_record = options.json_errors().then(|| {
+        (
+            "json",
+            json_record(
+                wrapper,
+                &inner_error,
+                &wrapper.inner_probes,
+                wrapper.function_location.clone(),
+                quote!(None),
+                quote!(None),
+            ),
+        )
+    });
+    let level = format_ident!("{}", options.error_level_name());
+    let context = &wrapper.contexts.base;
+    Some(build_log_statement(
+        &options.inner_error_message,
+        &[("error", inner_error.clone())]
+            .into_iter()
+            .chain(wrapper.parameters.call())
+            .chain([("args", wrapper.parameters.args.clone())])
+            .chain(parameters::context())
+            .chain(json_record)
+            .collect::<Vec<_>>(),
+        None,
+        &Level::Static(quote!(#level)),
+        &LogContext {
+            fields: context
+                .fields
+                .iter()
+                .cloned()
+                .chain(wrapper.error_fields.then(|| quote!(error = ?#inner_error)))
+                .collect(),
+            kv: context
+                .kv
+                .iter()
+                .cloned()
+                .chain(options.log_kv.then(|| quote!(error:? = #inner_error)))
+                .collect(),
+            ..context.clone()
+        },
+    ))
+}
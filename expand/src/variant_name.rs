@@ -0,0 +1,31 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error};
+
+/// Implements `wrap_match::VariantName` for an enum
+pub fn derive_variant_name(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "wrap_match: `VariantName` can only be derived for enums",
+        ));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let variants = data.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let ident_str = ident.to_string();
+        quote!(Self::#ident { .. } => #ident_str)
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::wrap_match::VariantName for #name #ty_generics #where_clause {
+            fn variant_name(&self) -> &'static str {
+                match *self {
+                    #(#variants,)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,173 @@
+use quote::quote;
+
+#[test]
+fn expand_wrap_match() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(success_message = "ran {function}"),
+        quote! {
+            fn my_function() -> Result<(), ()> {
+                Err(())?;
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(expanded.contains("fn my_function () -> Result < () , () >"));
+    assert!(expanded.contains("\"ran my_function\""));
+    assert!(expanded.contains("line_and_expr"));
+    assert!(!expanded.contains("compile_error"));
+}
+
+#[test]
+fn expand_errors() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(option = 1),
+        quote! {
+            fn my_function() -> Result<(), ()> {
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(expanded.contains("compile_error"));
+    assert!(expanded.contains("unknown configuration option"));
+
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(),
+        quote!(
+            struct NotAFunction;
+        ),
+    );
+    assert!(expanded.to_string().contains("compile_error"));
+}
+
+#[test]
+fn expand_derives() {
+    let expanded = wrap_match_expand::variant_name(quote! {
+        enum Error {
+            NotFound,
+        }
+    })
+    .to_string();
+    assert!(expanded.contains("\"NotFound\""));
+
+    let expanded = wrap_match_expand::wrap_match_level(quote! {
+        enum Error {
+            #[wrap_match(level = "warn")]
+            NotFound,
+        }
+    })
+    .to_string();
+    assert!(expanded.contains("Warn"));
+}
+
+#[test]
+fn expand_redact_literals() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(redact_literals = true),
+        quote! {
+            fn my_function(client: &Client) -> Result<(), ()> {
+                client.login("user", "hunter2", 'x', 3, vec![b"key"; 2])?;
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(!expanded.contains("compile_error"));
+    assert!(expanded.contains(r#""client.login(\"***\", \"***\", '*', _, vec![\"***\"; _])""#));
+    // the call itself is still there, but not in the expression's text
+    assert!(!expanded.contains(r#"\"hunter2\""#));
+}
+
+#[test]
+fn expand_hashed_expressions() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(expr_privacy = "hash"),
+        quote! {
+            fn my_function() -> Result<(), ()> {
+                connect("db")?;
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(!expanded.contains(r#""connect(\"db\")""#));
+
+    // the map is made from the source files, with the same hashes as the expansion
+    let dir = std::env::temp_dir().join(format!("wrap-match-expr-map-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("db")).unwrap();
+    std::fs::write(
+        dir.join("lib.rs"),
+        r#"
+            #[wrap_match::wrap_match(expr_privacy = "hash")]
+            fn my_function() -> Result<(), ()> {
+                connect("db")?;
+                Ok(())
+            }
+
+            #[wrap_match::wrap_match]
+            fn plain_function() -> Result<(), ()> {
+                connect("db")?;
+                Ok(())
+            }
+        "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("db").join("pool.rs"),
+        r#"
+            impl Pool {
+                #[wrap_match(expr_privacy = "hash", redact_literals = true)]
+                fn get(&self) -> Result<(), ()> {
+                    self.connect("db")?;
+                    Ok(())
+                }
+            }
+        "#,
+    )
+    .unwrap();
+    let map = wrap_match_expand::expr_map(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    let map = map.unwrap();
+    let lines: Vec<_> = map
+        .lines()
+        .map(|line| line.split('\t').collect::<Vec<_>>())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    // subdirectories come before `lib.rs`, since entries are sorted by path
+    assert_eq!(lines[0][1..], ["get", r#"self.connect("***")"#]);
+    assert_eq!(lines[1][1..], ["my_function", r#"connect("db")"#]);
+    assert!(expanded.contains(&format!("\"{}\"", lines[1][0])));
+}
+
+#[test]
+fn expand_infallible() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(),
+        quote! {
+            fn my_function() -> Result<(), Infallible> {
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(!expanded.contains("compile_error"));
+    assert!(!expanded.contains("An error occurred"));
+}
+
+#[test]
+fn expand_enabled_when() {
+    let expanded = wrap_match_expand::wrap_match(
+        quote!(enabled_when = feature = "observability"),
+        quote! {
+            fn my_function() -> Result<(), ()> {
+                Ok(())
+            }
+        },
+    )
+    .to_string();
+    assert!(expanded.starts_with(
+        r#"# [cfg (not (feature = "observability"))] fn my_function () -> Result < () , () > { Ok (()) }"#
+    ));
+    assert!(expanded.contains(r#"# [cfg (feature = "observability")]"#));
+}
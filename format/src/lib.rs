@@ -0,0 +1,254 @@
+//! A small lexer for format strings, so escaped braces (`{{` and `}}`) are never treated as placeholders. This is what `wrap_match` uses to find the
+//! placeholders in messages, and it is re-exported as `wrap_match::format`. Everything here is pure, so it can be tested on its own.
+//!
+//! See <https://doc.rust-lang.org/stable/std/fmt/index.html#syntax>
+
+/// A piece of a format string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Piece<'a> {
+    /// Text that is printed as-is (escaped braces are kept escaped)
+    Literal(&'a str),
+    /// A placeholder, such as `{error:?}`
+    Placeholder {
+        /// The argument of the placeholder, such as `error` or `request.user_id` (this is empty for `{}` and `{:?}`)
+        argument: &'a str,
+        /// The format spec of the placeholder, including the `:` (this is empty for `{error}`)
+        spec: &'a str,
+    },
+}
+
+impl Piece<'_> {
+    /// Writes the piece back into a format string
+    pub fn write_to(&self, output: &mut String) {
+        match self {
+            Piece::Literal(text) => output.push_str(text),
+            Piece::Placeholder { argument, spec } => {
+                output.push('{');
+                output.push_str(argument);
+                output.push_str(spec);
+                output.push('}');
+            }
+        }
+    }
+}
+
+/// Splits a format string into literals and placeholders. Unmatched braces are left in literals so `format_args!` can report them.
+pub fn parse(input: &str) -> Vec<Piece<'_>> {
+    let mut pieces = vec![];
+    let mut literal_start = 0;
+    let mut i = 0;
+    let bytes = input.as_bytes();
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'}' if bytes.get(i + 1) == Some(&bytes[i]) => i += 2,
+            b'{' => {
+                let Some(len) = input[i + 1..].find('}') else {
+                    break;
+                };
+                let contents = &input[i + 1..i + 1 + len];
+                if contents.contains('{') {
+                    // not a valid placeholder, so let `format_args!` complain about it
+                    i += 1;
+                    continue;
+                }
+                if literal_start < i {
+                    pieces.push(Piece::Literal(&input[literal_start..i]));
+                }
+                let (argument, spec) =
+                    contents.split_at(contents.find(':').unwrap_or(contents.len()));
+                pieces.push(Piece::Placeholder { argument, spec });
+                i += len + 2;
+                literal_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    if literal_start < input.len() {
+        pieces.push(Piece::Literal(&input[literal_start..]));
+    }
+    pieces
+}
+
+/// Returns the arguments of all named placeholders (such as `{name}` or `{name:?}`) in the input, without duplicates
+pub fn named_arguments(input: &str) -> Vec<&str> {
+    let mut names = vec![];
+    for piece in parse(input) {
+        if let Piece::Placeholder { argument, .. } = piece {
+            if !argument.is_empty()
+                && !argument.starts_with(|c: char| c.is_ascii_digit())
+                && !names.contains(&argument)
+            {
+                names.push(argument);
+            }
+        }
+    }
+    names
+}
+
+/// Returns whether the input has a placeholder for `name` (including placeholders that access its fields, such as `{name.field}`)
+pub fn uses_placeholder(input: &str, name: &str) -> bool {
+    parse(input).iter().any(|piece| match piece {
+        Piece::Placeholder { argument, .. } => argument.split('.').next() == Some(name),
+        Piece::Literal(_) => false,
+    })
+}
+
+/// Replaces every plain `{name}` placeholder with some text, escaping any braces in it
+pub fn substitute(input: &str, name: &str, value: &str) -> String {
+    let escaped = value.replace('{', "{{").replace('}', "}}");
+    let mut output = String::with_capacity(input.len());
+    for piece in parse(input) {
+        match piece {
+            Piece::Placeholder { argument, spec: "" } if argument == name => {
+                output.push_str(&escaped)
+            }
+            piece => piece.write_to(&mut output),
+        }
+    }
+    output
+}
+
+/// Renames every placeholder for `from` to `to`, keeping its format spec and field accesses (so `{from.field:?}` becomes `{to.field:?}`)
+pub fn rename(input: &str, from: &str, to: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for piece in parse(input) {
+        match piece {
+            Piece::Placeholder { argument, spec } if argument.split('.').next() == Some(from) => {
+                let argument = format!("{to}{}", &argument[from.len()..]);
+                Piece::Placeholder {
+                    argument: &argument,
+                    spec,
+                }
+                .write_to(&mut output);
+            }
+            piece => piece.write_to(&mut output),
+        }
+    }
+    output
+}
+
+/// Returns whether the input has conditional sections, like `{?line}(on line {line}){/line}`
+pub fn has_sections(input: &str) -> bool {
+    parse(input).iter().any(|piece| {
+        matches!(piece, Piece::Placeholder { argument, spec: "" } if argument.starts_with('?'))
+    })
+}
+
+/// Resolves the conditional sections in the input, like `{?line}(on line {line}){/line}`. The text of a section is kept if `keep` returns `true` for
+/// its name and left out otherwise, and the markers are always removed. Sections can be nested. Returns an error message if a section isn't closed
+/// (or is closed with the wrong name).
+pub fn resolve_sections(input: &str, keep: impl Fn(&str) -> bool) -> Result<String, String> {
+    resolve_sections_except(input, keep, &[])
+}
+
+/// Like [`resolve_sections`], but sections named in `except` are left in the output (markers included), so they can be resolved later
+pub fn resolve_sections_except(
+    input: &str,
+    keep: impl Fn(&str) -> bool,
+    except: &[&str],
+) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    // the names of the open sections, and whether they are kept
+    let mut open: Vec<(&str, bool)> = vec![];
+    for piece in parse(input) {
+        let kept = !matches!(open.last(), Some((_, false)));
+        match piece {
+            Piece::Placeholder { argument, spec: "" } if argument.starts_with('?') => {
+                let name = &argument[1..];
+                if except.contains(&name) {
+                    open.push((name, kept));
+                    if kept {
+                        piece.write_to(&mut output);
+                    }
+                } else {
+                    open.push((name, kept && keep(name)));
+                }
+            }
+            Piece::Placeholder { argument, spec: "" } if argument.starts_with('/') => {
+                let name = &argument[1..];
+                match open.pop() {
+                    Some((open_name, _)) if open_name == name => {
+                        if kept && except.contains(&name) {
+                            piece.write_to(&mut output);
+                        }
+                    }
+                    Some((open_name, _)) => {
+                        return Err(format!(
+                            "`{{/{name}}}` closes the section `{{?{open_name}}}`"
+                        ))
+                    }
+                    None => return Err(format!("`{{/{name}}}` doesn't close a section")),
+                }
+            }
+            piece if kept => piece.write_to(&mut output),
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some((name, _)) => Err(format!("the section `{{?{name}}}` isn't closed")),
+        None => Ok(output),
+    }
+}
+
+/// Removes the parenthesized or bracketed clauses (and the space before them) that contain placeholders for any of `names`. Returns `None` if one of
+/// the placeholders isn't in a clause.
+pub fn remove_clauses(input: &str, names: &[&str]) -> Option<String> {
+    let mut open = vec![];
+    let mut clauses = vec![];
+    let mut placeholders = vec![];
+    let mut offset = 0;
+    for piece in parse(input) {
+        match piece {
+            Piece::Literal(text) => {
+                for (i, c) in text.char_indices() {
+                    match c {
+                        '(' | '[' => open.push((c, offset + i)),
+                        ')' | ']' => {
+                            let expected = if c == ')' { '(' } else { '[' };
+                            if let Some(position) = open.iter().rposition(|(c, _)| *c == expected) {
+                                let (_, start) = open[position];
+                                open.truncate(position);
+                                clauses.push(start..offset + i + 1);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                offset += text.len();
+            }
+            Piece::Placeholder { argument, spec } => {
+                if names.contains(&argument.split('.').next().unwrap_or_default()) {
+                    placeholders.push(offset);
+                }
+                offset += argument.len() + spec.len() + 2;
+            }
+        }
+    }
+
+    let mut removed: Vec<std::ops::Range<usize>> = vec![];
+    for placeholder in placeholders {
+        // the innermost clause is the shortest one that contains the placeholder
+        let clause = clauses
+            .iter()
+            .filter(|clause| clause.contains(&placeholder))
+            .min_by_key(|clause| clause.len())?;
+        let start = if input[..clause.start].ends_with(' ') {
+            clause.start - 1
+        } else {
+            clause.start
+        };
+        removed.push(start..clause.end);
+    }
+
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    removed.sort_by_key(|range| range.start);
+    for range in removed {
+        if range.start >= last {
+            output.push_str(&input[last..range.start]);
+        }
+        last = last.max(range.end);
+    }
+    output.push_str(&input[last..]);
+    Some(output)
+}
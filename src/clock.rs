@@ -0,0 +1,161 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+static CLOCK: RwLock<&'static dyn Clock> = RwLock::new(&SystemClock);
+
+/// A source of time for `{duration}`. See [`set_clock`].
+pub trait Clock: Send + Sync {
+    /// The current time, as the time since some fixed point (such as when the clock was created)
+    fn now(&self) -> Duration;
+}
+
+/// The default clock, which uses [`Instant`].
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock that only moves when it is told to, so tests can make assertions about `{duration}`.
+///
+/// ```
+/// static CLOCK: wrap_match::MockClock = wrap_match::MockClock::new();
+/// wrap_match::set_clock(&CLOCK);
+/// CLOCK.advance(std::time::Duration::from_millis(1500));
+/// ```
+pub struct MockClock {
+    nanos: AtomicU64,
+}
+
+impl MockClock {
+    pub const fn new() -> Self {
+        Self {
+            nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Moves the clock forward
+    pub fn advance(&self, duration: Duration) {
+        self.nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+/// Sets the clock used for `{duration}` in all wrapped functions. The clock is global, so tests that depend on it shouldn't run in parallel with
+/// each other. Use [`SystemClock`] to go back to the default clock.
+pub fn set_clock(clock: &'static dyn Clock) {
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = clock;
+}
+
+/// When a wrapped function started running. The clock is only read from the lock once per call, and the same clock measures the end, even if
+/// another one is set in the meantime.
+pub struct Timer {
+    clock: &'static dyn Clock,
+    start: Duration,
+}
+
+impl Timer {
+    pub fn start() -> Self {
+        let clock = *CLOCK.read().unwrap_or_else(|e| e.into_inner());
+        Self {
+            clock,
+            start: clock.now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start)
+    }
+}
+
+/// How `{duration}` is formatted with `duration_format`
+#[derive(Clone, Copy)]
+pub enum DurationFormat {
+    Millis,
+    Seconds,
+    Human,
+}
+
+/// A duration formatted for `duration_format`. It formats the same way with `{duration}` and `{duration:?}`, so the messages that only supported
+/// `Debug` before keep working.
+pub struct FormattedDuration {
+    duration: Duration,
+    format: DurationFormat,
+    precision: Option<usize>,
+}
+
+impl FormattedDuration {
+    pub fn new(duration: Duration, format: DurationFormat, precision: Option<usize>) -> Self {
+        Self {
+            duration,
+            format,
+            precision,
+        }
+    }
+}
+
+impl fmt::Display for FormattedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let duration = self.duration;
+        match self.format {
+            DurationFormat::Millis => write!(
+                f,
+                "{:.*}",
+                self.precision.unwrap_or(3),
+                duration.as_secs_f64() * 1e3
+            ),
+            DurationFormat::Seconds => {
+                write!(
+                    f,
+                    "{:.*}",
+                    self.precision.unwrap_or(3),
+                    duration.as_secs_f64()
+                )
+            }
+            DurationFormat::Human => {
+                let precision = self.precision.unwrap_or(1);
+                let secs = duration.as_secs();
+                if secs >= 3600 {
+                    write!(f, "{}h {}m", secs / 3600, secs % 3600 / 60)
+                } else if secs >= 60 {
+                    write!(f, "{}m {}s", secs / 60, secs % 60)
+                } else if secs >= 1 {
+                    write!(f, "{:.*}s", precision, duration.as_secs_f64())
+                } else if duration.as_millis() >= 1 {
+                    write!(f, "{:.*}ms", precision, duration.as_secs_f64() * 1e3)
+                } else if duration.as_micros() >= 1 {
+                    write!(f, "{:.*}µs", precision, duration.as_secs_f64() * 1e6)
+                } else {
+                    write!(f, "{}ns", duration.as_nanos())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FormattedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
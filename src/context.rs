@@ -0,0 +1,100 @@
+use std::{borrow::Cow, cell::RefCell, fmt, marker::PhantomData};
+
+thread_local! {
+    /// The contexts that are currently active on this thread, outermost first
+    static CONTEXT: RefCell<Vec<Cow<'static, str>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Adds context to the errors logged by wrapped functions until the returned guard is dropped, like `anyhow`'s `context` but without changing
+/// error types.
+///
+/// Error messages get the active contexts (outermost first) at the end, like `(context: syncing user 42 > loading profile)`, unless they use
+/// `{context}` to put them somewhere else. Contexts are per thread, so in async code, don't hold a guard across an `.await`.
+///
+/// ```
+/// # #[derive(Debug)] enum CustomError { Error }
+/// #[wrap_match::wrap_match]
+/// fn load_profile() -> Result<(), CustomError> {
+///     Err(CustomError::Error)?;
+///     Ok(())
+/// }
+///
+/// let _context = wrap_match::context("syncing user 42");
+/// // logs "An error occurred when running load_profile (caused by `Err(CustomError::Error)` on line 4): Error (context: syncing user 42)"
+/// load_profile().unwrap_err();
+/// ```
+pub fn context(context: impl Into<Cow<'static, str>>) -> ContextGuard {
+    CONTEXT.with(|contexts| {
+        let mut contexts = contexts.borrow_mut();
+        contexts.push(context.into());
+        ContextGuard {
+            depth: contexts.len() - 1,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Removes a context added with [`context`] when it is dropped.
+#[must_use = "the context is removed when the guard is dropped"]
+pub struct ContextGuard {
+    depth: usize,
+    // the context belongs to the thread that added it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // this also removes contexts that were added after this one, in case their guards were leaked
+        CONTEXT.with(|contexts| contexts.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// The active contexts, for `{context}` (and the end of error messages that don't use it)
+pub struct ActiveContext {
+    contexts: Vec<Cow<'static, str>>,
+    suffix: bool,
+}
+
+impl ActiveContext {
+    pub fn get() -> Self {
+        Self {
+            contexts: CONTEXT.with(|contexts| contexts.borrow().clone()),
+            suffix: false,
+        }
+    }
+
+    /// Formats as ` (context: ...)`, or nothing if there aren't any contexts
+    pub fn suffix() -> Self {
+        Self {
+            suffix: true,
+            ..Self::get()
+        }
+    }
+}
+
+impl fmt::Display for ActiveContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.contexts.is_empty() {
+            return Ok(());
+        }
+        if self.suffix {
+            f.write_str(" (context: ")?;
+        }
+        for (i, context) in self.contexts.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            f.write_str(context)?;
+        }
+        if self.suffix {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ActiveContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.contexts).finish()
+    }
+}
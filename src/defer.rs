@@ -0,0 +1,220 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A function that deferred logging an error, and the line the error occurred on
+type Frame = (&'static str, Option<u32>);
+
+thread_local! {
+    /// How many functions with `defer_to_outermost` are currently running on this thread (or in the task it is polling)
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// The frames that deferred logging an error to an outer function, innermost first
+    static CHAIN: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    /// Whether a task's depth and chain are swapped in, so scopes further down the task use them instead of their own
+    static IN_SCOPE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A running function with `defer_to_outermost`. Dropping it forgets any errors that inner functions deferred to it, unless it deferred its own error.
+pub struct DeferFrame {
+    chain_len: usize,
+    deferred: Cell<bool>,
+}
+
+impl DeferFrame {
+    pub fn enter() -> Self {
+        DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Self {
+            chain_len: CHAIN.with(|chain| chain.borrow().len()),
+            deferred: Cell::new(false),
+        }
+    }
+
+    /// Whether no other function with `defer_to_outermost` is running above this one, so there is no outer frame to defer to
+    pub fn is_outermost(&self) -> bool {
+        DEPTH.with(Cell::get) == 1
+    }
+
+    /// Leaves logging the error to an outer function, adding this function to the chain
+    pub fn defer(&self, function: &'static str, line: Option<u32>) {
+        CHAIN.with(|chain| chain.borrow_mut().push((function, line)));
+        self.deferred.set(true);
+    }
+
+    /// Takes the frames that deferred to this function, keeping at most `max_frames` of the innermost ones
+    pub fn take_chain(&self, max_frames: Option<usize>, outermost_first: bool) -> Chain {
+        let mut frames = CHAIN.with(|chain| chain.borrow_mut().split_off(self.chain_len));
+        let omitted = match max_frames {
+            Some(max_frames) => frames.drain(max_frames.min(frames.len())..).count(),
+            None => 0,
+        };
+        if outermost_first {
+            frames.reverse();
+        }
+        Chain {
+            frames,
+            omitted,
+            outermost_first,
+        }
+    }
+}
+
+impl Drop for DeferFrame {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+        if !self.deferred.get() {
+            CHAIN.with(|chain| chain.borrow_mut().truncate(self.chain_len));
+        }
+    }
+}
+
+/// The body of an async function with `defer_to_outermost`. The depth and chain belong to the task instead of the thread: the scope that is polled
+/// first (the outermost wrapped function of the task) swaps its own depth and chain in while it is polled or dropped, so they follow the task across
+/// `.await`s and threads, and other tasks on the same thread don't see them. Wrapped functions that are called while it is polled (async or not) use
+/// them too.
+pub struct DeferScope<F> {
+    future: Option<Pin<Box<F>>>,
+    depth: usize,
+    chain: Vec<Frame>,
+}
+
+impl<F: Future> DeferScope<F> {
+    pub fn new(future: F) -> Self {
+        Self {
+            future: Some(Box::pin(future)),
+            depth: 0,
+            chain: vec![],
+        }
+    }
+
+    /// Runs `f` with this task's depth and chain, unless a scope further up the task is already running
+    fn enter<R>(&mut self, f: impl FnOnce(&mut Option<Pin<Box<F>>>) -> R) -> R {
+        if IN_SCOPE.with(Cell::get) {
+            return f(&mut self.future);
+        }
+        let _swapped = Swapped::new(&mut self.depth, &mut self.chain);
+        f(&mut self.future)
+    }
+}
+
+impl<F: Future> Future for DeferScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().enter(|future| {
+            let output = future
+                .as_mut()
+                .expect("polled after completion")
+                .as_mut()
+                .poll(cx);
+            if output.is_ready() {
+                // the frames are dropped while the task's depth is swapped in
+                *future = None;
+            }
+            output
+        })
+    }
+}
+
+impl<F> Drop for DeferScope<F> {
+    fn drop(&mut self) {
+        // a cancelled function drops its frame, which has to leave the task's depth and not the thread's
+        if self.future.is_some() {
+            if IN_SCOPE.with(Cell::get) {
+                self.future = None;
+            } else {
+                let _swapped = Swapped::new(&mut self.depth, &mut self.chain);
+                self.future = None;
+            }
+        }
+    }
+}
+
+/// Swaps a task's depth and chain with the thread's, and back when it is dropped (even if polling panicked)
+struct Swapped<'a> {
+    depth: &'a mut usize,
+    chain: &'a mut Vec<Frame>,
+}
+
+impl<'a> Swapped<'a> {
+    fn new(depth: &'a mut usize, chain: &'a mut Vec<Frame>) -> Self {
+        let mut swapped = Self { depth, chain };
+        swapped.swap();
+        IN_SCOPE.with(|in_scope| in_scope.set(true));
+        swapped
+    }
+
+    fn swap(&mut self) {
+        DEPTH.with(|depth| *self.depth = depth.replace(*self.depth));
+        CHAIN.with(|chain| mem::swap(self.chain, &mut chain.borrow_mut()));
+    }
+}
+
+impl Drop for Swapped<'_> {
+    fn drop(&mut self) {
+        self.swap();
+        IN_SCOPE.with(|in_scope| in_scope.set(false));
+    }
+}
+
+/// The functions an error propagated through before reaching the outermost function, for `{chain}`
+pub struct Chain {
+    frames: Vec<Frame>,
+    /// How many of the outermost frames were left out because of `chain_max_frames`
+    omitted: usize,
+    outermost_first: bool,
+}
+
+impl Chain {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let omitted = (self.omitted != 0).then(|| format!("{} more", self.omitted));
+        if self.outermost_first {
+            if let Some(omitted) = &omitted {
+                write!(f, "{omitted} -> ")?;
+            }
+        }
+        for (i, (function, line)) in self.frames.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" -> ")?;
+            }
+            f.write_str(function)?;
+            if let Some(line) = line {
+                write!(f, " (line {line})")?;
+            }
+        }
+        if !self.outermost_first {
+            if let Some(omitted) = &omitted {
+                write!(f, " -> {omitted}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats as ` (propagated through ...)`, or nothing if the chain is empty
+pub struct ChainSuffix<'a>(pub &'a Chain);
+
+impl fmt::Display for ChainSuffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        write!(f, " (propagated through {})", self.0)
+    }
+}
+
+impl fmt::Debug for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
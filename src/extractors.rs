@@ -0,0 +1,76 @@
+use std::{
+    any::Any,
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+type Extractor = Arc<dyn Fn(&dyn Any) -> Option<String> + Send + Sync>;
+
+static EXTRACTORS: RwLock<Vec<(&'static str, Extractor)>> = RwLock::new(Vec::new());
+
+/// Registers an extractor for the custom placeholder `{name}`.
+///
+/// When an error message contains a placeholder declared with `extract(...)`, wrap-match will ask the extractor registered under that name for a
+/// value. The extractor receives the error (as [`Any`], so you can downcast it to your error type) and returns
+/// `None` if it doesn't know how to handle it. For `Box<dyn Error>` errors, it receives the error inside the box if its type was registered with
+/// [`register_error_type`](crate::register_error_type). Registering an extractor with a name that is already registered replaces the old one.
+///
+/// See [custom placeholders](crate#custom-placeholders) for more info.
+pub fn register_extractor(
+    name: &'static str,
+    extractor: impl Fn(&dyn Any) -> Option<String> + Send + Sync + 'static,
+) {
+    let mut extractors = EXTRACTORS.write().unwrap_or_else(|e| e.into_inner());
+    extractors.retain(|(n, _)| *n != name);
+    extractors.push((name, Arc::new(extractor)));
+}
+
+/// Panics if one of a function's custom placeholders doesn't have an extractor, for the `self_check` option
+pub fn check_extractors(function: &str, placeholders: &[&str]) {
+    let extractors = EXTRACTORS.read().unwrap_or_else(|e| e.into_inner());
+    let missing: Vec<_> = placeholders
+        .iter()
+        .filter(|placeholder| !extractors.iter().any(|(name, _)| name == *placeholder))
+        .map(|placeholder| format!("`{{{placeholder}}}`"))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "wrap_match: the error messages of {function} use {}, but no extractor is registered for {} (see `wrap_match::register_extractor`)",
+        missing.join(", "),
+        if missing.len() == 1 { "it" } else { "them" },
+    );
+}
+
+/// The value of a custom placeholder. Formats as `<unknown>` if no extractor returned a value.
+pub struct Extracted(Option<String>);
+
+impl Extracted {
+    pub fn new(name: &str, error: &dyn Any) -> Self {
+        // the lock isn't held while the extractor runs, since it could log an error of a function that uses an extractor, or register one
+        let extractor = EXTRACTORS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, extractor)| Arc::clone(extractor));
+        Self(extractor.and_then(|extractor| extractor(crate::probes::extractor_input(error))))
+    }
+}
+
+impl fmt::Display for Extracted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(value) => fmt::Display::fmt(value, f),
+            None => f.write_str("<unknown>"),
+        }
+    }
+}
+
+impl fmt::Debug for Extracted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(value) => fmt::Debug::fmt(value, f),
+            None => f.write_str("<unknown>"),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! The record for `error_style = "json"`.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::{__private::SourceLocation, defer::Chain, timestamp::Timestamp};
+
+/// An error as a single line of JSON. The keys are always in the same order, and values that aren't known are `null`.
+pub struct JsonRecord<'a> {
+    pub function: &'static str,
+    pub module: &'static str,
+    pub location: SourceLocation,
+    pub expr: Option<&'a str>,
+    pub error_type: &'static str,
+    /// The error's `Debug` output
+    pub error: Option<String>,
+    /// The error's `Display` output, if it implements `Error`
+    pub display: Option<String>,
+    /// The functions the error propagated through, with `defer_to_outermost`. Records of other functions don't have the key.
+    pub chain: Option<&'a Chain>,
+}
+
+impl Display for JsonRecord<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (file, line, column) = match self.location {
+            SourceLocation::File { file, line, column } => (Some(file), Some(line), Some(column)),
+            SourceLocation::Site { .. } => (None, None, None),
+        };
+        write!(f, "{{\"timestamp\":\"{}\"", Timestamp::now())?;
+        f.write_str(",\"function\":")?;
+        write_string(f, Some(self.function))?;
+        f.write_str(",\"module\":")?;
+        write_string(f, Some(self.module))?;
+        f.write_str(",\"file\":")?;
+        write_string(f, file)?;
+        f.write_str(",\"line\":")?;
+        write_number(f, line)?;
+        f.write_str(",\"column\":")?;
+        write_number(f, column)?;
+        f.write_str(",\"expr\":")?;
+        write_string(f, self.expr)?;
+        f.write_str(",\"error_type\":")?;
+        write_string(f, Some(self.error_type))?;
+        f.write_str(",\"error\":")?;
+        write_string(f, self.error.as_deref())?;
+        f.write_str(",\"error_display\":")?;
+        write_string(f, self.display.as_deref())?;
+        if let Some(chain) = self.chain {
+            f.write_str(",\"chain\":")?;
+            let chain = (!chain.is_empty()).then(|| chain.to_string());
+            write_string(f, chain.as_deref())?;
+        }
+        f.write_char('}')
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: Option<&str>) -> fmt::Result {
+    let Some(value) = value else {
+        return f.write_str("null");
+    };
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+fn write_number(f: &mut Formatter<'_>, value: Option<u32>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "{value}"),
+        None => f.write_str("null"),
+    }
+}
@@ -11,10 +11,19 @@ wrap-match is an attribute macro that wraps your function in a `match` statement
 This allows you to know exactly what line and expression caused the error.
 
 wrap-match supports both `log` and `tracing`. It defaults to `log`, but it will use `tracing` if the `tracing` feature is enabled. See [`tracing` support](#tracing-support) for more info.
+The `log` feature is enabled by default; `tracing` users can turn off default features so `log` isn't compiled. Without either feature, messages are written to
+stderr (see [`logger`](#logger)).
 
 > **Note**
 >
-> wrap-match uses the `log` or `tracing` crate to log success and error messages. It does not expose the `log` or `tracing` crate for expanded functions to use; you must depend on them yourself.
+> Before the `log` feature existed, `log` was always used. If you already had `default-features = false` (which used to have no effect), and you log through `log`,
+> add `features = ["log"]`, or your messages will go to stderr with `eprintln!` instead of your `log` implementation. To catch this, functions that don't set
+> [`logger`](#logger) get a deprecation warning when neither feature is enabled; use `logger = "plain"` if stderr is what you want.
+
+> **Note**
+>
+> wrap-match uses the `log` or `tracing` crate to log success and error messages. Expanded functions use wrap-match's own dependency on them, so you don't have to
+> depend on them yourself (but you probably will, to set up logging and log your own messages).
 >
 > Additionally, **no messages will appear unless you use a logging implementation or `tracing` subscriber.** For `log`, I recommend `env_logger`, but you can find a full list
 > [here](https://docs.rs/log/#available-logging-implementations). For `tracing`, I recommend `tracing-subscriber`, but you can find a full list [here](https://docs.rs//tracing/#related-crates).
@@ -27,13 +36,11 @@ First, add this to your `Cargo.toml`:
 [dependencies]
 # For log users:
 wrap-match = "1"
-log = "*"
 # You'll also want a logging implementation, for example `env_logger`
 # More info here: https://docs.rs/log/#available-logging-implementations
 
 # For tracing users:
-wrap-match = { version = "1", features = ["tracing"] }
-tracing = "0.1"
+wrap-match = { version = "1", default-features = false, features = ["tracing"] }
 # You'll also want a `tracing` subscriber, for example `tracing-subscriber`
 # More info here: https://docs.rs//tracing/#related-crates
 ```
@@ -96,7 +103,8 @@ As you can see, wrap-match makes error logging extremely easy while still loggin
 Added in wrap-match 1.0.5, wrap-match supports `tracing` if the `tracing` feature is enabled. wrap-match **does not** do anything with spans. Additionally, you will not be able to manually create
 spans in functions you use wrap-match on. This is because the span will be dropped before wrap-match logs anything.
 
-To put both the function and wrap-match logs in a span, you have to use the `tracing::instrument` attribute macro. The ordering of the attribute macros is important; **it must go after wrap-match**.
+To put both the function and wrap-match logs in a span, use the [`instrument`](#instrument) option. You can also use the `tracing::instrument` attribute macro,
+but the ordering of the attribute macros is important; **it must go after wrap-match**.
 
 Example:
 
@@ -109,10 +117,64 @@ fn my_function() -> Result<(), ()> {
 }
 ```
 
+Every function's events get their own callsite name, `wrap_match <function>:<line>` (where `<line>` is the line the function's name is on), so filters, samplers and
+layers that look at an event's [metadata](https://docs.rs/tracing/latest/tracing/struct.Metadata.html#method.name) can target individual wrap-match sites. For
+example, the events for `my_function` above would be named `wrap_match my_function:3`.
+
+Since features are shared by every crate in the dependency graph, the `tracing` feature only picks the default. Individual functions can choose with the
+[`logger`](#logger) option.
+
 ## Customization
 
 wrap-match allows the user to customize success and error messages, as well as choosing whether or not to log anything on success.
 
+Messages are format strings, so literal braces have to be escaped as `{{` and `}}`, like in `format!`. Escaped braces are never treated as placeholders, so
+messages can contain JSON snippets such as `{{"error": "{error}"}}`.
+
+Messages can also be constants, `concat!`, `include_str!` or `stringify!`. This lets you keep message templates in one place:
+
+```ignore
+#[wrap_match::wrap_match(error_message = include_str!("messages/sync-failed.txt"))]
+fn sync_db() -> Result<(), CustomError> {
+    // ...
+}
+
+#[wrap_match::wrap_match(success_message = concat!("[db] ", "Successfully ran {function}"))]
+fn migrate_db() -> Result<(), CustomError> {
+    // ...
+}
+
+const CLEAR_FAILED: &str = "failed to clear the cache";
+
+#[wrap_match::wrap_match(error_message = CLEAR_FAILED)]
+fn clear_cache() -> Result<(), CustomError> {
+    // ...
+}
+```
+
+Since the value of a constant isn't known when the macro runs, it is logged as-is: placeholders in it aren't filled in and braces don't have to be escaped.
+`message_prefix` and `message_suffix` can still use placeholders. Other expressions, like function calls, can't be used as messages.
+
+Parts of a message can be made conditional with sections like `{?line}(on line {line}){/line}`: the text between `{?name}` and `{/name}` is only kept if
+the message has the `name` parameter, so one message can work with and without line and expression info. `line`, `expr`, `backtrace` and `span_trace`
+are only available in `error_message`, `{?chain}` sections are left out when the chain of [`defer_to_outermost`](#defer_to_outermost) is empty, and
+sections for any other name are always kept. If `error_message` has sections, it is also used as
+`error_message_without_info` (unless that is given too), with the sections that need line and expression info left out:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(error_message = "{function} failed{?line} (caused by `{expr}` on line {line}){/line}: {error:?}")]
+fn my_function(fail_early: bool) -> Result<(), CustomError> {
+    if fail_early {
+        // logs "my_function failed: Error"
+        return Err(CustomError::Error.into());
+    }
+    // logs "my_function failed (caused by `Err(CustomError::Error)` on line 7): Error"
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
 ### `success_message`
 
 The message that's logged on success.
@@ -120,6 +182,20 @@ The message that's logged on success.
 Available format parameters:
 
 -   `function`: The original function name. **Note**: You can only use `{function}`; other formats such as `{function:?}` are not supported.
+-   `ok`: The value the function returned. The `Ok` type only needs to implement `Debug`/`Display` if the format that requires it is used. You can also access fields
+    of the value, for example `{ok.id}` or `{ok.0:?}`. `{return}` can be used instead of `{ok}`, like `{return:?}` or `{return.id}` (only here, since the other
+    messages don't have an `{ok}`).
+-   `duration`: How long the function took to run, as a [`Duration`](std::time::Duration). Only `Debug` formats are supported, for example `{duration:?}`,
+    unless [`duration_format`](#duration_format-and-duration_precision) is set. The time
+    comes from the [`Clock`] set with [`set_clock`], so tests can use a [`MockClock`].
+-   `timestamp`: When the message was logged, in RFC 3339 format (in UTC, with milliseconds), like `2024-05-01T12:30:00.250Z`. This is for loggers
+    that don't add timestamps themselves, like the `eprintln!` fallback.
+-   `thread`: The name of the thread the function ran on, or its id (like `ThreadId(2)`) if it doesn't have a name.
+-   `request_id`: The ID of the request the function ran for, set with [`with_request_id`], or `-` if there isn't one.
+-   `invocation`: How many times the function has been called, including this call (starting at 1).
+-   `first_call`: Whether this call is one of the first calls of the function (see [`cold_start`](#cold_start)).
+-   `caller`: The location the function was called from, like `src/main.rs:12:5`. Using this marks the function `#[track_caller]`, so it can't be used in
+    async functions.
 -   [Function arguments](#using-function-arguments-in-messages)
 
 Default value: `Successfully ran {function}`
@@ -143,13 +219,39 @@ This would log:
 ### `error_message`
 
 The message that's logged on error, when line and expression info **is** available. Currently, this is only for try expressions (expressions with a `?` after them).
+If you set this on a function without any try expressions, you will get a warning, since it can never be used.
 
 Available format parameters:
 
 -   `function`: The original function name. **Note**: You can only use `{function}`; other formats such as `{function:?}` are not supported.
 -   `line`: The line the error occurred on.
 -   `expr`: The expression that caused the error.
+-   `backtrace`: The backtrace captured when the error occurred. Only available when [`backtrace`](#backtrace) is enabled.
+-   `span_trace`: The spans that were entered when the error occurred. Only available when [`span_trace`](#span_trace) is enabled.
 -   `error`: The error.
+-   `error_type`: The name of the error's type. See [below](#error_type) for more info.
+-   `variant`: The name of the error's enum variant. The error type must implement [`VariantName`].
+-   `duration`: How long the function took to run, as a [`Duration`](std::time::Duration). Only `Debug` formats are supported, for example `{duration:?}`,
+    unless [`duration_format`](#duration_format-and-duration_precision) is set. The time
+    comes from the [`Clock`] set with [`set_clock`], so tests can use a [`MockClock`].
+-   `timestamp`: When the message was logged, in RFC 3339 format (in UTC, with milliseconds), like `2024-05-01T12:30:00.250Z`. This is for loggers
+    that don't add timestamps themselves, like the `eprintln!` fallback.
+-   `thread`: The name of the thread the function ran on, or its id (like `ThreadId(2)`) if it doesn't have a name.
+-   `request_id`: The ID of the request the function ran for, set with [`with_request_id`], or `-` if there isn't one.
+-   `invocation`: How many times the function has been called, including this call (starting at 1).
+-   `first_call`: Whether this call is one of the first calls of the function (see [`cold_start`](#cold_start)).
+-   `location`: Where the error occurred, like `src/main.rs:12:5`. If that isn't known (see `error_message_without_info`), the location of the function is
+    used instead. For generated code (such as code a build script writes to `OUT_DIR` that is then `include!`d), file locations aren't useful, so a logical
+    location is used instead: the function's name and the index of the `?` in it (starting at 1, or 0 for the function itself), like `my_function#2`.
+-   `caller`: The location the function was called from, like `src/main.rs:12:5`. Using this marks the function `#[track_caller]`, so it can't be used in
+    async functions.
+-   `chain`: The functions the error propagated through before reaching this one. Only available when [`defer_to_outermost`](#defer_to_outermost) is enabled.
+-   `sources`: The chain of errors that caused the error (from [`Error::source`](std::error::Error::source)), like `caused by: X; caused by: Y`. The error type
+    must implement `std::error::Error` (boxed `dyn Error`s are also accepted).
+-   `root_cause`: The deepest error in the chain of errors that caused the error, or the error itself if it has no source. The error type must implement
+    `std::error::Error` (boxed `dyn Error`s are also accepted).
+-   `context`: The contexts added with [`context()`], outermost first, like `syncing user 42 > loading profile`. If the message doesn't use it, the
+    contexts are added to the end of the message, like ` (context: syncing user 42)`.
 -   [Function arguments](#using-function-arguments-in-messages)
 
 Default value: `` An error occurred when running {function} (caused by `{expr}` on line {line}): {error:?} ``
@@ -179,6 +281,29 @@ Available format parameters:
 
 -   `function`: The original function name. **Note**: You can only use `{function}`; other formats such as `{function:?}` are not supported.
 -   `error`: The error.
+-   `error_type`: The name of the error's type. See [below](#error_type) for more info.
+-   `variant`: The name of the error's enum variant. The error type must implement [`VariantName`].
+-   `duration`: How long the function took to run, as a [`Duration`](std::time::Duration). Only `Debug` formats are supported, for example `{duration:?}`,
+    unless [`duration_format`](#duration_format-and-duration_precision) is set. The time
+    comes from the [`Clock`] set with [`set_clock`], so tests can use a [`MockClock`].
+-   `timestamp`: When the message was logged, in RFC 3339 format (in UTC, with milliseconds), like `2024-05-01T12:30:00.250Z`. This is for loggers
+    that don't add timestamps themselves, like the `eprintln!` fallback.
+-   `thread`: The name of the thread the function ran on, or its id (like `ThreadId(2)`) if it doesn't have a name.
+-   `request_id`: The ID of the request the function ran for, set with [`with_request_id`], or `-` if there isn't one.
+-   `invocation`: How many times the function has been called, including this call (starting at 1).
+-   `first_call`: Whether this call is one of the first calls of the function (see [`cold_start`](#cold_start)).
+-   `location`: Where the error occurred, like `src/main.rs:12:5`. If that isn't known (see `error_message_without_info`), the location of the function is
+    used instead. For generated code (such as code a build script writes to `OUT_DIR` that is then `include!`d), file locations aren't useful, so a logical
+    location is used instead: the function's name and the index of the `?` in it (starting at 1, or 0 for the function itself), like `my_function#2`.
+-   `caller`: The location the function was called from, like `src/main.rs:12:5`. Using this marks the function `#[track_caller]`, so it can't be used in
+    async functions.
+-   `chain`: The functions the error propagated through before reaching this one. Only available when [`defer_to_outermost`](#defer_to_outermost) is enabled.
+-   `sources`: The chain of errors that caused the error (from [`Error::source`](std::error::Error::source)), like `caused by: X; caused by: Y`. The error type
+    must implement `std::error::Error` (boxed `dyn Error`s are also accepted).
+-   `root_cause`: The deepest error in the chain of errors that caused the error, or the error itself if it has no source. The error type must implement
+    `std::error::Error` (boxed `dyn Error`s are also accepted).
+-   `context`: The contexts added with [`context()`], outermost first, like `syncing user 42 > loading profile`. If the message doesn't use it, the
+    contexts are added to the end of the message, like ` (context: syncing user 42)`.
 -   [Function arguments](#using-function-arguments-in-messages)
 
 Default value: `An error occurred when running {function}: {error:?}`
@@ -199,27 +324,34 @@ This would log:
 [ERROR] oh no, my_function failed with this error: Error
 ```
 
-### `log_success`
+### `derive_without_info`
 
-If `false`, nothing will be logged on success.
+Sets `error_message_without_info` to `error_message` without the clause that mentions `{expr}` and `{line}`, so you only have to write one error message. The
+clause must be in parentheses or brackets (the space before it is also removed). This replaces `error_message_without_info` if it was also given.
 
-Default value: `true`
+Default value: `false`
 
 Example:
 
 ```
 # #[derive(Debug)] enum CustomError { Error }
-#[wrap_match::wrap_match(log_success = false)]
+#[wrap_match::wrap_match(error_message = "{function} failed [at `{expr}` on line {line}]: {error:?}", derive_without_info = true)]
 fn my_function() -> Result<(), CustomError> {
-    Ok(())
+    Err(CustomError::Error.into())
 }
 ```
 
-This would log nothing.
+This would log:
+
+```log
+[ERROR] my_function failed: Error
+```
 
-### `disregard_result`
+### `redact_literals`
 
-If `true`, the resulting function will return `()` and throw away whatever the `Result` is. Useful for `main` functions.
+If `true`, the literals in the expressions used for `{expr}` are replaced at compile time, so secrets in them (like `client.login("user", "hunter2")?`) don't
+end up in logs. Strings become `"***"`, characters become `'*'` and numbers become `_`. This applies everywhere `{expr}` is used, including
+[`wide_event`](#wide_event) and [`log_kv`](#log_kv).
 
 Default value: `false`
 
@@ -227,76 +359,2047 @@ Example:
 
 ```
 # #[derive(Debug)] enum CustomError { Error }
-#[wrap_match::wrap_match(disregard_result = true)]
-fn main() -> Result<(), CustomError> {
+# struct Client;
+# impl Client { fn login(&self, user: &str, password: &str) -> Result<(), CustomError> { Err(CustomError::Error) } }
+#[wrap_match::wrap_match(redact_literals = true)]
+fn my_function(client: &Client) -> Result<(), CustomError> {
+    client.login("user", "hunter2")?;
     Ok(())
 }
 ```
 
-The `main` function would be turned into this:
+This would log:
+
+```log
+[ERROR] An error occurred when running my_function (caused by `client.login("***", "***")` on line 3): Error
+```
+
+### `expr_privacy`
+
+How expressions are shown in `{expr}`: `"plain"` shows the expression's code, and `"hash"` shows a short hash of it instead, for binaries that shouldn't leak
+the structure of their source code. The hashes stay the same across builds as long as the function's name and the expression don't change.
+
+To map hashes back to expressions, call `wrap_match_expand::expr_map` with your source directory, for example from a build script (add `wrap-match-expand`
+with the same version as wrap-match to your `[build-dependencies]`). It finds the functions that use `expr_privacy = "hash"` and returns a tab-separated
+map (the hash, the function and the expression), which you can keep with your release instead of shipping it in the binary:
 
 ```ignore
+// build.rs
 fn main() {
-    fn _wrap_match_inner_main() -> Result<(), WrapMatchError<CustomError>> {
-        Ok(())
-    }
+    println!("cargo:rerun-if-changed=src");
+    let map = wrap_match_expand::expr_map("src").unwrap();
+    std::fs::write("wrap-match-expr-map.tsv", map).unwrap();
+}
+```
 
-    match _wrap_match_inner_main() {
-        // the Result would be logged like normal, but it is not returned
-    }
+Default value: `"plain"`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(expr_privacy = "hash")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
 }
 ```
 
-## Using function arguments in messages
+This would log:
 
-As of wrap-match 1.0.5, you can use function arguments in messages, as long as they weren't moved/dropped. You should only use this for references and items that implement `Copy`.
+```log
+[ERROR] An error occurred when running my_function (caused by `1f0c9a3e` on line 3): Error
+```
+
+### `message_prefix` and `message_suffix`
+
+Text that is added to the start or end of every message (including the default messages), so you don't have to repeat a component tag in all three messages.
+They can use the same format parameters as the messages they are added to.
+
+Default value: none
 
 Example:
 
-```rust
-#[wrap_match::wrap_match(success_message = "Success! input = {input}")]
-fn my_function(input: i64) -> Result<i64, ()> {
-    Ok(input)
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(message_prefix = "[ingest] ")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
 }
 ```
 
-## Limitations
+This would log:
 
-wrap-match currently has the following limitations:
+```log
+[ERROR] [ingest] An error occurred when running my_function: Error
+```
 
-1.  ~~wrap-match cannot be used on functions in implementations that take a `self` parameter. If you need support for this, please create a GitHub issue with your use case.~~ This is now supported!
-    However, it does require wrap-match to move the inner function out of the generated one, so it will add a new method to the implementation. This method is marked as deprecated, made private, and
-    is not shown in documentation. Hopefully this won't cause any issues.
+### `locale`
 
-1.  wrap-match only supports `Result`s. If you need support for `Option`s, please create a GitHub issue with your use case.
+The language of the default messages, for teams whose logs have to be in a language other than English. Messages you give yourself are not changed, and level
+names (like `ERROR`) come from your logger, so they aren't translated either.
 
-1.  ~~`error_message` and `error_message_without_info` only support formatting `error` using the `Debug` or `Display` formatters. This is because of how we determine what formatting specifiers are
-    used. If you need support for other formatting specifiers, please create a GitHub issue with your use case.~~ All format parameters (except `function`) now support all basic formats that `format!`
-    supports (however, features such as precision, sign, fill, alignment and width will most likely never be supported)..
+Available locales: `en`, `de` (German), `es` (Spanish) and `fr` (French)
 
-1.  wrap-match cannot be used on `const` functions. This is because the `log` crate cannot be used in `const` contexts.
+Default value: `en`
 
-If wrap-match doesn't work for something not on this list, please create a GitHub issue!
-*/
+Example:
 
-#[doc(inline)]
-pub use wrap_match_impl::wrap_match;
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(locale = "de")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+```
 
-// Not public API.
-#[doc(hidden)]
-pub mod __private {
-    #[doc(hidden)]
-    pub struct WrapMatchError<'lt, E> {
-        pub line_and_expr: Option<(u32, &'lt str)>,
-        pub inner: E,
+This would log:
+
+```log
+[ERROR] Fehler beim Ausführen von my_function: Error
+```
+
+### `duration_format` and `duration_precision`
+
+How `{duration}` is formatted. By default, it is the `Debug` format of the [`Duration`](std::time::Duration) (like `1.5s` or `250µs`), which picks its
+unit by itself, so log pipelines that parse durations can't rely on it. With `duration_format`, `{duration}` and `{duration:?}` are formatted the same way:
+
+-   `ms`: The number of milliseconds, like `1234.567`
+-   `s`: The number of seconds, like `1.235`
+-   `human`: With a unit that fits the duration, like `850ns`, `1.5ms`, `12.3s` or `2m 30s`
+
+`duration_precision` is the number of digits after the decimal point, which defaults to `3` for `ms` and `s` and `1` for `human`. Durations of a minute or
+more are always formatted without decimals with `human`. `duration_precision` can only be used with `duration_format`.
+
+Default value: none (the `Debug` format)
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(
+    duration_format = "ms",
+    duration_precision = 1,
+    success_message = "{function} succeeded duration_ms={duration}"
+)]
+fn my_function() -> Result<(), CustomError> {
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+[INFO] my_function succeeded duration_ms=0.2
+```
+
+### `owner`
+
+The owner of the function, for example the team that maintains it. This is available as `{owner}` in all messages, and when using `tracing`, it is also added to
+every event as the `owner` field so alerts can be routed to the right team.
+
+Default value: none
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(owner = "payments-team", error_message_without_info = "[{owner}] {function} failed: {error:?}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+```
+
+This would log:
+
+```log
+[ERROR] [payments-team] my_function failed: Error
+```
+
+### `event_name`
+
+The name of the `tracing` events that are emitted on error, instead of the default [callsite name](#tracing-support). This is useful for backends that key on event
+names rather than message text. Since `tracing` needs event names at compile time, only the `{function}` and `{owner}` placeholders can be used. This is ignored
+when logging through `log`.
+
+Default value: none
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(event_name = "{function}.failed")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+Errors from this function would be emitted as events named `my_function.failed`.
+
+### `message_key`
+
+The key of this function's messages in the [message catalog](#message-catalogs). Requires the `catalog` feature.
+
+Default value: none
+
+### `catalog`
+
+The path of the [message catalog](#message-catalogs) (relative to the crate being compiled), for functions that use a different catalog than the rest of the
+crate.
+
+Default value: none
+
+### `target`
+
+The target of the log statements (or `tracing` events), instead of the module path. This is useful if your log routing filters on targets. Since targets are
+needed at compile time, only the `{function}`, `{owner}` and `{module}` (the module path) placeholders can be used. The compact lines from
+[`dual_output`](#dual_output) always use the `wrap_match::machine` target.
+
+Default value: none (the module path)
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(target = "audit::{module}::{function}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+```
+
+Errors from this function would be logged with a target like `audit::my_crate::my_function`.
+
+### `success_level` and `error_level`
+
+The levels that successes and errors are logged at: `trace`, `debug`, `info`, `warn` or `error`. This is useful for hot paths where logging every success at
+`info` is too chatty, or for functions whose errors are really warnings. `error_level` cannot be used with [`level_fn`](#level_fn). With
+[`level_from_error`](#level_from_error), the level chosen by the error type is used instead of `error_level`.
+
+Default value: `info` for `success_level`, `error` for `error_level`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(success_level = "trace", error_level = "warn")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+```
+
+This would log:
+
+```log
+[WARN] An error occurred when running my_function: Error
+```
+
+### `level_for`
+
+Levels for specific variants of the error type, so they can be logged at a lower severity while everything else is logged at the
+[`error_level`](#success_level-and-error_level). Variants can be written without the error type (like `NotFound`), or as full paths (like
+`std::io::ErrorKind::NotFound`) if the error type is something else. `level_for` cannot be used with [`level_fn`](#level_fn).
+
+Default value: none
+
+Example:
+
+```
+# #[cfg(not(any(feature = "log", feature = "tracing")))] fn main() {}
+# #[cfg(any(feature = "log", feature = "tracing"))] fn main() {
+# #[derive(Debug)] enum CustomError { NotFound, Timeout(u64), Other }
+#[wrap_match::wrap_match(level_for(NotFound = "warn", Timeout = "info"))]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+# }
+```
+
+This would log:
+
+```log
+[WARN] An error occurred when running my_function (caused by `Err(CustomError::NotFound)` on line 3): NotFound
+```
+
+### `exhaustive`
+
+If `true`, [`level_for`](#level_for) has to list every variant of the error type, so adding a variant without choosing its level is a compile error
+instead of it being logged at the [`error_level`](#success_level-and-error_level). It can only be used with `level_for`, and not with error types that
+are `#[non_exhaustive]` (since they can't be matched exhaustively outside of their crate). To check this for a [`WrapMatchLevel`] derive instead, put
+`#[wrap_match(exhaustive = true)]` on the enum.
+
+Default value: `false`
+
+Example:
+
+```
+# #[cfg(not(any(feature = "log", feature = "tracing")))] fn main() {}
+# #[cfg(any(feature = "log", feature = "tracing"))] fn main() {
+# #[derive(Debug)] enum CustomError { NotFound, Timeout(u64), Other }
+#[wrap_match::wrap_match(level_for(NotFound = "warn", Timeout = "info", Other = "error"), exhaustive = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+# }
+```
+
+If `CustomError` gets another variant, this will fail to compile until the variant is added to `level_for`.
+
+### `level_from_error`
+
+If `true`, errors are logged at the level chosen by the error type's [`WrapMatchLevel`] implementation (which can be derived), falling back to the
+[`error_level`](#success_level-and-error_level) if the error type doesn't implement it. [`level_for`](#level_for) takes precedence over it. This is opt-in
+since a level that's only known at runtime makes every error statement check the level, and with `tracing` needs a callsite for every level. It cannot be
+used with [`level_fn`](#level_fn) or with error types that are type parameters.
+
+Default value: `false`
+
+Example:
+
+```
+# #[cfg(not(any(feature = "log", feature = "tracing")))] fn main() {}
+# #[cfg(any(feature = "log", feature = "tracing"))] fn main() {
+# #[derive(Debug, wrap_match::WrapMatchLevel)] #[wrap_match(level = "warn")] enum DbError { NotFound }
+#[wrap_match::wrap_match(level_from_error = true)]
+fn my_function() -> Result<(), DbError> {
+    Err(DbError::NotFound)?;
+    Ok(())
+}
+# }
+```
+
+If `DbError` derives `WrapMatchLevel` with `#[wrap_match(level = "warn")]`, this would log:
+
+```log
+[WARN] An error occurred when running my_function (caused by `Err(DbError::NotFound)` on line 3): NotFound
+```
+
+### `level_fn`
+
+A function that chooses the level errors are logged at. It receives a reference to the error and must return a `log::Level` (or a `tracing::Level` if the
+function logs through `tracing`, see [`logger`](#logger)).
+
+Default value: none (errors are logged at the [`error_level`](#success_level-and-error_level))
+
+Example:
+
+```
+# #[cfg(not(all(feature = "log", not(feature = "tracing"))))] fn main() {}
+# #[cfg(all(feature = "log", not(feature = "tracing")))] fn main() {
+# #[derive(Debug)] enum CustomError { NotFound, Other }
+fn error_level(error: &CustomError) -> log::Level {
+    match error {
+        CustomError::NotFound => log::Level::Warn,
+        _ => log::Level::Error,
     }
+}
 
-    impl<E> From<E> for WrapMatchError<'_, E> {
-        fn from(inner: E) -> Self {
-            Self {
-                line_and_expr: None,
-                inner,
+#[wrap_match::wrap_match(level_fn = error_level)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+# }
+```
+
+### `logger`
+
+The crate to log through, either `"log"` or `"tracing"`, or `"plain"` to write messages to stderr with `eprintln!`. This overrides the default picked by the
+enabled features, which is useful when some crates in the dependency graph want `log` and others want `tracing`. The crate's feature still needs to be enabled
+(`log` is enabled by default), and [`bridge_safe`](#bridge_safe) can only be used with `"log"`.
+
+`"plain"` doesn't need any features, so tiny tools can turn off default features to avoid depending on a logging crate. Plain messages are always written, since
+they don't have levels, so [`level_fn`](#level_fn) and [`level_for`](#level_for) can't be used with them. Targets and structured fields are ignored.
+
+Default value: `"tracing"` if the `tracing` feature is enabled, `"log"` if the `log` feature is enabled, otherwise `"plain"` (with a deprecation warning)
+
+Example:
+
+```
+# #[cfg(not(feature = "log"))] fn main() {}
+# #[cfg(feature = "log")] fn main() {
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(logger = "log")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+# }
+```
+
+### `instrument`
+
+If `true`, wrap-match enters a span named after the function (at the `info` level, like `tracing::instrument`) around the function's body and its own log
+statements, so everything the function logs is in the span. Instead of `true`, a list of arguments can be given, like `instrument(user_id, request)`, to
+record them as fields of the span with their `Debug` implementations. For async functions, the span is entered every time the function is polled. This
+requires the `tracing` feature.
+
+Default value: `false`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(instrument(user_id))]
+fn my_function(user_id: u64) -> Result<(), CustomError> {
+    tracing::info!("hello from the span!");
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+ INFO my_function{user_id=1}: hello from the span!
+ERROR my_function{user_id=1}: An error occurred when running my_function (caused by `Err(CustomError::Error)` on line 4): Error
+```
+
+### `enabled_when`
+
+A `cfg` predicate, like `feature = "observability"`, that has to be true for the function to be wrapped. It is checked in the crate using wrap-match, so
+libraries can make their logging optional with one of their own features instead of wrapping every `#[wrap_match]` in a `cfg_attr`. When the predicate is
+false, the function is left as it was written. `any(...)`, `all(...)` and `not(...)` work like they do in `cfg`.
+
+Default value: none (functions are always wrapped)
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(enabled_when = feature = "observability")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would only log if the crate's `observability` feature is enabled.
+
+### `log_kv`
+
+If `true`, records logged through `log` also get the function's name, the [`owner`](#owner) and (for errors) the line, expression and error as key-values,
+so log collectors that output JSON get machine-readable fields instead of having to parse messages. The error is captured with its `Debug` implementation. This
+requires the `kv` feature, which enables the `kv` feature of `log`. It has no effect when logging through `tracing`, which gets structured fields with
+[`wide_event`](#wide_event) and [`error_style`](#error_style).
+
+Default value: `false`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(log_kv = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+The error record would have the key-values `function = "my_function"`, `line = 3`, `expr = "Err(CustomError::Error)"` and `error = Error`.
+
+### `fields`
+
+Constant key-values that are added to every message of the function (on success and on error), like `fields(component = "importer", tenant = "acme")`.
+The values must be literals. When logging through `tracing`, they are added as structured fields. When logging through `log`, they are added as key-values,
+which requires the `kv` feature (but not [`log_kv`](#log_kv)). Plain messages ignore them.
+
+Default value: none
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(fields(component = "importer", tenant = "acme"))]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log (when using `tracing`):
+
+```log
+ERROR my_function: An error occurred when running my_function (caused by `Err(CustomError::Error)` on line 3): Error component="importer" tenant="acme"
+```
+
+### `metrics`
+
+If `true`, the function's results are counted with the [`metrics`](https://docs.rs/metrics) facade: `wrap_match_success_total` is incremented with a
+`function` label on success, and `wrap_match_error_total` is incremented with `function` and `error_type` labels (like `{error_type}`) on error. With
+[`flatten`](#flatten-and-inner_error_message), `Ok(Err(...))` counts as an error. The counters are incremented whether or not the result is logged. This
+requires the `metrics` feature.
+
+Default value: `false`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(metrics = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would increment `wrap_match_error_total{function="my_function", error_type="my_crate::CustomError"}`.
+
+### `reporter`
+
+Sends the function's results to the [`Reporter`] set with [`set_reporter`], so failures can be routed to custom sinks. `"also"` sends them in addition to
+logging them, and `"instead"` only sends them to the reporter. The reporter gets a [`SuccessInfo`] on success and an [`ErrorInfo`] (with the error, its type
+and, for errors from a `?`, the location and expression) on error. With [`flatten`](#flatten-and-inner_error_message), `Ok(Err(...))` is sent as an error.
+If the `serde` feature is enabled, both implement `Serialize`, so they can be sent as they are.
+
+Default value: none (results are only logged)
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(reporter = "instead")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+### `emit_order`
+
+The order that a result's log statements, [`metrics`](#metrics) counters and hooks (the [`reporter`](#reporter), [Sentry](#sentry) and
+[OpenTelemetry](#opentelemetry)) are emitted in. Each of `log`, `metrics` and `hook` has to be listed once. This matters when a hook can panic or exit the
+process: with the default order, the counters are always incremented, but the error is only logged after the hooks ran.
+
+Default value: `emit_order(metrics, hook, log)`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(metrics = true, reporter = "also", emit_order(log, metrics, hook))]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+### `error_style`
+
+How errors are logged: `"interpolated"` puts the error (and the line and expression that caused it) in the message, and `"fields"` adds them as the structured
+fields `error`, `error.line` and `error.expr` instead, so subscribers can filter and index on them. With `"fields"`, the default error messages leave the error
+out, since it's already in the fields. The error is captured with its `Debug` implementation. `"fields"` can only be used with `tracing` (use
+[`log_kv`](#log_kv) for `log`), and not with [`wide_event`](#wide_event), which already adds the error as fields.
+
+`"json"` replaces the error messages with a single-line JSON object, so log aggregators can ingest errors without parsing prose. It has the keys `timestamp`
+(RFC 3339, in UTC), `function`, `module`, `file`, `line`, `column`, `expr`, `error_type`, `error` (the `Debug` output) and `error_display` (the `Display`
+output, or `null` if the error doesn't implement `Error`), always in that order. The location is the one of the `?` that caused the error, or of the function
+if the error didn't come from a `?`. With [`defer_to_outermost`](#defer_to_outermost), the record also has a `chain` key at the end with the functions the
+error propagated through (or `null` if there weren't any), instead of the chain being appended to the message. `"json"` works with every logger, but the
+error messages can't be customized with it.
+
+Default value: `"interpolated"`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(error_style = "fields")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+ERROR my_function: An error occurred when running my_function error=Error error.line=3 error.expr="Err(CustomError::Error)"
+```
+
+With `error_style = "json"`, it would log:
+
+```log
+[ERROR] {"timestamp":"2024-05-01T12:30:00.250Z","function":"my_function","module":"my_crate","file":"src/main.rs","line":3,"column":5,"expr":"Err(CustomError::Error)","error_type":"my_crate::CustomError","error":"Error","error_display":"error"}
+```
+
+### `dual_output`
+
+If `true`, errors will also be logged as a compact line that is easy for machines to parse, in addition to the normal message. The compact line is logged at the
+same level with the target `wrap_match::machine`, so it can be routed separately from the normal messages.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(dual_output = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+[ERROR] An error occurred when running my_function (caused by `Err(CustomError::Error)` on line 3): Error
+[ERROR wrap_match::machine] function=my_function line=3 expr="Err(CustomError::Error)" error=Error
+```
+
+### `bridge_safe`
+
+If `true`, the function will always log through the `log` crate, even when the `tracing` feature is enabled. Use this when `log` records are bridged into
+`tracing` (for example with `tracing_log::LogTracer`) and `tracing` is also set up to emit `log` records (its `log` or `log-always` features), since events
+would otherwise be emitted through both paths and show up twice. Structured fields (such as `owner`) are not supported by `log`, so they are left out.
+
+Note that with `bridge_safe = true` nothing will be logged unless a `log` logger (such as `LogTracer`) is installed.
+
+Default value: `false`
+
+Example:
+
+```
+# #[cfg(not(feature = "log"))] fn main() {}
+# #[cfg(feature = "log")] fn main() {
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(bridge_safe = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+# }
+```
+
+This would log (through `log`):
+
+```log
+[ERROR] An error occurred when running my_function (caused by `Err(CustomError::Error)` on line 3): Error
+```
+
+### `backtrace`
+
+If `true`, a [`Backtrace`](std::backtrace::Backtrace) is captured whenever a `?` fails, and it is available as `{backtrace}` in `error_message`. Capturing
+backtraces has a runtime cost, so this is disabled by default. Backtraces are always captured when this is enabled, regardless of `RUST_BACKTRACE`. No backtrace
+is captured for errors you return yourself, which are logged with `error_message_without_info`.
+
+To keep the error path fast, only the stack frames are recorded when a `?` fails. Resolving them to symbols (the slow part) is deferred until the message is
+actually formatted, which only happens if the error level is enabled. If errors are logged at the default level, the backtrace isn't captured at all when
+that level is disabled.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(backtrace = true, error_message = "{function} failed: {error:?}\n{backtrace}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+### `span_trace`
+
+If `true`, a [`SpanTrace`](https://docs.rs/tracing-error/latest/tracing_error/struct.SpanTrace.html) is captured whenever a `?` fails, and it is available as
+`{span_trace}` in `error_message`. A span trace lists the `tracing` spans that were entered, with their fields, so the context of an error is visible even
+when the error type doesn't carry it (which is especially useful for async code, where backtraces don't say much). Like with [`backtrace`](#backtrace), no
+span trace is captured for errors you return yourself.
+
+This requires the `span_trace` feature (which enables `tracing`), and the subscriber needs to have `tracing_error::ErrorLayer`, otherwise the span trace is
+empty.
+
+Default value: `false`
+
+Example:
+
+```ignore
+#[wrap_match::wrap_match(instrument(user_id), span_trace = true, error_message = "{function} failed: {error:?}\n{span_trace}")]
+fn my_function(user_id: u64) -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+ERROR my_function{user_id=1}: my_function failed: Error
+   0: my_crate::my_function
+           with user_id=1
+             at src/main.rs:1
+```
+
+### `wide_event`
+
+If `true`, the one event that's logged for each call (on success or on error) gets everything wrap-match knows about the call attached to it, in the style of a
+"canonical log line" or wide event: `outcome` (`success` or `failure`), `duration`, `error`, `line` and `expr` (when available) and `args`. When using
+`tracing`, these are added as structured fields. When using `log`, they are appended to the message as `key=value` pairs. The error type and all of the
+function's arguments must implement `Debug`.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(wide_event = true, error_message = "{function} failed")]
+fn my_function(user_id: u64) -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+```
+
+This would log (when using `log`):
+
+```log
+[ERROR] my_function failed outcome=failure duration=1.2µs error=Error line=3 expr="Err(CustomError::Error)" args=(user_id: 1)
+```
+
+### `defer_to_outermost`
+
+If `true`, errors are only logged by the outermost running function that also has `defer_to_outermost` enabled. Functions further down the call stack don't log
+anything; they only add themselves (and the line the error occurred on, if available) to a chain that the outermost function logs as part of its error message.
+This avoids logging the same propagated error once for every wrapped function it passes through. If the error message doesn't use `{chain}`, the chain is
+appended to it as ` (propagated through {chain})` when it isn't empty. A message that uses `{chain}` can leave it out when it is empty with a section, like
+`{?chain} (through {chain}){/chain}`.
+
+Wrapped functions are tracked per thread, except that async functions with `defer_to_outermost` track the functions they call per task: the chain
+follows the task across `.await`s, and a suspended task's functions don't make other code on the same thread defer to them.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(defer_to_outermost = true)]
+fn load_config() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(defer_to_outermost = true)]
+fn start() -> Result<(), CustomError> {
+    load_config()?;
+    Ok(())
+}
+```
+
+Calling `start` would log:
+
+```log
+[ERROR] An error occurred when running start (caused by `load_config()` on line 9): Error (propagated through load_config (line 3))
+```
+
+### `chain_max_frames`
+
+The maximum number of functions to include in `{chain}` when using [`defer_to_outermost`](#defer_to_outermost). The functions closest to where the error
+occurred are kept, and the rest are summarized, like `a (line 3) -> b (line 9) -> 2 more`.
+
+Default value: none (all functions are included)
+
+### `chain_order`
+
+The order of the functions in `{chain}` when using [`defer_to_outermost`](#defer_to_outermost): `innermost_first` (where the error occurred first) or
+`outermost_first` (the order the functions were called in).
+
+Default value: `innermost_first`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(defer_to_outermost = true)]
+fn load_config() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(defer_to_outermost = true)]
+fn load() -> Result<(), CustomError> {
+    load_config()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(defer_to_outermost = true, chain_order = "outermost_first", error_message = "{function} failed: {chain}")]
+fn start() -> Result<(), CustomError> {
+    load()?;
+    Ok(())
+}
+```
+
+Calling `start` would log:
+
+```log
+[ERROR] start failed: load (line 9) -> load_config (line 3)
+```
+
+### `cold_start`
+
+The number of calls of the function that count as first calls, so failures while a program is starting up can be told apart from failures once it is
+running. Every call is counted (across all threads), and `{first_call}` is `true` for the first `cold_start` calls. This also adds `invocation` and
+`first_call` as structured fields (for `log`, only with [`log_kv`](#log_kv)).
+
+Default value: none (`{first_call}` is only `true` for the first call, and no fields are added)
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(cold_start = 3, error_message_without_info = "{function} failed on call {invocation} (during start up: {first_call}): {error:?}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+```
+
+This would log:
+
+```log
+[ERROR] my_function failed on call 1 (during start up: true): Error
+```
+
+### `timeout` and `timeout_message`
+
+How long an async function can run for, like `"500ms"`, `"30s"`, `"5m"` or `"1h"` (requires the `tokio` feature). If the function doesn't finish in
+time, it is stopped and `timeout_message` is logged at the error level (or [`error_level`](#success_level-and-error_level), if it is set). The function
+then returns an error made from a [`TimedOut`], so its error type has to implement `From<TimedOut>` (boxed errors like
+`Box<dyn Error + Send + Sync>` already do). Apart from the message, the error is handled like the function's own errors, so it is counted by
+[`metrics`](#metrics), sent to the [`reporter`](#reporter) and makes [`panic_on_error`](#panic_on_error) panic. Sync functions can't be stopped while they're running, so this can only be used on async functions. Like
+`tokio::time::timeout`, the function panics if it isn't run on a tokio runtime with the time driver enabled.
+
+Available format parameters for `timeout_message`:
+
+-   `function`: The original function name.
+-   `timeout`: How long the function was allowed to run for, as a [`Duration`](std::time::Duration). Only `Debug` formats are supported.
+-   `timestamp`, `thread`, `request_id` and `context`: Like in `error_message`.
+-   [Function arguments](#using-function-arguments-in-messages)
+
+Default value: none (`timeout_message` defaults to `"{function} timed out after {timeout:?}"`)
+
+Example:
+
+```ignore
+# #[derive(Debug)] enum CustomError { TimedOut(wrap_match::TimedOut) }
+# impl From<wrap_match::TimedOut> for CustomError {
+#     fn from(timed_out: wrap_match::TimedOut) -> Self { Self::TimedOut(timed_out) }
+# }
+#[wrap_match::wrap_match(timeout = "10ms", timeout_message = "syncing user {user_id} took longer than {timeout:?}")]
+async fn sync_user(user_id: u64) -> Result<(), CustomError> {
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+[ERROR] syncing user 1 took longer than 10ms
+```
+
+### `catch_panics` and `panic_message`
+
+If `true`, panics in the function are caught and turned into errors, for functions that call code that might panic. When the function panics,
+`panic_message` is logged at the error level (or [`error_level`](#success_level-and-error_level), if it is set), and the function returns an error made
+from a [`Panicked`], so its error type has to implement `From<Panicked>` (boxed errors like `Box<dyn Error + Send + Sync>` already do). Like
+timeouts, the error is handled like the function's own errors apart from the message. The panic hook
+still runs, so the panic is also printed to stderr unless the hook is replaced with [`std::panic::set_hook`].
+
+Enabling `catch_panics` asserts that the function is [unwind safe](std::panic::UnwindSafe): if it panics in the middle of changing something it
+borrowed, the change is left half done.
+
+Available format parameters for `panic_message`:
+
+-   `function`: The original function name.
+-   `panic`: The message the function panicked with, or `<non-string panic payload>` if it didn't panic with a string.
+-   `timestamp`, `thread`, `request_id` and `context`: Like in `error_message`.
+-   [Function arguments](#using-function-arguments-in-messages)
+
+Default value: `false` (`panic_message` defaults to `"{function} panicked: {panic}"`)
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Panicked(wrap_match::Panicked) }
+# impl From<wrap_match::Panicked> for CustomError {
+#     fn from(panicked: wrap_match::Panicked) -> Self { Self::Panicked(panicked) }
+# }
+# fn parse_legacy_config(_: &str) -> u64 { panic!("unexpected end of input") }
+#[wrap_match::wrap_match(catch_panics = true, panic_message = "the legacy parser panicked on {path}: {panic}")]
+fn load_config(path: &str) -> Result<u64, CustomError> {
+    Ok(parse_legacy_config(path))
+}
+# load_config("config.ini").unwrap_err();
+```
+
+This would log:
+
+```log
+[ERROR] the legacy parser panicked on config.ini: unexpected end of input
+```
+
+### `panic_on_error`
+
+If `true`, the function panics instead of returning its error, after the error is logged. The panic message is the error message (`error_message` or
+`error_message_without_info`) with the same parameters, so this is one switch between logging errors and continuing, and logging them and aborting,
+for invariants that should never fail. With `disregard_result`, the error panics instead of being thrown away.
+
+Since the functions that defer to the outermost one don't log their errors, this can't be used with
+[`defer_to_outermost`](#defer_to_outermost).
+
+Default value: `false`
+
+Example:
+
+```should_panic
+# #[derive(Debug)] enum CustomError { Error }
+# fn read_embedded_schema() -> Result<String, CustomError> { Err(CustomError::Error) }
+#[wrap_match::wrap_match(panic_on_error = true, error_message = "the embedded schema is invalid: {error:?}")]
+fn load_schema() -> Result<String, CustomError> {
+    Ok(read_embedded_schema()?)
+}
+# load_schema().ok();
+```
+
+This would log, and then panic with the same message:
+
+```log
+[ERROR] the embedded schema is invalid: Error
+```
+
+### `log_success`
+
+If `false`, nothing will be logged on success.
+
+When success is logged but its level is disabled at runtime (by the logger's maximum level or a `tracing` filter), the success message costs only the
+logging crate's level check: its parameters, including `{ok}`, `{args}` and field accesses, are only evaluated after that check passes.
+
+Default value: `true`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(log_success = false)]
+fn my_function() -> Result<(), CustomError> {
+    Ok(())
+}
+```
+
+This would log nothing.
+
+### `log_success_if`
+
+A predicate over the `Ok` value, as a closure or the path of a function taking a reference to it, that has to return `true` for the success to be
+logged. This is checked at runtime after the function returns, so successes that didn't do anything interesting aren't logged. With
+[`flatten`](#flatten-and-inner_error_message), it gets the inner `Ok` value. This can't be used with `log_success = false`.
+
+Default value: none
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn run_migrations() -> Result<u64, CustomError> { Ok(0) }
+#[wrap_match::wrap_match(success_message = "migrated {ok} rows", log_success_if = |rows| *rows > 0)]
+fn migrate() -> Result<u64, CustomError> {
+    Ok(run_migrations()?)
+}
+# migrate().unwrap();
+```
+
+If no rows were migrated, this would log nothing.
+
+### `disregard_result` and `exit_on_error`
+
+If `true`, the resulting function will return `()` and throw away whatever the `Result` is. Useful for `main` functions.
+
+With `exit_on_error = <code>`, the error isn't thrown away: after it is logged, the process exits with the given status code (with
+[`std::process::exit`], so destructors don't run), or the one chosen by the error type if it implements [`ExitStatus`]. This makes `main` functions of CLIs fail like they should. The `log` logger is flushed before
+exiting. Since the functions that defer to the outermost one don't log their errors, `exit_on_error` can't be used with
+[`defer_to_outermost`](#defer_to_outermost).
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(disregard_result = true)]
+fn main() -> Result<(), CustomError> {
+    Ok(())
+}
+```
+
+The `main` function would be turned into this:
+
+```ignore
+fn main() {
+    fn _wrap_match_inner_main() -> Result<(), WrapMatchError<CustomError>> {
+        Ok(())
+    }
+
+    match _wrap_match_inner_main() {
+        // the Result would be logged like normal, but it is not returned
+    }
+}
+```
+
+With `exit_on_error`:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn run() -> Result<(), CustomError> { Ok(()) }
+#[wrap_match::wrap_match(disregard_result = true, exit_on_error = 2)]
+fn main() -> Result<(), CustomError> {
+    run()?;
+    Ok(())
+}
+```
+
+### `exit_code`
+
+If `true`, the resulting function will return a [`std::process::ExitCode`] instead of the `Result`: [`ExitCode::SUCCESS`](std::process::ExitCode::SUCCESS)
+if the function succeeded and [`ExitCode::FAILURE`](std::process::ExitCode::FAILURE) if it failed (or the code chosen by the error type, if it
+implements [`ExitStatus`]), after the result is logged. Unlike `exit_on_error`, the function returns normally, so destructors still run. This is
+meant for `main` functions, and can't be used with `disregard_result` or `panic_on_error`. If the function times out or panics (with `timeout` or
+`catch_panics`), it also returns `ExitCode::FAILURE`.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn run() -> Result<(), CustomError> { Ok(()) }
+#[wrap_match::wrap_match(exit_code = true)]
+fn main() -> Result<(), CustomError> {
+    run()?;
+    Ok(())
+}
+```
+
+The `main` function would be turned into this:
+
+```ignore
+fn main() -> std::process::ExitCode {
+    // ...
+    match _wrap_match_inner_main() {
+        Ok(_wrap_match_ok) => {
+            // the success would be logged like normal
+            std::process::ExitCode::SUCCESS
+        }
+        Err(_wrap_match_error) => {
+            // the error would be logged like normal
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+```
+
+### `or_else`
+
+A fallback value that the function returns in `Ok` after an error is logged, so callers don't have to handle errors that are only worth recording.
+This can be any expression, or a closure that makes the fallback from the error. If the function times out or panics (with `timeout` or
+`catch_panics`), the closure gets an error made from the [`TimedOut`] or [`Panicked`]. This can't be used with `disregard_result`, `exit_code`,
+`panic_on_error` or [`defer_to_outermost`](#defer_to_outermost).
+
+Default value: none
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn fetch_user_count() -> Result<u64, CustomError> { Err(CustomError::Error) }
+#[wrap_match::wrap_match(or_else = 0)]
+fn user_count() -> Result<u64, CustomError> {
+    Ok(fetch_user_count()?)
+}
+
+#[wrap_match::wrap_match(or_else = |error| format!("unavailable ({error:?})"))]
+fn status() -> Result<String, CustomError> {
+    Err(CustomError::Error)?;
+    Ok("ok".to_owned())
+}
+# assert_eq!(user_count().unwrap(), 0);
+# assert_eq!(status().unwrap(), "unavailable (Error)");
+```
+
+### `ok_or_default`
+
+If `true`, the resulting function will return the `Ok` type instead of the `Result`, and after an error is logged it returns that type's
+[`Default`] value. This is meant for best-effort tasks, so callers don't need `unwrap_or_default` everywhere. The `Result`'s `Ok` type has to be
+written out, and this can't be used with `disregard_result`, `exit_code`, `panic_on_error`, `or_else` or
+[`defer_to_outermost`](#defer_to_outermost). If the function times out or panics (with `timeout` or `catch_panics`), it also returns the default
+value.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn sync_cache() -> Result<usize, CustomError> { Err(CustomError::Error) }
+#[wrap_match::wrap_match(ok_or_default = true)]
+fn refresh_cache() -> Result<usize, CustomError> {
+    let refreshed = sync_cache()?;
+    Ok(refreshed)
+}
+# assert_eq!(refresh_cache(), 0);
+```
+
+The `refresh_cache` function would be turned into this:
+
+```ignore
+fn refresh_cache() -> usize {
+    // ...
+    match _wrap_match_inner_refresh_cache() {
+        Ok(_wrap_match_ok) => {
+            // the success would be logged like normal
+            _wrap_match_ok
+        }
+        Err(_wrap_match_error) => {
+            // the error would be logged like normal
+            Default::default()
+        }
+    }
+}
+```
+
+### `ok_or_none`
+
+Like `disregard_result`, but instead of discarding the value, the resulting function will return an [`Option`] of the `Ok` type: `Some` with the
+value if the function succeeded, and `None` after an error is logged. The `Result`'s `Ok` type has to be written out, and this can't be used with
+`disregard_result`, `exit_code`, `panic_on_error`, `or_else`, `ok_or_default` or [`defer_to_outermost`](#defer_to_outermost). If the function
+times out or panics (with `timeout` or `catch_panics`), it also returns `None`.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn fetch_config() -> Result<String, CustomError> { Err(CustomError::Error) }
+#[wrap_match::wrap_match(ok_or_none = true)]
+fn load_config() -> Result<String, CustomError> {
+    Ok(fetch_config()?)
+}
+# assert_eq!(load_config(), None);
+```
+
+The `load_config` function would be turned into this:
+
+```ignore
+fn load_config() -> Option<String> {
+    // ...
+    match _wrap_match_inner_load_config() {
+        Ok(_wrap_match_ok) => {
+            // the success would be logged like normal
+            Some(_wrap_match_ok)
+        }
+        Err(_wrap_match_error) => {
+            // the error would be logged like normal
+            None
+        }
+    }
+}
+```
+
+### `require_error_trait`
+
+If `true`, the function's error type must implement `std::error::Error` (boxed `dyn Error`s are also accepted). This is checked at compile time, so you can make
+sure that wrapped functions use proper error types instead of something like `String`.
+
+Default value: `false`
+
+Example:
+
+```compile_fail
+#[wrap_match::wrap_match(require_error_trait = true)]
+fn my_function() -> Result<(), String> { // error: `String` doesn't implement `std::error::Error`
+    Ok(())
+}
+```
+
+### `tail_result`
+
+If `true`, a `Result` returned as the function's tail expression (like `do_last_step()` instead of `Ok(do_last_step()?)`) is treated like a try expression, so
+its error gets line and expression info too. Without this, the function's tail expression has to be wrapped in `Ok(...?)`, since the function's error type is
+changed when it is expanded. The tail expressions of `if`, `match` and block expressions at the end of the function are handled the same way. Tail expressions
+that are already `Ok(...)` or `Err(...)` and macro invocations are left alone.
+
+Default value: `false`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# fn do_last_step() -> Result<(), CustomError> { Err(CustomError::Error) }
+#[wrap_match::wrap_match(tail_result = true)]
+fn my_function() -> Result<(), CustomError> {
+    do_last_step()
+}
+```
+
+This would log:
+
+```log
+[ERROR] An error occurred when running my_function (caused by `do_last_step()` on line 3): Error
+```
+
+### `flatten` and `inner_error_message`
+
+If `flatten` is `true`, a function that returns a `Result` of a `Result` (for example the result of joining a thread) logs `inner_error_message` when it
+returns `Ok(Err(...))`, instead of logging success. The error is logged at the level set with [`error_level`](#success_level-and-error_level), and `{ok}` in
+`success_message` is the inner value. The function still returns the nested `Result`.
+
+Available format parameters for `inner_error_message`:
+
+-   `function`: The original function name. **Note**: You can only use `{function}`; other formats such as `{function:?}` are not supported.
+-   `error`: The inner error.
+-   `duration`, `timestamp`, `thread`, `request_id`, `caller`, `invocation`, `first_call` and `context`: Like in `error_message`.
+-   [Function arguments](#using-function-arguments-in-messages)
+
+Default value of `flatten`: `false`
+
+Default value of `inner_error_message`: `{function} ran, but returned an inner error: {error:?}`
+
+Example:
+
+```
+# #[derive(Debug)] enum CustomError { Error }
+# #[derive(Debug)] enum JobError { Failed }
+#[wrap_match::wrap_match(flatten = true)]
+fn my_function() -> Result<Result<(), JobError>, CustomError> {
+    Ok(Err(JobError::Failed))
+}
+```
+
+This would log:
+
+```log
+[ERROR] my_function ran, but returned an inner error: Failed
+```
+
+### `strict`
+
+If `true`, cases where wrap-match silently falls back to something are compile errors, so you can see exactly what was and wasn't instrumented:
+
+-   The return type isn't named `Result`, so it is assumed to be an alias of `Result` whose last generic argument is the error type.
+-   A closure or async block has try expressions (`?`) in it. These return from the closure or async block instead of the function, so they are left alone.
+-   A macro invocation has a `?` in it. wrap-match can't see into macro invocations, so errors from try expressions inside them won't have line and expression
+    info.
+
+Default value: `false`
+
+Example:
+
+```compile_fail
+#[wrap_match::wrap_match(strict = true)]
+fn my_function(input: &str) -> Result<(), std::num::ParseIntError> {
+    println!("{}", input.parse::<u8>()?); // error: try expressions in macro invocations are left alone
+    Ok(())
+}
+```
+
+### `self_check`
+
+If `true`, debug builds check the function's error messages the first time it is called, instead of when it first fails. Messages are checked when
+compiling, except for [custom placeholders](#custom-placeholders), which need an extractor to be registered at runtime: the check panics if one of them
+doesn't have an extractor (for example because of a typo in the extractor's name, or because the extractor is registered too late). Release builds (without
+`debug_assertions`) skip the check, and functions without custom placeholders don't need it.
+
+Default value: `false`
+
+Example:
+
+```should_panic
+# #[derive(Debug)] enum CustomError { Error }
+#[wrap_match::wrap_match(self_check = true, extract(tenant), error_message = "{function} failed for tenant {tenant}: {error:?}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+wrap_match::register_extractor("tennant", |_| Some("acme".to_owned()));
+// panics: "wrap_match: the error messages of my_function use `{tenant}`, but no extractor is registered for it"
+my_function().unwrap();
+```
+
+### `inline`
+
+If `true`, the function's body is kept in the function wrap-match makes (as a closure, or an async block for async functions) instead of being moved to an
+inner function. Methods that take `self` normally get an extra hidden method next to them, which isn't allowed in trait implementations and confuses
+tools that look at every method of an implementation, like `mockall`. With `inline`, wrap-match only ever makes the one function.
+
+Try expressions in the body still work like they do without `inline`, and so does `return`. Arguments that the body borrows can still be used in
+messages, but arguments it consumes can't be.
+
+Default value: `false` (`true` for async methods that take `self`)
+
+Example:
+
+```ignore
+#[mockall::automock]
+trait Store {
+    fn get(&self, id: u32) -> Result<String, DbError>;
+}
+
+impl Store for Database {
+    #[wrap_match::wrap_match(inline = true)]
+    fn get(&self, id: u32) -> Result<String, DbError> {
+        Ok(self.query(id)?)
+    }
+}
+```
+
+Note that `mockall` copies the attributes of methods to the mocks it generates, so `#[automock]` should be on the trait rather than on an implementation
+with wrapped methods.
+
+## Using function arguments in messages
+
+As of wrap-match 1.0.5, you can use function arguments in messages, as long as they weren't moved/dropped. You should only use this for references and items that implement `Copy`.
+
+Example:
+
+```rust
+#[wrap_match::wrap_match(success_message = "Success! input = {input}")]
+fn my_function(input: i64) -> Result<i64, ()> {
+    Ok(input)
+}
+```
+
+You can also access fields of arguments, for example `{request.user_id}`. In methods, you can access fields of `self` the same way:
+
+```rust
+struct Worker {
+    id: u32,
+}
+
+impl Worker {
+    #[wrap_match::wrap_match(error_message_without_info = "worker {self.id} failed: {error:?}")]
+    fn work(&self) -> Result<(), ()> {
+        Err(().into())
+    }
+}
+```
+
+You can also use `{args:?}` to log all of the function's arguments (except `self`), like `(input: 1, other: "hi")`. This requires all of the arguments to implement
+`Debug`, and the same rules about moved arguments apply. The arguments are only formatted if the message is actually logged.
+
+```rust
+#[wrap_match::wrap_match(error_message_without_info = "failed with args {args:?}: {error:?}")]
+fn my_function(input: i64, other: &str) -> Result<i64, ()> {
+    Err(().into())
+}
+```
+
+If an argument has the same name as a built-in format parameter (such as `error` or `line`), the built-in format parameter is used. You can still use the
+argument with `{args.name}`:
+
+```rust
+#[wrap_match::wrap_match(error_message = "could not parse {args.line} on line {line}: {error:?}")]
+fn parse_line(line: &str) -> Result<i64, std::num::ParseIntError> {
+    Ok(line.parse::<i64>()?)
+}
+```
+
+## Boxed errors
+
+Functions can return boxed trait objects like `Box<dyn Error + Send + Sync + 'static>`, with any auto traits and lifetime, in sync and async functions.
+`?` converts errors into them like it does without wrap-match. `Err(...)` with `.into()` only works for strings (`&str`, `String` and `Cow<str>`) though,
+so other errors have to use `Err(error)?` instead of `Err(error.into())`:
+
+```
+# use std::error::Error;
+#[wrap_match::wrap_match]
+fn my_function(input: &str) -> Result<u32, Box<dyn Error + Send + Sync>> {
+    if input.is_empty() {
+        return Err("the input is empty".into());
+    }
+    Ok(input.parse::<u32>()?)
+}
+```
+
+## `error_type`
+
+`{error_type}` is the name of the error's type (from [`std::any::type_name`]). When the error type is `Box<dyn Error>` (optionally with `Send` and `Sync`), the
+name of the concrete type is shown if it is known. Common error types from the standard library are already known; other types can be added with
+[`register_error_type`]. Unknown types are shown as the box's type, and so are boxes with a lifetime other than `'static` (like
+`Box<dyn Error + Send + Sync + 'a>`), since they can't be downcast.
+
+Example:
+
+```
+# use std::error::Error;
+#[wrap_match::wrap_match(error_message = "{function} failed with a {error_type}: {error}")]
+fn my_function() -> Result<(), Box<dyn Error>> {
+    "not a number".parse::<i32>()?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+[ERROR] my_function failed with a core::num::error::ParseIntError: invalid digit found in string
+```
+
+## `variant`
+
+`{variant}` is the name of the error's enum variant (without the enum name or any fields), which is useful for grepping and alerting. The error type must
+implement [`VariantName`], which can be derived:
+
+```
+#[derive(Debug, wrap_match::VariantName)]
+enum CustomError {
+    NotFound { path: String },
+    Other,
+}
+
+#[wrap_match::wrap_match(error_message = "{function} failed with {variant}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound { path: "a.txt".to_owned() })?;
+    Ok(())
+}
+```
+
+This would log:
+
+```log
+[ERROR] my_function failed with NotFound
+```
+
+## Custom placeholders
+
+Placeholders declared with `extract(...)`, like `extract(tenant, region)`, are filled in by the extractor registered with [`register_extractor`] under the
+same name. This lets you pull domain-specific information out of your errors without wrap-match needing to know about it. Other placeholders that aren't
+built-in format parameters or function arguments are still compile errors, so typos are caught.
+
+Extractors receive the error as [`Any`](std::any::Any), so the error type must be `'static` if the function uses `extract`. If no extractor is registered or the
+extractor returns `None`, the placeholder will be replaced with `<unknown>`. [`self_check`](#self_check) catches placeholders without an extractor in debug
+builds, before the function fails. For `Box<dyn Error>` errors, extractors receive the error inside the box if its type is
+known (see [`register_error_type`]), so they can downcast it like any other error.
+
+Example:
+
+```
+#[derive(Debug)]
+struct TenantError {
+    tenant: String,
+}
+
+#[wrap_match::wrap_match(extract(tenant), error_message_without_info = "tenant {tenant} failed: {error:?}")]
+fn my_function() -> Result<(), TenantError> {
+    Err(TenantError { tenant: "acme".to_owned() }.into())
+}
+
+wrap_match::register_extractor("tenant", |e| e.downcast_ref::<TenantError>().map(|e| e.tenant.clone()));
+```
+
+## Policy enforcement
+
+If the `policy` feature is enabled, wrap-match will enforce a policy at compile time, which lets organizations disallow certain uses of wrap-match. Each rule can be
+set to `"deny"` or `"allow"` in a policy file, and **all rules are denied by default**.
+
+The policy file is read from the path in the `WRAP_MATCH_POLICY` environment variable (relative to the crate being compiled), or `wrap-match-policy.toml` in the
+root of the crate being compiled if it isn't set. The policy file is optional.
+
+Available rules:
+
+-   `log_success_in_libraries`: Functions in libraries must use `log_success = false`.
+-   `sensitive_arguments`: Arguments with a sensitive name (any of the names in `sensitive_argument_names` is part of the argument's name) must not be used in
+    messages, including through `{args:?}`. Default value of `sensitive_argument_names`: `["password", "secret", "token"]`
+
+Example policy file:
+
+```toml
+log_success_in_libraries = "allow"
+sensitive_arguments = "deny"
+sensitive_argument_names = ["password", "api_key"]
+```
+
+## Message catalogs
+
+If the `catalog` feature is enabled, messages can be kept in a TOML file instead of the source code, and looked up with `message_key`. This makes it easy to
+review or translate all messages at once. Since format strings have to be known at compile time, the catalog is embedded when compiling, and the function is
+recompiled when it changes.
+
+The catalog is read from the path in the `catalog` option, the path in the `WRAP_MATCH_CATALOG` environment variable, or `wrap-match-messages.toml`, all relative
+to the root of the crate being compiled. Each key can either be a string, which is used as the `error_message`, or a table with any of `success_message`,
+`error_message`, `error_message_without_info` and `inner_error_message`. Messages from the catalog replace the ones given as options. Dotted keys can be written either as nested tables
+or as quoted keys. Only TOML catalogs are supported (Fluent files can't be used).
+
+Example catalog:
+
+```toml
+[db.sync]
+failed = "failed to sync the database (caused by `{expr}` on line {line}): {error}"
+
+[db.migrate]
+success_message = "migrated the database in {duration:?}"
+error_message = "failed to migrate the database: {error}"
+```
+
+Example usage:
+
+```ignore
+#[wrap_match::wrap_match(message_key = "db.sync.failed")]
+fn sync() -> Result<(), std::io::Error> {
+    std::fs::write("database", "data")?;
+    Ok(())
+}
+```
+
+## Request IDs
+
+Messages can include the ID of the request a function ran for with `{request_id}`, so everything logged about a request can be found with one search. The ID
+is set for a future with [`with_request_id`], and every wrapped function that runs while the future is polled (async or not) uses it. It isn't inherited
+by spawned tasks, and is `-` outside of a request.
+
+```ignore
+#[wrap_match::wrap_match(error_message = "[{request_id}] {function} failed: {error}")]
+async fn load_user(id: u64) -> Result<User, DbError> {
+    Ok(db().await?.user(id).await?)
+}
+
+let user = wrap_match::with_request_id(request.header("x-request-id"), load_user(id)).await?;
+```
+
+## Context
+
+[`context()`] adds context to the errors logged while the returned guard is alive, so you can see what the program was doing when a function failed
+without changing error types (like `anyhow`'s `context`). Contexts can be nested, and are added to the end of error messages (except with
+`error_style = "json"`), unless the messages use `{context}`. Contexts are per thread, so in async code, use [`with_request_id`] or a message with
+arguments instead of holding a guard across an `.await`.
+
+```ignore
+for user in users {
+    let _context = wrap_match::context(format!("syncing user {}", user.id));
+    // logs "An error occurred when running load_profile (caused by `db.get(id)` on line 12): NotFound (context: syncing user 42)"
+    load_profile(user.id)?;
+}
+```
+
+## Background tasks
+
+Tasks spawned on a tokio runtime aren't functions you can annotate, so if the `tokio` feature is enabled, [`spawn_logged`] can be used instead of
+`tokio::spawn` to log the task's result. Errors and panics of the task are logged with where it was spawned, and the returned handle works like the one from
+`tokio::spawn`.
+
+```ignore
+wrap_match::spawn_logged(async move {
+    let data = fetch(&url).await?;
+    store(data).await
+});
+```
+
+This would log (if `store` failed):
+
+```log
+[ERROR wrap_match::spawn] An error occurred in the task spawned at src/main.rs:12:1: Error
+```
+
+## OpenTelemetry
+
+If the `otel` feature is enabled, errors are also recorded on the active [OpenTelemetry](https://docs.rs/opentelemetry) span, so wrap-match feeds traces
+and not just logs. Each error adds an `exception` event with the attributes `exception.type` (like `{error_type}`), `exception.message` (the error's `Debug`
+output, if it implements `Debug`), `code.function.name` and, for errors from a `?`, `code.line.number` and `wrap_match.expr`. The span's status is set to
+an error with the same message. This happens whether or not the error is logged, and spans that aren't recording are left alone.
+
+## Sentry
+
+If the `sentry` feature is enabled, errors are also captured as [Sentry](https://docs.rs/sentry) events, with `function` and, for errors from a `?`,
+`line` and `expr` as extras. Errors that implement `Error` are captured like `sentry::capture_error` (with their sources), and other errors are captured
+as a message with their `Debug` output. The errors are still logged like they would be without the feature.
+
+The `sentry` option changes this for a function: `sentry = "breadcrumb"` adds a breadcrumb instead (with the same data), so the error only shows up if
+something else is captured later, and `sentry = "off"` doesn't report the function's errors at all. Using the option without the feature is an error.
+
+```ignore
+// a missing cache entry isn't worth an event, but it's useful context for one
+#[wrap_match::wrap_match(sentry = "breadcrumb")]
+fn read_cache(key: &str) -> Result<Vec<u8>, CacheError> {
+    Ok(cache()?.get(key)?)
+}
+```
+
+## Testing the expansion
+
+If the `expand_for_tests` feature is enabled, the expansion of wrap-match's macros can be used without a compiler through `wrap_match::expand_for_tests`, which takes and
+returns `proc_macro2` token streams. This is useful for snapshot tests of the expansion (for example with `insta`), or for tools that check how wrap-match is
+used. Compile errors are returned as `compile_error!` invocations, like the macros do. The expansion depends on wrap-match's other features, such as `tracing`.
+
+```ignore
+let expanded = wrap_match::expand_for_tests::wrap_match(
+    quote::quote!(log_success = false),
+    quote::quote! {
+        fn my_function() -> Result<(), ()> {
+            Ok(())
+        }
+    },
+);
+insta::assert_snapshot!(expanded.to_string());
+```
+
+## Limitations
+
+wrap-match currently has the following limitations:
+
+1.  ~~wrap-match cannot be used on functions in implementations that take a `self` parameter. If you need support for this, please create a GitHub issue with your use case.~~ This is now supported!
+    However, it does require wrap-match to move the inner function out of the generated one, so it will add a new method to the implementation. This method is marked as deprecated, made private, and
+    is not shown in documentation. Hopefully this won't cause any issues. Async methods don't need this (their body is kept in the generated method as an
+    async block), so they also work in trait implementations. Sync methods in trait implementations need [`inline`](#inline).
+
+1.  wrap-match only supports `Result`s. If you need support for `Option`s, please create a GitHub issue with your use case. Functions whose error type is
+    `Infallible` (or `!`) can't fail, so no error logging code is generated for them, which is useful when the error type comes from a type parameter
+    somewhere else.
+
+1.  Functions whose error type is one of their type parameters (like `fn load<E: From<io::Error> + Debug>() -> Result<(), E>`) work, but since the caller
+    picks the error type, wrap-match can't tell which traits it implements. The type parameter's bounds need to include what the messages use (such as
+    `Debug` for `{error:?}` or `Error` for `{sources}`), [`level_from_error`](#level_from_error) can't be used (use [`level_fn`](#level_fn) instead), and `{error_type}` doesn't look
+    inside `Box<dyn Error>`.
+
+1.  Try expressions (`?`) inside macro invocations don't get line and expression info, since wrap-match can't see into them. Try expressions in closures and
+    async blocks are left alone, since they return from the closure or async block instead of the function. Use [`strict`](#strict) to find these.
+
+1.  ~~`error_message` and `error_message_without_info` only support formatting `error` using the `Debug` or `Display` formatters. This is because of how we determine what formatting specifiers are
+    used. If you need support for other formatting specifiers, please create a GitHub issue with your use case.~~ All format parameters (except `function`) now support all basic formats that `format!`
+    supports, including precision, sign, fill, alignment and width.
+
+1.  wrap-match cannot be used on `const` functions. This is because the `log` crate cannot be used in `const` contexts.
+
+1.  wrap-match cannot be used on naked functions, since their bodies can only contain inline assembly. Codegen attributes that affect the function's body
+    (`#[target_feature]`, `#[track_caller]`, `#[optimize]` and `#[instruction_set]`), `#[cfg]` and lint levels (except `#[expect]`) are put on both the
+    generated function and the inner function, so they work as expected. Everything else, including attributes that affect the function's symbol (like
+    `#[no_mangle]`, `#[export_name]` and `#[link_section]`), is only put on the generated function, which keeps the original function's name and ABI, so
+    wrap-match can be used on FFI wrappers.
+
+If wrap-match doesn't work for something not on this list, please create a GitHub issue!
+*/
+
+#[doc(inline)]
+pub use wrap_match_impl::wrap_match;
+
+/// The format string parser wrap-match uses for messages.
+#[doc(inline)]
+pub use wrap_match_format as format;
+
+/// The expansion of wrap-match's macros, usable without a compiler (enabled with the `expand_for_tests` feature). See
+/// [Testing the expansion](crate#testing-the-expansion).
+#[cfg(feature = "expand_for_tests")]
+#[doc(inline)]
+pub use wrap_match_expand as expand_for_tests;
+
+mod clock;
+pub use clock::{set_clock, Clock, MockClock, SystemClock};
+
+mod context;
+pub use context::{context, ContextGuard};
+
+mod defer;
+
+mod extractors;
+pub use extractors::register_extractor;
+
+mod json;
+
+pub mod runtime;
+
+mod request_id;
+pub use request_id::{request_id, with_request_id, WithRequestId};
+
+mod timestamp;
+
+mod panic;
+pub use panic::Panicked;
+
+mod reporter;
+pub use reporter::{set_reporter, ErrorInfo, Reporter, SuccessInfo};
+
+#[cfg(feature = "otel")]
+mod otel;
+
+#[cfg(feature = "sentry")]
+mod sentry;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+
+#[cfg(feature = "tokio")]
+mod spawn;
+#[cfg(feature = "tokio")]
+pub use spawn::spawn_logged;
+
+#[cfg(feature = "tokio")]
+mod timeout;
+#[cfg(feature = "tokio")]
+pub use timeout::TimedOut;
+
+/// Gets the name of an enum variant, for `{variant}`.
+///
+/// This can be derived for enums with `#[derive(wrap_match::VariantName)]`.
+pub trait VariantName {
+    /// Returns the name of the variant (without the enum name or any fields)
+    fn variant_name(&self) -> &'static str;
+}
+
+#[doc(inline)]
+pub use wrap_match_impl::VariantName;
+
+/// A level errors can be logged at, for [`WrapMatchLevel`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Chooses the level an error is logged at, so the severity of errors can be decided next to the error type instead of in every `#[wrap_match]`.
+///
+/// wrap-match uses this for functions with [`level_from_error`](crate#level_from_error) whose error type implements it (the error type has to be known
+/// when the macro is expanded, so this doesn't work with generic error types). [`level_for`](crate#level_for) takes precedence over it.
+///
+/// This can be derived for enums with `#[derive(wrap_match::WrapMatchLevel)]`. Variants are logged at the level in their `#[wrap_match(level = "...")]`
+/// attribute, or the level in the enum's attribute if they don't have one (which is `error` by default). With `#[wrap_match(exhaustive = true)]` on the
+/// enum, every variant needs its own level, so a new variant can't be added without choosing one.
+///
+/// ```
+/// #[derive(Debug, wrap_match::WrapMatchLevel)]
+/// #[wrap_match(level = "warn")]
+/// enum DbError {
+///     NotFound,
+///     #[wrap_match(level = "error")]
+///     Disconnected,
+///     #[wrap_match(level = "info")]
+///     Retrying { attempt: u32 },
+/// }
+/// ```
+///
+/// Or implemented manually:
+///
+/// ```
+/// #[derive(Debug)]
+/// enum DbError {
+///     NotFound,
+///     Disconnected,
+/// }
+///
+/// impl wrap_match::WrapMatchLevel for DbError {
+///     fn level(&self) -> wrap_match::Level {
+///         match self {
+///             DbError::NotFound => wrap_match::Level::Info,
+///             DbError::Disconnected => wrap_match::Level::Error,
+///         }
+///     }
+/// }
+/// ```
+pub trait WrapMatchLevel {
+    fn level(&self) -> Level;
+}
+
+#[doc(inline)]
+pub use wrap_match_impl::WrapMatchLevel;
+
+/// Chooses the status code the process exits with when an error ends it, so CLIs can give different kinds of errors different exit codes.
+///
+/// wrap-match uses this with [`exit_on_error`](crate#disregard_result-and-exit_on_error) and [`exit_code`](crate#exit_code) when the function's error
+/// type implements it (like [`WrapMatchLevel`], the error type has to be known when the macro is expanded). The code it chooses takes precedence over
+/// the one given to `exit_on_error`, and is used instead of [`ExitCode::FAILURE`](std::process::ExitCode::FAILURE) with `exit_code`.
+///
+/// ```
+/// #[derive(Debug)]
+/// enum CliError {
+///     InvalidArguments,
+///     Io(std::io::Error),
+/// }
+///
+/// impl wrap_match::ExitStatus for CliError {
+///     fn exit_status(&self) -> u8 {
+///         match self {
+///             CliError::InvalidArguments => 2,
+///             CliError::Io(_) => 74,
+///         }
+///     }
+/// }
+/// ```
+pub trait ExitStatus {
+    fn exit_status(&self) -> u8;
+}
+
+mod probes;
+pub use probes::register_error_type;
+
+// Not public API. Generated code logs through these, so crates using wrap-match don't need to depend on them (and can't end up with a different version).
+#[doc(hidden)]
+pub mod __log {
+    #[cfg(feature = "log")]
+    #[doc(hidden)]
+    pub use log;
+    #[cfg(feature = "tracing")]
+    #[doc(hidden)]
+    pub use tracing;
+    #[cfg(feature = "span_trace")]
+    #[doc(hidden)]
+    pub use tracing_error;
+}
+
+// Not public API.
+#[doc(hidden)]
+pub mod __private {
+    #[doc(hidden)]
+    pub use crate::clock::{DurationFormat, FormattedDuration, Timer};
+    #[doc(hidden)]
+    pub use crate::context::ActiveContext;
+    #[doc(hidden)]
+    pub use crate::defer::{Chain, ChainSuffix, DeferFrame, DeferScope};
+    #[doc(hidden)]
+    pub use crate::extractors::{check_extractors, Extracted};
+    #[doc(hidden)]
+    pub use crate::json::JsonRecord;
+    #[cfg(feature = "metrics")]
+    #[doc(hidden)]
+    pub use crate::metrics::{record_error as count_error, record_success as count_success};
+    #[cfg(feature = "otel")]
+    #[doc(hidden)]
+    pub use crate::otel::record_error as record_otel_error;
+    #[doc(hidden)]
+    pub use crate::panic::{catch_panic, catch_panic_async};
+    #[doc(hidden)]
+    pub use crate::probes::*;
+    #[doc(hidden)]
+    pub use crate::reporter::{report_error, report_success};
+    #[doc(hidden)]
+    pub use crate::request_id::CurrentRequestId;
+    #[cfg(feature = "sentry")]
+    #[doc(hidden)]
+    pub use crate::sentry::report_error as report_sentry_error;
+    #[cfg(feature = "tokio")]
+    #[doc(hidden)]
+    pub use crate::timeout::timeout;
+    #[doc(hidden)]
+    pub use crate::timestamp::Timestamp;
+
+    /// Exits the process after an error was logged, for `exit_on_error`. `process::exit` doesn't run destructors, so the `log` logger is flushed
+    /// first
+    #[doc(hidden)]
+    pub fn exit(code: i32) -> ! {
+        #[cfg(feature = "log")]
+        log::logger().flush();
+        std::process::exit(code)
+    }
+
+    /// Why a function stopped before returning, for `timeout` and `catch_panics`. The error they turn into goes through the same pipeline as the
+    /// function's own errors, and this picks the message it's logged with.
+    #[doc(hidden)]
+    pub enum EarlyExit {
+        TimedOut(core::time::Duration),
+        Panicked(String),
+    }
+
+    /// Converts a timeout or a caught panic into the function's error. Going through a function lets the error type be inferred from the result
+    #[doc(hidden)]
+    pub fn early_exit_error<'lt, X, E: From<X>>(error: X) -> WrapMatchError<'lt, E> {
+        E::from(error).into()
+    }
+
+    /// Makes the fallback value for `or_else` from an error. Going through a function lets the closure's argument type be inferred from the error
+    #[doc(hidden)]
+    pub fn or_else<E, T>(error: E, f: impl FnOnce(E) -> T) -> T {
+        f(error)
+    }
+
+    /// Checks the predicate for `log_success_if`. Going through a function lets the closure's argument type be inferred from the `Ok` value
+    #[doc(hidden)]
+    pub fn log_success_if<T: ?Sized>(ok: &T, predicate: impl FnOnce(&T) -> bool) -> bool {
+        predicate(ok)
+    }
+
+    /// Lets the output type of an async block be inferred from the function's return type, so `?` works in it
+    #[doc(hidden)]
+    pub fn typed_future<O, F: core::future::Future<Output = O>>(future: F) -> F {
+        future
+    }
+
+    /// The conversion `?` does. It goes through a trait of our own so when a `From` implementation is missing, the error notes that the `?` was
+    /// rewritten by wrap_match (`#[diagnostic::on_unimplemented]` doesn't help here, since rustc reports the `From` bound that isn't satisfied)
+    #[doc(hidden)]
+    pub trait QuestionMarkConversion<E> {
+        fn convert_error(self) -> E;
+    }
+
+    impl<T: Into<E>, E> QuestionMarkConversion<E> for T {
+        #[inline]
+        fn convert_error(self) -> E {
+            self.into()
+        }
+    }
+
+    #[doc(hidden)]
+    pub struct WrapMatchError<'lt, E> {
+        pub line_and_expr: Option<(u32, &'lt str)>,
+        pub location: Option<SourceLocation>,
+        /// Only captured when the `backtrace` option is enabled
+        pub backtrace: Option<std::backtrace::Backtrace>,
+        /// Only captured when the `span_trace` option is enabled
+        #[cfg(feature = "span_trace")]
+        pub span_trace: Option<tracing_error::SpanTrace>,
+        pub inner: E,
+    }
+
+    /// Where an error occurred, for `{location}`
+    #[doc(hidden)]
+    #[derive(Clone, Copy)]
+    pub enum SourceLocation {
+        File {
+            file: &'static str,
+            line: u32,
+            column: u32,
+        },
+        /// For generated code, where file locations aren't useful. The index counts the try expressions in the function (starting at 1), and is 0 for the
+        /// function itself.
+        Site { function: &'static str, index: u32 },
+    }
+
+    impl core::fmt::Display for SourceLocation {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self {
+                Self::File { file, line, column } => write!(f, "{file}:{line}:{column}"),
+                Self::Site { function, index } => write!(f, "{function}#{index}"),
+            }
+        }
+    }
+
+    impl core::fmt::Debug for SourceLocation {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            core::fmt::Debug::fmt(&self.to_string(), f)
+        }
+    }
+
+    /// The backtrace captured at the failing try expression, for `{backtrace}`
+    #[doc(hidden)]
+    pub struct CapturedBacktrace<'a>(pub Option<&'a std::backtrace::Backtrace>);
+
+    impl core::fmt::Display for CapturedBacktrace<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0 {
+                Some(backtrace) => core::fmt::Display::fmt(backtrace, f),
+                None => f.write_str("<no backtrace>"),
+            }
+        }
+    }
+
+    impl core::fmt::Debug for CapturedBacktrace<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0 {
+                Some(backtrace) => core::fmt::Debug::fmt(backtrace, f),
+                None => f.write_str("<no backtrace>"),
+            }
+        }
+    }
+
+    /// The span trace captured at the failing try expression, for `{span_trace}`
+    #[cfg(feature = "span_trace")]
+    #[doc(hidden)]
+    pub struct CapturedSpanTrace<'a>(pub Option<&'a tracing_error::SpanTrace>);
+
+    #[cfg(feature = "span_trace")]
+    impl core::fmt::Display for CapturedSpanTrace<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0 {
+                Some(span_trace) => core::fmt::Display::fmt(span_trace, f),
+                None => f.write_str("<no span trace>"),
+            }
+        }
+    }
+
+    #[cfg(feature = "span_trace")]
+    impl core::fmt::Debug for CapturedSpanTrace<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0 {
+                Some(span_trace) => core::fmt::Debug::fmt(span_trace, f),
+                None => f.write_str("<no span trace>"),
+            }
+        }
+    }
+
+    /// The current thread's name (or its id, if it doesn't have a name), for `{thread}`
+    #[doc(hidden)]
+    pub struct ThreadName(pub std::thread::Thread);
+
+    impl core::fmt::Display for ThreadName {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0.name() {
+                Some(name) => f.write_str(name),
+                None => write!(f, "{:?}", self.0.id()),
+            }
+        }
+    }
+
+    impl core::fmt::Debug for ThreadName {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self.0.name() {
+                Some(name) => core::fmt::Debug::fmt(name, f),
+                None => core::fmt::Debug::fmt(&self.0.id(), f),
+            }
+        }
+    }
+
+    /// The arguments of a function, for `{args:?}`. This only holds references, so nothing is formatted unless the message is actually logged.
+    #[doc(hidden)]
+    pub struct Args<'a>(pub &'a [(&'static str, &'a dyn core::fmt::Debug)]);
+
+    impl core::fmt::Debug for Args<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("(")?;
+            for (i, (name, value)) in self.0.iter().enumerate() {
+                if i != 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{name}: ")?;
+                value.fmt(f)?;
+            }
+            f.write_str(")")
+        }
+    }
+
+    impl<E> From<E> for WrapMatchError<'_, E> {
+        fn from(inner: E) -> Self {
+            Self {
+                line_and_expr: None,
+                location: None,
+                backtrace: None,
+                #[cfg(feature = "span_trace")]
+                span_trace: None,
+                inner,
             }
         }
     }
+
+    // `Err("message".into())` converts to a boxed error in functions that don't have wrap-match, so it has to keep working in functions that do. `From`
+    // can't be implemented for every error type, since it would overlap with the implementation above if `Box<dyn Error>` implemented `Error`, so only
+    // the conversions from strings are added (`?` works for every error type).
+    macro_rules! impl_from_str_for_boxed_error {
+        ($(($($bound:ident),*)),*) => {
+            $(
+                impl<'e> From<&str> for WrapMatchError<'_, Box<dyn std::error::Error $(+ $bound)* + 'e>> {
+                    fn from(message: &str) -> Self {
+                        Box::<dyn std::error::Error $(+ $bound)* + 'e>::from(message).into()
+                    }
+                }
+
+                impl<'e> From<String> for WrapMatchError<'_, Box<dyn std::error::Error $(+ $bound)* + 'e>> {
+                    fn from(message: String) -> Self {
+                        Box::<dyn std::error::Error $(+ $bound)* + 'e>::from(message).into()
+                    }
+                }
+
+                impl<'e> From<std::borrow::Cow<'_, str>> for WrapMatchError<'_, Box<dyn std::error::Error $(+ $bound)* + 'e>> {
+                    fn from(message: std::borrow::Cow<'_, str>) -> Self {
+                        Box::<dyn std::error::Error $(+ $bound)* + 'e>::from(message).into()
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_from_str_for_boxed_error!((), (Send, Sync));
 }
@@ -0,0 +1,16 @@
+//! Counts the results of wrapped functions with the `metrics` facade (enabled with the `metrics` feature).
+
+/// Increments `wrap_match_success_total{function}`
+pub fn record_success(function: &'static str) {
+    metrics::counter!("wrap_match_success_total", "function" => function).increment(1);
+}
+
+/// Increments `wrap_match_error_total{function, error_type}`
+pub fn record_error(function: &'static str, error_type: &'static str) {
+    metrics::counter!(
+        "wrap_match_error_total",
+        "function" => function,
+        "error_type" => error_type,
+    )
+    .increment(1);
+}
@@ -0,0 +1,35 @@
+//! Records errors on the active OpenTelemetry span (enabled with the `otel` feature).
+
+use opentelemetry::{
+    trace::{get_active_span, Status},
+    KeyValue,
+};
+
+/// Adds an `exception` event to the active span and marks it as failed. `message` is the error's `Debug` output, if it implements `Debug`.
+pub fn record_error(
+    function: &'static str,
+    error_type: &'static str,
+    message: Option<String>,
+    line_and_expr: Option<(u32, &str)>,
+) {
+    get_active_span(|span| {
+        if !span.is_recording() {
+            return;
+        }
+        let mut attributes = vec![
+            KeyValue::new("exception.type", error_type),
+            KeyValue::new("code.function.name", function),
+        ];
+        if let Some(message) = &message {
+            attributes.push(KeyValue::new("exception.message", message.clone()));
+        }
+        if let Some((line, expr)) = line_and_expr {
+            attributes.push(KeyValue::new("code.line.number", i64::from(line)));
+            attributes.push(KeyValue::new("wrap_match.expr", expr.to_owned()));
+        }
+        span.add_event("exception", attributes);
+        span.set_status(Status::error(
+            message.unwrap_or_else(|| error_type.to_owned()),
+        ));
+    });
+}
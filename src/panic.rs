@@ -0,0 +1,85 @@
+//! Catching panics, for the `catch_panics` option (and `spawn_logged`).
+
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The error of a wrapped function that panicked while [`catch_panics`](crate#catch_panics-and-panic_message) is enabled. To use `catch_panics`, the
+/// function's error type has to implement `From<Panicked>` (which boxed errors like `Box<dyn Error + Send + Sync>` already do).
+#[derive(Debug, Clone)]
+pub struct Panicked {
+    function: &'static str,
+    message: String,
+}
+
+impl Panicked {
+    fn new(function: &'static str, payload: &(dyn Any + Send)) -> Self {
+        Self {
+            function,
+            message: panic_message(payload).to_owned(),
+        }
+    }
+
+    /// The name of the function that panicked
+    pub fn function(&self) -> &'static str {
+        self.function
+    }
+
+    /// The message the function panicked with, or `<non-string panic payload>` if it didn't panic with a string
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} panicked: {}", self.function, self.message)
+    }
+}
+
+impl Error for Panicked {}
+
+/// Runs a function, catching its panic if it has one. Enabling `catch_panics` is what asserts the function is unwind safe.
+pub fn catch_panic<T>(function: &'static str, f: impl FnOnce() -> T) -> Result<T, Panicked> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| Panicked::new(function, &*payload))
+}
+
+/// Runs a future, catching its panic if it has one while it's polled
+pub async fn catch_panic_async<F: Future>(
+    function: &'static str,
+    future: F,
+) -> Result<F::Output, Panicked> {
+    CatchUnwind(Box::pin(future))
+        .await
+        .map_err(|payload| Panicked::new(function, &*payload))
+}
+
+/// Catches panics while polling a future
+pub(crate) struct CatchUnwind<F>(pub(crate) Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.0.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// The message of a panic, if it was made with a string (which is the case for `panic!` and friends)
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>")
+}
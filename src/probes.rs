@@ -0,0 +1,302 @@
+//! Autoref-based specialization probes, which let generated code use a trait implementation if a type has one, or fall back to something else if
+//! it doesn't. Specialized implementations go on `ErrorProbe` and fallbacks go on `&ErrorProbe`; generated code calls methods on
+//! `(&ErrorProbe(&value))`, so the specialized implementations are preferred. This only works for types that are known when the macro is expanded.
+
+use std::{
+    any::{type_name, Any},
+    error::Error,
+    sync::RwLock,
+};
+
+pub struct ErrorProbe<'a, T: ?Sized>(pub &'a T);
+
+type ErrorTypeDowncast = for<'e> fn(&'e (dyn Error + 'static)) -> Option<&'e dyn Any>;
+
+static ERROR_TYPES: RwLock<Vec<(ErrorTypeDowncast, &'static str)>> = RwLock::new(Vec::new());
+
+/// Registers an error type so `{error_type}` can show its name when it is hidden behind a `Box<dyn Error>`, and so
+/// [extractors](crate::register_extractor) get the error itself instead of the box.
+///
+/// Common error types from the standard library are already known.
+pub fn register_error_type<T: Error + 'static>() {
+    ERROR_TYPES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((
+            |e| e.downcast_ref::<T>().map(|e| e as &dyn Any),
+            type_name::<T>(),
+        ));
+}
+
+/// Finds the name of the concrete type of a `dyn Error`, if it is a known type
+fn dyn_error_type_name(error: &(dyn Error + 'static)) -> Option<&'static str> {
+    downcast_known_type(error).map(|(name, _)| name)
+}
+
+/// Finds the concrete type of a `dyn Error` if it is a known type, returning its name and the error as that type
+fn downcast_known_type<'e>(
+    error: &'e (dyn Error + 'static),
+) -> Option<(&'static str, &'e dyn Any)> {
+    macro_rules! known_types {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if let Some(error) = error.downcast_ref::<$ty>() {
+                    return Some((type_name::<$ty>(), error));
+                }
+            )*
+        };
+    }
+    known_types!(
+        std::io::Error,
+        std::fmt::Error,
+        std::num::ParseIntError,
+        std::num::ParseFloatError,
+        std::num::TryFromIntError,
+        std::str::ParseBoolError,
+        std::str::Utf8Error,
+        std::string::FromUtf8Error,
+        std::char::ParseCharError,
+        std::net::AddrParseError,
+        std::env::VarError,
+        std::time::SystemTimeError,
+    );
+
+    ERROR_TYPES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find_map(|(downcast, name)| Some((*name, downcast(error)?)))
+}
+
+/// The error that extractors get: the error inside a `Box<dyn Error>` if its type is known, since extractors couldn't downcast the box to it,
+/// otherwise the error itself
+pub(crate) fn extractor_input(error: &dyn Any) -> &dyn Any {
+    macro_rules! unbox {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if let Some(boxed) = error.downcast_ref::<$ty>() {
+                    return downcast_known_type(&**boxed).map_or(error, |(_, error)| error);
+                }
+            )*
+        };
+    }
+    unbox!(
+        Box<dyn Error>,
+        Box<dyn Error + Send>,
+        Box<dyn Error + Sync>,
+        Box<dyn Error + Send + Sync>,
+    );
+    error
+}
+
+/// `Box<dyn Error>` doesn't implement `Error`, so it needs to be handled separately
+pub trait BoxedErrorProbe<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn Error + 'static);
+
+    fn error_type_name(&self) -> &'static str;
+
+    /// For `DynErrorProbe`
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)> {
+        Some(self.as_dyn_error())
+    }
+}
+
+macro_rules! impl_boxed_error_probe {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> BoxedErrorProbe<'a> for ErrorProbe<'a, $ty> {
+                fn as_dyn_error(&self) -> &'a (dyn Error + 'static) {
+                    &**self.0
+                }
+
+                fn error_type_name(&self) -> &'static str {
+                    dyn_error_type_name(BoxedErrorProbe::as_dyn_error(self)).unwrap_or(type_name::<$ty>())
+                }
+            }
+        )*
+    };
+}
+
+impl_boxed_error_probe!(
+    Box<dyn Error>,
+    Box<dyn Error + Send>,
+    Box<dyn Error + Sync>,
+    Box<dyn Error + Send + Sync>
+);
+
+/// Like `BoxedErrorProbe`, for boxed errors with a lifetime other than `'static` (like `Box<dyn Error + Send + Sync + 'a>`). Their concrete type can't
+/// be found by downcasting, so `{error_type}` is the name of the box.
+pub trait BorrowedBoxedErrorProbe<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn Error + 'a);
+
+    fn error_type_name(&self) -> &'static str;
+
+    /// For `DynErrorProbe`
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)> {
+        Some(self.as_dyn_error())
+    }
+}
+
+macro_rules! impl_borrowed_boxed_error_probe {
+    ($(($($bound:ident),*)),*) => {
+        $(
+            impl<'a, 'e: 'a> BorrowedBoxedErrorProbe<'a> for ErrorProbe<'a, Box<dyn Error $(+ $bound)* + 'e>> {
+                fn as_dyn_error(&self) -> &'a (dyn Error + 'a) {
+                    &**self.0
+                }
+
+                fn error_type_name(&self) -> &'static str {
+                    type_name::<Box<dyn Error $(+ $bound)*>>()
+                }
+            }
+        )*
+    };
+}
+
+impl_borrowed_boxed_error_probe!((), (Send), (Sync), (Send, Sync));
+
+pub trait TypeNameProbe {
+    fn error_type_name(&self) -> &'static str;
+}
+
+impl<T: ?Sized> TypeNameProbe for &ErrorProbe<'_, T> {
+    fn error_type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+}
+
+pub trait ErrorTraitProbe<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn Error + 'a);
+}
+
+impl<'a, T: Error + 'a> ErrorTraitProbe<'a> for &ErrorProbe<'a, T> {
+    fn as_dyn_error(&self) -> &'a (dyn Error + 'a) {
+        self.0
+    }
+}
+
+pub trait LevelProbe {
+    fn wrap_match_level(&self) -> Option<crate::Level>;
+}
+
+impl<T: crate::WrapMatchLevel + ?Sized> LevelProbe for ErrorProbe<'_, T> {
+    fn wrap_match_level(&self) -> Option<crate::Level> {
+        Some(self.0.level())
+    }
+}
+
+pub trait NoLevelProbe {
+    fn wrap_match_level(&self) -> Option<crate::Level>;
+}
+
+impl<T: ?Sized> NoLevelProbe for &ErrorProbe<'_, T> {
+    fn wrap_match_level(&self) -> Option<crate::Level> {
+        None
+    }
+}
+
+pub trait ExitStatusProbe {
+    fn exit_status(&self) -> Option<u8>;
+}
+
+impl<T: crate::ExitStatus + ?Sized> ExitStatusProbe for ErrorProbe<'_, T> {
+    fn exit_status(&self) -> Option<u8> {
+        Some(self.0.exit_status())
+    }
+}
+
+pub trait NoExitStatusProbe {
+    fn exit_status(&self) -> Option<u8>;
+}
+
+impl<T: ?Sized> NoExitStatusProbe for &ErrorProbe<'_, T> {
+    fn exit_status(&self) -> Option<u8> {
+        None
+    }
+}
+
+pub trait DebugProbe {
+    fn debug_string(&self) -> Option<String>;
+}
+
+impl<T: std::fmt::Debug + ?Sized> DebugProbe for ErrorProbe<'_, T> {
+    fn debug_string(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+pub trait NoDebugProbe {
+    fn debug_string(&self) -> Option<String>;
+}
+
+impl<T: ?Sized> NoDebugProbe for &ErrorProbe<'_, T> {
+    fn debug_string(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Like `ErrorTraitProbe`, but for errors that don't have to implement `Error` (`Box<dyn Error>` goes through `BoxedErrorProbe`)
+pub trait DynErrorProbe<'a> {
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)>;
+}
+
+impl<'a, T: Error + 'a> DynErrorProbe<'a> for ErrorProbe<'a, T> {
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)> {
+        Some(self.0)
+    }
+}
+
+pub trait NoDynErrorProbe<'a> {
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)>;
+}
+
+impl<'a, T: ?Sized> NoDynErrorProbe<'a> for &ErrorProbe<'a, T> {
+    fn dyn_error(&self) -> Option<&'a (dyn Error + 'a)> {
+        None
+    }
+}
+
+/// The deepest error in the chain of errors that caused an error (or the error itself if it has no source), for `{root_cause}`
+pub fn root_cause<'a>(error: &'a (dyn Error + 'a)) -> &'a (dyn Error + 'a) {
+    let mut root = error;
+    while let Some(source) = root.source() {
+        root = source;
+    }
+    root
+}
+
+/// The chain of errors that caused an error, for `{sources}`
+pub struct Sources<'a>(pub &'a (dyn Error + 'a));
+
+impl Sources<'_> {
+    fn fmt_with(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        fmt: fn(&dyn Error, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        let mut source = self.0.source();
+        let mut first = true;
+        while let Some(error) = source {
+            if !first {
+                f.write_str("; ")?;
+            }
+            first = false;
+            f.write_str("caused by: ")?;
+            fmt(error, f)?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Sources<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, |e, f| std::fmt::Display::fmt(e, f))
+    }
+}
+
+impl std::fmt::Debug for Sources<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, |e, f| std::fmt::Debug::fmt(e, f))
+    }
+}
@@ -0,0 +1,140 @@
+use std::{error::Error, sync::RwLock, time::Duration};
+
+use crate::__private::SourceLocation;
+
+static REPORTER: RwLock<&'static dyn Reporter> = RwLock::new(&());
+
+/// Receives the results of functions wrapped with the [`reporter`](crate#reporter) option, so they can be sent somewhere other than `log` or `tracing`.
+/// See [`set_reporter`].
+///
+/// Both methods do nothing by default, so reporters only need to implement the ones they care about.
+///
+/// ```
+/// struct FailureCounter(std::sync::atomic::AtomicUsize);
+///
+/// impl wrap_match::Reporter for FailureCounter {
+///     fn on_error(&self, info: &wrap_match::ErrorInfo<'_>) {
+///         self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+///         eprintln!("{} failed on line {:?}", info.function, info.line);
+///     }
+/// }
+///
+/// static FAILURES: FailureCounter = FailureCounter(std::sync::atomic::AtomicUsize::new(0));
+/// wrap_match::set_reporter(&FAILURES);
+/// ```
+pub trait Reporter: Send + Sync {
+    /// Called when a wrapped function returns `Ok`
+    fn on_success(&self, info: &SuccessInfo) {
+        let _ = info;
+    }
+
+    /// Called when a wrapped function returns `Err`
+    fn on_error(&self, info: &ErrorInfo<'_>) {
+        let _ = info;
+    }
+}
+
+/// The default reporter, which ignores everything
+impl Reporter for () {}
+
+/// Sets the reporter used by all wrapped functions with the [`reporter`](crate#reporter) option. Use `&()` to go back to the default reporter, which
+/// ignores everything.
+pub fn set_reporter(reporter: &'static dyn Reporter) {
+    *REPORTER.write().unwrap_or_else(|e| e.into_inner()) = reporter;
+}
+
+fn reporter() -> &'static dyn Reporter {
+    *REPORTER.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A successful call of a wrapped function, for [`Reporter::on_success`]
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SuccessInfo {
+    /// The name of the function
+    pub function: &'static str,
+    /// The module the function is in
+    pub module: &'static str,
+    /// How long the function took to run
+    pub duration: Duration,
+}
+
+/// An error returned by a wrapped function, for [`Reporter::on_error`].
+///
+/// With the `serde` feature, this implements `Serialize`, with the error serialized as its `Display` output.
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ErrorInfo<'a> {
+    /// The name of the function
+    pub function: &'static str,
+    /// The module the function is in
+    pub module: &'static str,
+    /// The name of the error's type, like `{error_type}`
+    pub error_type: &'static str,
+    /// The error, if its type implements [`Error`]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error"))]
+    pub error: Option<&'a (dyn Error + 'a)>,
+    /// The error's `Debug` output, if its type implements `Debug`
+    pub debug: Option<String>,
+    /// The file of the try expression (`?`) that caused the error, if it was caused by one in a file the user wrote (not generated code)
+    pub file: Option<&'static str>,
+    /// The line of the try expression (`?`) that caused the error, if it was caused by one
+    pub line: Option<u32>,
+    /// The column of the try expression (`?`) that caused the error, if it was caused by one in a file the user wrote (not generated code)
+    pub column: Option<u32>,
+    /// The try expression that caused the error, like `{expr}`
+    pub expr: Option<&'a str>,
+    /// How long the function took to run
+    pub duration: Duration,
+}
+
+pub fn report_success(function: &'static str, module: &'static str, duration: Duration) {
+    reporter().on_success(&SuccessInfo {
+        function,
+        module,
+        duration,
+    });
+}
+
+// only called by generated code, which passes everything it has
+#[allow(clippy::too_many_arguments)]
+pub fn report_error(
+    function: &'static str,
+    module: &'static str,
+    error_type: &'static str,
+    error: Option<&(dyn Error + '_)>,
+    debug: Option<String>,
+    line_and_expr: Option<(u32, &str)>,
+    location: Option<SourceLocation>,
+    duration: Duration,
+) {
+    let (file, column) = match location {
+        Some(SourceLocation::File { file, column, .. }) => (Some(file), Some(column)),
+        _ => (None, None),
+    };
+    reporter().on_error(&ErrorInfo {
+        function,
+        module,
+        error_type,
+        error,
+        debug,
+        file,
+        line: line_and_expr.map(|(line, _)| line),
+        column,
+        expr: line_and_expr.map(|(_, expr)| expr),
+        duration,
+    });
+}
+
+#[cfg(feature = "serde")]
+fn serialize_error<S: serde::Serializer>(
+    error: &Option<&(dyn Error + '_)>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match error {
+        Some(error) => serializer.collect_str(error),
+        None => serializer.serialize_none(),
+    }
+}
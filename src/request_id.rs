@@ -0,0 +1,89 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+thread_local! {
+    /// The request ID of the future that is currently being polled on this thread
+    static REQUEST_ID: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Runs a future with a request ID, which wrapped functions called while it runs can log with `{request_id}`, so all of the messages about a request can
+/// be found by searching for its ID.
+///
+/// The ID is only set while the future is being polled, so it doesn't leak into other tasks on the same thread. Tasks spawned by the future don't get
+/// it, so wrap them with `with_request_id` too if they should.
+///
+/// ```
+/// # #[derive(Debug)] enum CustomError { Error }
+/// #[wrap_match::wrap_match(error_message = "[{request_id}] {function} failed: {error:?}")]
+/// async fn load_user() -> Result<(), CustomError> {
+///     Err(CustomError::Error)?;
+///     Ok(())
+/// }
+///
+/// # pollster::block_on(async {
+/// wrap_match::with_request_id("7f3a", load_user()).await.unwrap_err();
+/// // logs "[7f3a] load_user failed: Error"
+/// # });
+/// ```
+pub fn with_request_id<F: Future>(id: impl Into<Arc<str>>, future: F) -> WithRequestId<F> {
+    WithRequestId {
+        id: id.into(),
+        future: Box::pin(future),
+    }
+}
+
+/// The request ID set with [`with_request_id`] for the future that is currently running, if there is one
+pub fn request_id() -> Option<Arc<str>> {
+    REQUEST_ID.with(|id| id.borrow().clone())
+}
+
+/// A future with a request ID. See [`with_request_id`].
+pub struct WithRequestId<F> {
+    id: Arc<str>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithRequestId<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _guard = RestoreRequestId(REQUEST_ID.with(|id| id.replace(Some(self.id.clone()))));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// Puts back the request ID of an outer future once polling an inner one is done, even if it panicked
+struct RestoreRequestId(Option<Arc<str>>);
+
+impl Drop for RestoreRequestId {
+    fn drop(&mut self) {
+        REQUEST_ID.with(|id| *id.borrow_mut() = self.0.take());
+    }
+}
+
+/// The current request ID, for `{request_id}`
+pub struct CurrentRequestId(Option<Arc<str>>);
+
+impl CurrentRequestId {
+    pub fn get() -> Self {
+        Self(request_id())
+    }
+}
+
+impl fmt::Display for CurrentRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.as_deref().unwrap_or("-"))
+    }
+}
+
+impl fmt::Debug for CurrentRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0.as_deref(), f)
+    }
+}
@@ -0,0 +1,99 @@
+//! Atomic primitives for the statics the macro generates in wrapped functions (such as the invocation counter for `cold_start`).
+//!
+//! Wrapped functions can be called from any number of threads at once, so instead of emitting atomics inline, the expansion goes through these types,
+//! which are tested with [loom](https://docs.rs/loom) (run `RUSTFLAGS="--cfg loom" cargo test --release --test loom`). They are public so the generated
+//! code can use them, but are also fine to use directly.
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// A counter that can be incremented from many threads at once. Every increment gets its own count, so exactly one caller sees `1`.
+///
+/// ```
+/// static CALLS: wrap_match::runtime::Counter = wrap_match::runtime::Counter::new();
+/// assert_eq!(CALLS.increment(), 1);
+/// assert_eq!(CALLS.increment(), 2);
+/// assert_eq!(CALLS.get(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct Counter {
+    count: AtomicU64,
+}
+
+impl Counter {
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// loom's atomics can't be made in constants
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Adds one to the counter and returns the new count. The count saturates at `u64::MAX` instead of wrapping around to `0`.
+    pub fn increment(&self) -> u64 {
+        // the count is only used for its value, so no other memory needs to be synchronized with it
+        match self
+            .count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_add(1)
+            }) {
+            Ok(previous) => previous + 1,
+            Err(max) => max,
+        }
+    }
+
+    /// The current count
+    pub fn get(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// A flag that is set once. Exactly one caller of [`set`](Flag::set) sees `true`, which makes it useful for doing something only the first time.
+///
+/// ```
+/// static WARNED: wrap_match::runtime::Flag = wrap_match::runtime::Flag::new();
+/// assert!(WARNED.set());
+/// assert!(!WARNED.set());
+/// assert!(WARNED.is_set());
+/// ```
+#[derive(Debug, Default)]
+pub struct Flag {
+    set: AtomicBool,
+}
+
+impl Flag {
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        Self {
+            set: AtomicBool::new(false),
+        }
+    }
+
+    /// loom's atomics can't be made in constants
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        Self {
+            set: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the flag, returning `true` if it wasn't set before
+    pub fn set(&self) -> bool {
+        // whoever sets the flag first may have written data that callers who see it set read, so this releases and acquires
+        !self.set.swap(true, Ordering::AcqRel)
+    }
+
+    /// Whether the flag has been set
+    pub fn is_set(&self) -> bool {
+        self.set.load(Ordering::Acquire)
+    }
+}
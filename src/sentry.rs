@@ -0,0 +1,51 @@
+//! Reports errors to Sentry (enabled with the `sentry` feature).
+
+use std::error::Error;
+
+use sentry_core::protocol::{Breadcrumb, Event, Level, Map};
+
+/// Captures an event for an error, or adds a breadcrumb for it if `breadcrumb` is true. `error` is the error if it implements `Error` (so the event
+/// gets its type and sources), and `message` is its `Debug` output, if it implements `Debug`. The function and, for errors from a `?`, the line and
+/// expression are attached as extras (or breadcrumb data).
+pub fn report_error(
+    function: &'static str,
+    error_type: &'static str,
+    error: Option<&(dyn Error + '_)>,
+    message: Option<String>,
+    line_and_expr: Option<(u32, &str)>,
+    breadcrumb: bool,
+) {
+    let mut data = Map::new();
+    data.insert("function".to_owned(), function.into());
+    if let Some((line, expr)) = line_and_expr {
+        data.insert("line".to_owned(), line.into());
+        data.insert("expr".to_owned(), expr.into());
+    }
+    let message = format!(
+        "An error occurred when running {function}: {}",
+        message.as_deref().unwrap_or(error_type)
+    );
+
+    if breadcrumb {
+        sentry_core::add_breadcrumb(Breadcrumb {
+            ty: "error".to_owned(),
+            category: Some("wrap_match".to_owned()),
+            level: Level::Error,
+            message: Some(message),
+            data,
+            ..Default::default()
+        });
+        return;
+    }
+
+    let mut event = match error {
+        Some(error) => sentry_core::event_from_error(error),
+        None => Event {
+            message: Some(message),
+            level: Level::Error,
+            ..Default::default()
+        },
+    };
+    event.extra.extend(data);
+    sentry_core::capture_event(event);
+}
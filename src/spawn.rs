@@ -0,0 +1,70 @@
+use std::{
+    fmt::Debug,
+    future::Future,
+    panic::{self, Location},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::panic::{panic_message, CatchUnwind};
+
+/// Logs a message through the logging crate picked by the enabled features, like the messages of wrapped functions
+macro_rules! log_message {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::$level!($($arg)+);
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        eprintln!($($arg)+);
+    }};
+}
+
+/// Spawns a task on the current tokio runtime and logs its result, so background tasks get the same treatment as wrapped functions even though they
+/// can't be annotated (enabled with the `tokio` feature).
+///
+/// Success is logged at the `info` level, and errors and panics are logged at the `error` level. The messages mention where the task was spawned,
+/// since tasks don't have names. The returned handle behaves like the one from [`tokio::spawn`]: it gives the task's `Result`, and if the task panicked,
+/// the panic is resumed so [`JoinError::is_panic`](tokio::task::JoinError::is_panic) still works.
+///
+/// Like [`tokio::spawn`], this panics if it isn't called from a tokio runtime.
+///
+/// ```
+/// # #[derive(Debug)] enum CustomError { Error }
+/// # async fn sync_database() -> Result<(), CustomError> { Err(CustomError::Error) }
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let handle = wrap_match::spawn_logged(async { sync_database().await });
+/// // logs "An error occurred in the task spawned at src/main.rs:3:14: Error"
+/// # handle.await.unwrap().unwrap_err();
+/// # });
+/// ```
+#[track_caller]
+pub fn spawn_logged<F, T, E>(future: F) -> JoinHandle<Result<T, E>>
+where
+    F: Future<Output = Result<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Debug + Send + 'static,
+{
+    let location = Location::caller();
+    tokio::spawn(async move {
+        match CatchUnwind(Box::pin(future)).await {
+            Ok(Ok(ok)) => {
+                log_message!(info, "Successfully ran the task spawned at {location}");
+                Ok(ok)
+            }
+            Ok(Err(error)) => {
+                log_message!(
+                    error,
+                    "An error occurred in the task spawned at {location}: {error:?}"
+                );
+                Err(error)
+            }
+            Err(payload) => {
+                let message = panic_message(&*payload);
+                log_message!(error, "The task spawned at {location} panicked: {message}");
+                panic::resume_unwind(payload)
+            }
+        }
+    })
+}
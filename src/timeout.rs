@@ -0,0 +1,43 @@
+//! Timeouts for async functions, for the `timeout` option (enabled with the `tokio` feature).
+
+use std::{error::Error, fmt, future::Future, time::Duration};
+
+/// The error of a wrapped async function that didn't finish within its [`timeout`](crate#timeout). To use `timeout`, the function's error type has to
+/// implement `From<TimedOut>` (which boxed errors like `Box<dyn Error + Send + Sync>` already do).
+#[derive(Debug, Clone)]
+pub struct TimedOut {
+    function: &'static str,
+    after: Duration,
+}
+
+impl TimedOut {
+    /// The name of the function that timed out
+    pub fn function(&self) -> &'static str {
+        self.function
+    }
+
+    /// How long the function was allowed to run for
+    pub fn after(&self) -> Duration {
+        self.after
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} timed out after {:?}", self.function, self.after)
+    }
+}
+
+impl Error for TimedOut {}
+
+/// Runs a future until it finishes or the timeout elapses. Like `tokio::time::timeout`, this panics if it isn't run on a tokio runtime with the time
+/// driver enabled.
+pub async fn timeout<F: Future>(
+    function: &'static str,
+    after: Duration,
+    future: F,
+) -> Result<F::Output, TimedOut> {
+    tokio::time::timeout(after, future)
+        .await
+        .map_err(|_| TimedOut { function, after })
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    error::Error,
+    future::Future,
+    pin::pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use wrap_match::Panicked;
+
+use common::LOGGED;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+    Panicked(Panicked),
+}
+
+impl From<Panicked> for CustomError {
+    fn from(panicked: Panicked) -> Self {
+        Self::Panicked(panicked)
+    }
+}
+
+#[test]
+fn catch_panics() {
+    common::init(log::LevelFilter::Info);
+    // the panics are expected, so they don't need to be printed
+    std::panic::set_hook(Box::new(|_| {}));
+
+    assert_eq!(ok_no_panic().unwrap(), 1);
+    let CustomError::Panicked(panicked) = err_panic(3).unwrap_err() else {
+        panic!("expected a panic");
+    };
+    assert_eq!(panicked.function(), "err_panic");
+    assert_eq!(
+        panicked.message(),
+        "index out of bounds: the len is 1 but the index is 3"
+    );
+    assert!(matches!(err_returned(), Err(CustomError::Error)));
+    let error = err_panic_boxed("config.ini").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "err_panic_boxed panicked: config.ini is missing"
+    );
+
+    let mut context = Context::from_waker(Waker::noop());
+    let Poll::Ready(Err(CustomError::Panicked(panicked))) =
+        pin!(err_panic_async()).poll(&mut context)
+    else {
+        panic!("expected a panic");
+    };
+    assert_eq!(panicked.message(), "<non-string panic payload>");
+
+    let _ = std::panic::take_hook();
+    let returned = format!(
+        "An error occurred when running err_returned (caused by `Err(CustomError::Error)` on line {}): Error",
+        ERR_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_no_panic",
+            "err_panic panicked: index out of bounds: the len is 1 but the index is 3",
+            returned.as_str(),
+            "loading config.ini panicked: config.ini is missing",
+            "err_panic_async panicked: <non-string panic payload>",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", catch_panics = true)]
+fn ok_no_panic() -> Result<u8, CustomError> {
+    Ok(1)
+}
+
+#[wrap_match::wrap_match(logger = "log", catch_panics = true)]
+fn err_panic(index: usize) -> Result<u8, CustomError> {
+    let values = [1];
+    Ok(values[index])
+}
+
+#[wrap_match::wrap_match(logger = "log", catch_panics = true)]
+fn err_returned() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    catch_panics = true,
+    panic_message = "loading {path} panicked: {panic}"
+)]
+fn err_panic_boxed(path: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    panic!("{path} is missing");
+}
+
+#[wrap_match::wrap_match(logger = "log", catch_panics = true)]
+async fn err_panic_async() -> Result<(), CustomError> {
+    std::panic::panic_any(1);
+}
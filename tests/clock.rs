@@ -0,0 +1,122 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::LOGGED;
+
+static CLOCK: wrap_match::MockClock = wrap_match::MockClock::new();
+
+#[test]
+fn mock_clock() {
+    common::init(log::LevelFilter::Trace);
+    wrap_match::set_clock(&CLOCK);
+
+    ok_slow().unwrap();
+    err_slow().unwrap_err();
+    ok_millis().unwrap();
+    ok_seconds().unwrap();
+    for duration in [
+        Duration::from_nanos(850),
+        Duration::from_nanos(1500),
+        Duration::from_millis(250),
+        Duration::from_millis(1500),
+        Duration::from_secs(150),
+        Duration::from_secs(7500),
+    ] {
+        ok_human(duration).unwrap();
+    }
+    err_escaped_braces().unwrap_err();
+    ok_timestamp().unwrap();
+
+    let messages = LOGGED.lock().unwrap();
+    let (timestamp, messages) = messages.split_last().unwrap();
+    // like `2024-05-01T12:30:00.250Z`
+    let timestamp = timestamp.strip_prefix("ok_timestamp at ").unwrap();
+    assert_eq!(timestamp.len(), 24);
+    assert_eq!(&timestamp[10..11], "T");
+    assert!(timestamp.ends_with('Z'));
+    assert!(timestamp.as_bytes()[..4].iter().all(u8::is_ascii_digit));
+    assert_eq!(
+        messages,
+        [
+            "ok_slow took 1.5s",
+            "err_slow failed after 250ms: ()",
+            "ok_millis took 1234.567 ms",
+            "ok_seconds took 1.2 s",
+            "ok_human took 850ns",
+            "ok_human took 1.5µs",
+            "ok_human took 250.0ms",
+            "ok_human took 1.5s",
+            "ok_human took 2m 30s",
+            "ok_human took 2h 5m",
+            r#"{"function": "err_escaped_braces", "error": "()", "literal": "{error} {function}", "took": "250.0ms"}"#,
+        ]
+    );
+}
+
+// `bridge_safe` makes sure these go to our logger even with the `tracing` feature
+#[wrap_match::wrap_match(success_message = "{function} took {duration:?}", bridge_safe = true)]
+fn ok_slow() -> Result<(), ()> {
+    CLOCK.advance(Duration::from_millis(1500));
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    error_message = "{function} failed after {duration:?}: {error:?}",
+    log_success = false,
+    bridge_safe = true
+)]
+fn err_slow() -> Result<(), ()> {
+    CLOCK.advance(Duration::from_millis(250));
+    Err(())?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    success_message = "{function} took {duration} ms",
+    duration_format = "ms",
+    bridge_safe = true
+)]
+fn ok_millis() -> Result<(), ()> {
+    CLOCK.advance(Duration::from_micros(1_234_567));
+    Ok(())
+}
+
+// `Debug` formats work the same way
+#[wrap_match::wrap_match(
+    success_message = "{function} took {duration:?} s",
+    duration_format = "s",
+    duration_precision = 1,
+    bridge_safe = true
+)]
+fn ok_seconds() -> Result<(), ()> {
+    CLOCK.advance(Duration::from_micros(1_234_567));
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    success_message = "{function} took {duration}",
+    duration_format = "human",
+    bridge_safe = true
+)]
+fn ok_human(duration: Duration) -> Result<(), ()> {
+    CLOCK.advance(duration);
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    error_message = r#"{{"function": "{function}", "error": "{error:?}", "literal": "{{error}} {{function}}", "took": "{duration:.1?}"}}"#,
+    bridge_safe = true
+)]
+fn err_escaped_braces() -> Result<(), ()> {
+    CLOCK.advance(Duration::from_millis(250));
+    Err(())?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(success_message = "{function} at {timestamp}", bridge_safe = true)]
+fn ok_timestamp() -> Result<(), ()> {
+    Ok(())
+}
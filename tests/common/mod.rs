@@ -0,0 +1,56 @@
+//! A logger shared by the integration tests that keeps everything that was logged
+
+// not every test uses everything in here
+#![allow(dead_code)]
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// The messages that were logged, in order
+pub static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The level of each message in [`LOGGED`]
+pub static LEVELS: Mutex<Vec<Level>> = Mutex::new(Vec::new());
+
+/// The key-values of each message in [`LOGGED`]
+#[cfg(feature = "kv")]
+pub static KEY_VALUES: Mutex<Vec<Vec<(String, String)>>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        // held until the key-values are pushed, so both stay in the same order
+        let mut logged = LOGGED.lock().unwrap();
+        #[cfg(feature = "kv")]
+        {
+            use log::kv::{Error, Key, Value, VisitSource};
+
+            struct Collect(Vec<(String, String)>);
+            impl<'kvs> VisitSource<'kvs> for Collect {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0.push((key.to_string(), value.to_string()));
+                    Ok(())
+                }
+            }
+            let mut collect = Collect(vec![]);
+            record.key_values().visit(&mut collect).unwrap();
+            KEY_VALUES.lock().unwrap().push(collect.0);
+        }
+        LEVELS.lock().unwrap().push(record.level());
+        logged.push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sets the logger and the max level; can only be called once per test binary
+pub fn init(level: LevelFilter) {
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(level);
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use common::LOGGED;
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+mod messages {
+    pub const SYNCED: &str = "cache synced";
+    pub const SYNC_FAILED: &str = "cache sync failed ({error:?} isn't filled in)";
+}
+
+#[test]
+fn const_message() {
+    common::init(log::LevelFilter::Info);
+
+    ok_sync().unwrap();
+    err_sync().unwrap_err();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "cache synced",
+            "[db] cache sync failed ({error:?} isn't filled in) in err_sync",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", success_message = messages::SYNCED)]
+fn ok_sync() -> Result<(), CustomError> {
+    Ok(())
+}
+
+// prefixes and suffixes can still have placeholders
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = messages::SYNC_FAILED,
+    message_prefix = "[db] ",
+    message_suffix = " in {function}"
+)]
+fn err_sync() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
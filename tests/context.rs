@@ -0,0 +1,69 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use common::LOGGED;
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn context() {
+    common::init(log::LevelFilter::Trace);
+
+    err_context().unwrap_err();
+    {
+        let _sync = wrap_match::context("syncing user 42");
+        err_context().unwrap_err();
+        {
+            let _profile = wrap_match::context(format!("loading profile {}", 7));
+            err_context().unwrap_err();
+            err_context_placeholder().unwrap_err();
+            ok_flatten_context().unwrap().unwrap_err();
+        }
+        err_context().unwrap_err();
+    }
+    err_context().unwrap_err();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "err_context failed: Error",
+            "err_context failed: Error (context: syncing user 42)",
+            "err_context failed: Error (context: syncing user 42 > loading profile 7)",
+            "while syncing user 42 > loading profile 7 ([\"syncing user 42\", \"loading profile 7\"]): err_context_placeholder failed",
+            "ok_flatten_context returned Error (context: syncing user 42 > loading profile 7)",
+            "err_context failed: Error (context: syncing user 42)",
+            "err_context failed: Error",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    log_success = false,
+    error_message_without_info = "{function} failed: {error:?}"
+)]
+fn err_context() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "while {context} ({context:?}): {function} failed"
+)]
+fn err_context_placeholder() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    flatten = true,
+    inner_error_message = "{function} returned {error:?}"
+)]
+fn ok_flatten_context() -> Result<Result<(), CustomError>, CustomError> {
+    Ok(Err(CustomError::Error))
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    future::Future,
+    pin::pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use common::LOGGED;
+
+static INNER_LINE: AtomicU32 = AtomicU32::new(0);
+static SYNC_LINE: AtomicU32 = AtomicU32::new(0);
+static CHAIN_INNER_LINE: AtomicU32 = AtomicU32::new(0);
+static CHAIN_MIDDLE_LINE: AtomicU32 = AtomicU32::new(0);
+static SECTION_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+/// Returns `Pending` once, like a future that waits for IO
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn defer_to_outermost() {
+    common::init(log::LevelFilter::Error);
+
+    let mut context = Context::from_waker(Waker::noop());
+    let mut task = pin!(err_outer_async());
+    assert!(task.as_mut().poll(&mut context).is_pending());
+    // the suspended task's functions don't make this one defer to them
+    err_sync().unwrap_err();
+    assert!(matches!(
+        task.as_mut().poll(&mut context),
+        Poll::Ready(Err(CustomError::Error))
+    ));
+    // a cancelled task doesn't leave its functions behind either
+    let mut cancelled = Box::pin(err_outer_async());
+    assert!(cancelled.as_mut().poll(&mut context).is_pending());
+    drop(cancelled);
+    err_sync().unwrap_err();
+
+    err_chain_outer().unwrap_err();
+    // the inner error is handled, so it shouldn't show up in any later chain
+    ok_chain_recovered().unwrap();
+    err_chain_limited().unwrap_err();
+    err_chain_section().unwrap_err();
+    err_chain_section_outer().unwrap_err();
+
+    let (sync_line, inner_line) = (
+        SYNC_LINE.load(Ordering::Relaxed),
+        INNER_LINE.load(Ordering::Relaxed),
+    );
+    let (chain_inner_line, chain_middle_line, section_line) = (
+        CHAIN_INNER_LINE.load(Ordering::Relaxed),
+        CHAIN_MIDDLE_LINE.load(Ordering::Relaxed),
+        SECTION_LINE.load(Ordering::Relaxed),
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "err_sync failed".to_owned(),
+            format!(
+                "err_outer_async failed (propagated through err_sync (line {sync_line}) -> err_inner_async (line {inner_line}))"
+            ),
+            "err_sync failed".to_owned(),
+            format!(
+                "err_chain_outer failed (propagated through err_chain_inner (line {chain_inner_line}) -> err_chain_middle (line {chain_middle_line}))"
+            ),
+            // the frame closest to the error is kept, even when the outermost frames come first
+            format!("err_chain_limited failed: 1 more -> err_chain_inner (line {chain_inner_line})"),
+            // the section is left out when there's no chain
+            "err_chain_section failed".to_owned(),
+            format!("err_chain_section_outer failed (through err_chain_section (line {section_line}))"),
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed",
+    error_message_without_info = "{function} failed"
+)]
+async fn err_outer_async() -> Result<(), CustomError> {
+    err_inner_async().await?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed",
+    error_message_without_info = "{function} failed"
+)]
+async fn err_inner_async() -> Result<(), CustomError> {
+    YieldOnce(false).await;
+    INNER_LINE.store(line!() + 1, Ordering::Relaxed);
+    err_sync()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed",
+    error_message_without_info = "{function} failed"
+)]
+fn err_sync() -> Result<(), CustomError> {
+    SYNC_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed"
+)]
+fn err_chain_inner() -> Result<(), CustomError> {
+    CHAIN_INNER_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed"
+)]
+fn err_chain_middle() -> Result<(), CustomError> {
+    CHAIN_MIDDLE_LINE.store(line!() + 1, Ordering::Relaxed);
+    err_chain_inner()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed"
+)]
+fn err_chain_outer() -> Result<(), CustomError> {
+    err_chain_middle()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", defer_to_outermost = true)]
+fn ok_chain_recovered() -> Result<(), CustomError> {
+    let _ = err_chain_inner();
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    chain_max_frames = 1,
+    chain_order = "outermost_first",
+    error_message = "{function} failed: {chain}"
+)]
+fn err_chain_limited() -> Result<(), CustomError> {
+    err_chain_middle()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed{?chain} (through {chain}){/chain}"
+)]
+fn err_chain_section() -> Result<(), CustomError> {
+    SECTION_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    defer_to_outermost = true,
+    error_message = "{function} failed{?chain} (through {chain}){/chain}"
+)]
+fn err_chain_section_outer() -> Result<(), CustomError> {
+    err_chain_section()?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static FORMATTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts how many times it is formatted
+struct Probe;
+
+impl fmt::Debug for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        FORMATTED.fetch_add(1, Ordering::Relaxed);
+        f.write_str("Probe")
+    }
+}
+
+#[test]
+fn disabled_success_isnt_formatted() {
+    // success messages are logged at `info`, so nothing in them is evaluated when only errors are enabled
+    common::init(log::LevelFilter::Error);
+    ok_probe(&Probe).unwrap();
+    assert_eq!(FORMATTED.load(Ordering::Relaxed), 0);
+
+    log::set_max_level(log::LevelFilter::Info);
+    ok_probe(&Probe).unwrap();
+    assert_eq!(FORMATTED.load(Ordering::Relaxed), 3);
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "{function} returned {ok:?} for {probe:?} ({args:?})"
+)]
+fn ok_probe(probe: &Probe) -> Result<&Probe, ()> {
+    Ok(probe)
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{borrow::Cow, error::Error};
+
+use common::LOGGED;
+
+type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+#[test]
+fn dyn_errors() {
+    common::init(log::LevelFilter::Error);
+
+    err_static().unwrap_err();
+    pollster::block_on(err_static_async()).unwrap_err();
+    err_alias().unwrap_err();
+    err_sync().unwrap_err();
+    err_borrowed("x").unwrap_err();
+    pollster::block_on(err_borrowed_async("x")).unwrap_err();
+    for kind in 0..3 {
+        err_strings(kind).unwrap_err();
+    }
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "err_static: std::io::error::Error",
+            "err_static_async: core::num::error::ParseIntError",
+            "err_alias: core::fmt::Error",
+            "err_sync: core::fmt::Error",
+            // borrowed errors can't be downcast to find their type
+            "err_borrowed: alloc::boxed::Box<dyn core::error::Error + core::marker::Send + core::marker::Sync>",
+            "err_borrowed_async: alloc::boxed::Box<dyn core::error::Error + core::marker::Send + core::marker::Sync>",
+            "err_strings: str",
+            "err_strings: string",
+            "err_strings: cow",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: {error_type}")]
+fn err_static() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    std::fs::read("/wrap-match/does-not-exist")?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: {error_type}")]
+async fn err_static_async() -> Result<u32, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    Ok("x".parse::<u32>()?)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    require_error_trait = true,
+    error_message = "{function}: {error_type}"
+)]
+fn err_alias() -> Result<(), BoxError> {
+    Err(std::fmt::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    require_error_trait = true,
+    error_message = "{function}: {error_type}"
+)]
+fn err_sync() -> Result<(), Box<dyn Error + Sync>> {
+    Err(Box::new(std::fmt::Error) as Box<dyn Error + Sync>)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    require_error_trait = true,
+    error_message = "{function}: {error_type}"
+)]
+fn err_borrowed<'a>(s: &'a str) -> Result<&'a str, Box<dyn Error + Send + Sync + 'a>> {
+    s.parse::<u32>()?;
+    Ok(s)
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: {error_type}")]
+async fn err_borrowed_async<'a>(s: &'a str) -> Result<&'a str, Box<dyn Error + Send + Sync + 'a>> {
+    s.parse::<u32>()?;
+    Ok(s)
+}
+
+// like without wrap-match, strings can be converted into boxed errors with `.into()`
+#[wrap_match::wrap_match(logger = "log", error_message_without_info = "{function}: {error}")]
+fn err_strings(kind: u8) -> Result<(), BoxError> {
+    match kind {
+        0 => Err("str".into()),
+        1 => Err(String::from("string").into()),
+        _ => Err(Cow::Borrowed("cow").into()),
+    }
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use wrap_match::{ErrorInfo, Reporter, SuccessInfo};
+
+use common::LOGGED;
+
+/// Reports into the same list as the logger, so the order they ran in can be checked
+struct Collector;
+
+impl Reporter for Collector {
+    fn on_success(&self, info: &SuccessInfo) {
+        LOGGED
+            .lock()
+            .unwrap()
+            .push(format!("reported {}", info.function));
+    }
+
+    fn on_error(&self, info: &ErrorInfo<'_>) {
+        LOGGED
+            .lock()
+            .unwrap()
+            .push(format!("reported {} failing", info.function));
+    }
+}
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[derive(Debug)]
+struct InnerError;
+
+#[test]
+fn emit_order() {
+    common::init(log::LevelFilter::Info);
+    wrap_match::set_reporter(&Collector);
+
+    ok_default().unwrap();
+    err_default().unwrap_err();
+    ok_log_first().unwrap();
+    err_log_first().unwrap_err();
+    ok_flatten_log_first().unwrap().unwrap_err();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "reported ok_default",
+            "ok_default succeeded",
+            "reported err_default failing",
+            "err_default failed",
+            "ok_log_first succeeded",
+            "reported ok_log_first",
+            "err_log_first failed",
+            "reported err_log_first failing",
+            "ok_flatten_log_first failed",
+            "reported ok_flatten_log_first failing",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    reporter = "also",
+    success_message = "{function} succeeded"
+)]
+fn ok_default() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "also", error_message = "{function} failed")]
+fn err_default() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    reporter = "also",
+    emit_order(log, metrics, hook),
+    success_message = "{function} succeeded"
+)]
+fn ok_log_first() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    reporter = "also",
+    emit_order(log, hook, metrics),
+    error_message = "{function} failed"
+)]
+fn err_log_first() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    reporter = "also",
+    flatten = true,
+    emit_order(log, metrics, hook),
+    inner_error_message = "{function} failed"
+)]
+fn ok_flatten_log_first() -> Result<Result<(), InnerError>, CustomError> {
+    Ok(Err(InnerError))
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    process::ExitCode,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use common::LOGGED;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+    Panicked,
+}
+
+impl From<wrap_match::Panicked> for CustomError {
+    fn from(_: wrap_match::Panicked) -> Self {
+        Self::Panicked
+    }
+}
+
+#[derive(Debug)]
+enum CliError {
+    InvalidArguments,
+}
+
+impl wrap_match::ExitStatus for CliError {
+    fn exit_status(&self) -> u8 {
+        match self {
+            CliError::InvalidArguments => 2,
+        }
+    }
+}
+
+#[test]
+fn exit_code() {
+    common::init(log::LevelFilter::Info);
+
+    assert_eq!(ok_main(), ExitCode::SUCCESS);
+    assert_eq!(err_main(), ExitCode::FAILURE);
+    assert_eq!(err_exit_status_main(), ExitCode::from(2));
+    // the panic is expected, so it doesn't need to be printed
+    std::panic::set_hook(Box::new(|_| {}));
+    assert_eq!(err_panic_main(), ExitCode::FAILURE);
+    let _ = std::panic::take_hook();
+
+    let err = format!(
+        "An error occurred when running err_main (caused by `Err(CustomError::Error)` on line {}): Error",
+        ERR_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_main",
+            err.as_str(),
+            "An error occurred when running err_exit_status_main: InvalidArguments",
+            "err_panic_main panicked: no config",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", exit_code = true)]
+fn ok_main() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", exit_code = true)]
+fn err_main() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", exit_code = true)]
+fn err_exit_status_main() -> Result<(), CliError> {
+    Err(CliError::InvalidArguments.into())
+}
+
+#[wrap_match::wrap_match(logger = "log", exit_code = true, catch_panics = true)]
+fn err_panic_main() -> Result<(), CustomError> {
+    panic!("no config");
+}
@@ -0,0 +1,86 @@
+use std::{
+    env,
+    process::{Command, Output},
+};
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[derive(Debug)]
+enum CliError {
+    InvalidArguments,
+}
+
+impl wrap_match::ExitStatus for CliError {
+    fn exit_status(&self) -> u8 {
+        match self {
+            CliError::InvalidArguments => 2,
+        }
+    }
+}
+
+/// Set when the test runs itself again, in the process that should exit. The value is the function that should exit it.
+const CHILD: &str = "WRAP_MATCH_EXIT_ON_ERROR_CHILD";
+
+/// Runs the test again in a new process, which calls `function`
+fn run_child(function: &str) -> Output {
+    Command::new(env::current_exe().unwrap())
+        .args(["--exact", "exit_on_error", "--nocapture"])
+        .env(CHILD, function)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn exit_on_error() {
+    match env::var(CHILD).as_deref() {
+        Ok("err_main") => {
+            ok_main();
+            err_main();
+            unreachable!("err_main should have exited");
+        }
+        Ok("err_exit_status_main") => {
+            err_exit_status_main();
+            unreachable!("err_exit_status_main should have exited");
+        }
+        _ => {}
+    }
+
+    let output = run_child("err_main");
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Successfully ran ok_main\n"));
+    // the line isn't checked, since it can't be passed back from the child process
+    assert!(stderr.lines().any(|line| {
+        line.starts_with(
+            "An error occurred when running err_main (caused by `Err(CustomError::Error)` on line ",
+        ) && line.ends_with("): Error")
+    }));
+    assert!(!stderr.contains("should have exited"));
+
+    // the error type's exit status takes precedence over the one given to `exit_on_error`
+    let output = run_child("err_exit_status_main");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("An error occurred when running err_exit_status_main: InvalidArguments\n")
+    );
+}
+
+#[wrap_match::wrap_match(logger = "plain", disregard_result = true, exit_on_error = 3)]
+fn ok_main() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "plain", disregard_result = true, exit_on_error = 3)]
+fn err_main() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "plain", disregard_result = true, exit_on_error = 3)]
+fn err_exit_status_main() -> Result<(), CliError> {
+    Err(CliError::InvalidArguments.into())
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{error::Error, fmt};
+
+use common::LOGGED;
+
+#[derive(Debug)]
+struct TenantError {
+    tenant: &'static str,
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tenant {} failed", self.tenant)
+    }
+}
+
+impl Error for TenantError {}
+
+#[test]
+fn extractors() {
+    common::init(log::LevelFilter::Trace);
+
+    wrap_match::register_extractor("tenant", |e| {
+        e.downcast_ref::<TenantError>().map(|e| e.tenant.to_owned())
+    });
+    wrap_match::register_error_type::<TenantError>();
+    err_extractor().unwrap_err();
+    err_extractor_boxed().unwrap_err();
+
+    // the registry isn't locked while an extractor runs, so extractors can register other extractors (which are used right away, since
+    // placeholders are extracted in order)
+    wrap_match::register_extractor("region", |_| {
+        wrap_match::register_extractor("zone", |_| Some("b".to_owned()));
+        Some("eu".to_owned())
+    });
+    err_extractor_registering().unwrap_err();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "tenant acme failed (<unknown>)",
+            // the error is taken out of the box, since its type is registered
+            "boxed tenant acme failed",
+            "region eu, zone b",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    extract(tenant, missing),
+    error_message = "tenant {tenant} failed ({missing})"
+)]
+fn err_extractor() -> Result<(), TenantError> {
+    Err(TenantError { tenant: "acme" })?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    extract(tenant),
+    error_message = "boxed tenant {tenant} failed"
+)]
+fn err_extractor_boxed() -> Result<(), Box<dyn Error + Send + Sync>> {
+    Err(TenantError { tenant: "acme" })?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    extract(region, zone),
+    error_message = "region {region}, zone {zone}"
+)]
+fn err_extractor_registering() -> Result<(), TenantError> {
+    Err(TenantError { tenant: "acme" })?;
+    Ok(())
+}
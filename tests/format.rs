@@ -0,0 +1,148 @@
+use wrap_match::format::{self, Piece};
+
+/// Every string up to `max_len` characters long made of characters that matter to format strings
+fn inputs(max_len: u32) -> impl Iterator<Item = String> {
+    const ALPHABET: [char; 6] = ['{', '}', 'a', ':', '.', '('];
+    (0..=max_len).flat_map(|len| {
+        (0..ALPHABET.len().pow(len)).map(move |mut n| {
+            (0..len)
+                .map(|_| {
+                    let c = ALPHABET[n % ALPHABET.len()];
+                    n /= ALPHABET.len();
+                    c
+                })
+                .collect()
+        })
+    })
+}
+
+#[test]
+fn parse_round_trips() {
+    for input in inputs(6) {
+        let mut output = String::new();
+        for piece in format::parse(&input) {
+            piece.write_to(&mut output);
+        }
+        assert_eq!(output, input);
+    }
+}
+
+#[test]
+fn escaped_braces_are_literals() {
+    assert_eq!(
+        format::parse("{{a}} {b:?}"),
+        [
+            Piece::Literal("{{a}} "),
+            Piece::Placeholder {
+                argument: "b",
+                spec: ":?"
+            }
+        ]
+    );
+    assert!(!format::uses_placeholder("{{a}}", "a"));
+    assert!(format::uses_placeholder("{a.b:?}", "a"));
+    assert_eq!(format::named_arguments("{a} {0} {} {a:?} {b}"), ["a", "b"]);
+}
+
+#[test]
+fn substitute_only_changes_placeholders() {
+    for input in inputs(5) {
+        if !format::uses_placeholder(&input, "a") {
+            assert_eq!(format::substitute(&input, "a", "{x}"), input);
+        }
+    }
+    assert_eq!(format::substitute("{a} {a:?}", "a", "{x}"), "{{x}} {a:?}");
+}
+
+#[test]
+fn rename_keeps_specs_and_fields() {
+    for input in inputs(5) {
+        if !format::uses_placeholder(&input, "a") {
+            assert_eq!(format::rename(&input, "a", "b"), input);
+        }
+    }
+    assert_eq!(
+        format::rename("{{a}} {a} {a:?} {a.0:>3} {ab}", "a", "b"),
+        "{{a}} {b} {b:?} {b.0:>3} {ab}"
+    );
+}
+
+#[test]
+fn remove_clauses() {
+    for input in inputs(5) {
+        assert_eq!(
+            format::remove_clauses(&input, &[]).as_deref(),
+            Some(&*input)
+        );
+    }
+    assert_eq!(
+        format::remove_clauses("failed (at {line}) [{expr}]: {error}", &["expr", "line"])
+            .as_deref(),
+        Some("failed: {error}")
+    );
+    assert_eq!(format::remove_clauses("failed on {line}", &["line"]), None);
+}
+
+#[test]
+fn resolve_sections() {
+    for input in inputs(5) {
+        assert_eq!(
+            format::resolve_sections(&input, |_| false).as_deref(),
+            Ok(&*input)
+        );
+    }
+    let input = "failed{?line} (on line {line}{?expr}: `{expr}`{/expr}){/line}: {error}";
+    assert!(format::has_sections(input));
+    assert_eq!(
+        format::resolve_sections(input, |_| true).as_deref(),
+        Ok("failed (on line {line}: `{expr}`): {error}")
+    );
+    assert_eq!(
+        format::resolve_sections(input, |name| name == "line").as_deref(),
+        Ok("failed (on line {line}): {error}")
+    );
+    assert_eq!(
+        format::resolve_sections(input, |name| name == "expr").as_deref(),
+        Ok("failed: {error}")
+    );
+    assert!(!format::has_sections("{{?line}} {line}"));
+    assert_eq!(
+        format::resolve_sections("{{?line}}", |_| false).as_deref(),
+        Ok("{{?line}}")
+    );
+    assert_eq!(
+        format::resolve_sections("{?line} {line}", |_| true),
+        Err("the section `{?line}` isn't closed".to_owned())
+    );
+    assert_eq!(
+        format::resolve_sections("{?line}{?expr}{/line}{/expr}", |_| true),
+        Err("`{/line}` closes the section `{?expr}`".to_owned())
+    );
+    assert_eq!(
+        format::resolve_sections("{/line}", |_| true),
+        Err("`{/line}` doesn't close a section".to_owned())
+    );
+}
+
+#[test]
+fn resolve_sections_except() {
+    let input = "failed{?chain} (through {chain}{?line}, line {line}{/line}){/chain}: {error}";
+    assert_eq!(
+        format::resolve_sections_except(input, |_| false, &["chain"]).as_deref(),
+        Ok("failed{?chain} (through {chain}){/chain}: {error}")
+    );
+    // sections inside a section that is left out are left out with it
+    assert_eq!(
+        format::resolve_sections_except(
+            "{?line}{?chain}{chain}{/chain}{/line}",
+            |_| false,
+            &["chain"]
+        )
+        .as_deref(),
+        Ok("")
+    );
+    assert_eq!(
+        format::resolve_sections_except("{?chain}{/line}", |_| true, &["chain"]),
+        Err("`{/line}` closes the section `{?chain}`".to_owned())
+    );
+}
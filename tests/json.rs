@@ -0,0 +1,191 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use common::LOGGED;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+static FN_LINE: AtomicU32 = AtomicU32::new(0);
+static FLATTEN_LINE: AtomicU32 = AtomicU32::new(0);
+static DEFERRED_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    NotFound { path: String },
+}
+
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomError::NotFound { path } => write!(f, "\"{path}\" not found"),
+        }
+    }
+}
+
+impl Error for CustomError {}
+
+#[derive(Debug)]
+struct InnerError;
+
+#[test]
+fn json() {
+    common::init(log::LevelFilter::Trace);
+
+    err_json().unwrap_err();
+    err_json_without_info().unwrap_err();
+    ok_flatten_json().unwrap().unwrap_err();
+
+    let logged = LOGGED.lock().unwrap();
+    let records: Vec<_> = logged
+        .iter()
+        .filter(|message| !message.starts_with("Successfully ran"))
+        .map(|record| {
+            // the timestamp changes, so only its format is checked
+            let (timestamp, rest) = record
+                .strip_prefix("{\"timestamp\":\"")
+                .unwrap()
+                .split_once('"')
+                .unwrap();
+            assert_eq!(timestamp.len(), "2024-05-01T12:30:00.250Z".len());
+            assert_eq!(&timestamp[10..11], "T");
+            assert!(timestamp.ends_with('Z'));
+            rest.to_owned()
+        })
+        .collect();
+    let file = file!();
+    let err_line = ERR_LINE.load(Ordering::Relaxed);
+    let fn_line = FN_LINE.load(Ordering::Relaxed);
+    let flatten_line = FLATTEN_LINE.load(Ordering::Relaxed);
+    assert_eq!(
+        records,
+        [
+            format!(
+                r#","function":"err_json","module":"json","file":"{file}","line":{err_line},"column":5,"expr":"Err(CustomError::NotFound {{\n    path: \"a\\\\b\".to_owned(),\n}})","error_type":"json::CustomError","error":"NotFound {{ path: \"a\\\\b\" }}","error_display":"\"a\\b\" not found"}}"#
+            ),
+            format!(
+                r#","function":"err_json_without_info","module":"json","file":"{file}","line":{fn_line},"column":4,"expr":null,"error_type":"json::CustomError","error":"NotFound {{ path: \"b\" }}","error_display":"\"b\" not found"}}"#
+            ),
+            // inner errors don't come from a `?`, so they point at the function
+            format!(
+                r#","function":"ok_flatten_json","module":"json","file":"{file}","line":{flatten_line},"column":4,"expr":null,"error_type":"json::InnerError","error":"InnerError","error_display":null}}"#
+            ),
+        ]
+    );
+    drop(logged);
+
+    // the functions the error propagated through are a key of the record, so it's still valid JSON
+    err_json_deferred_outer().unwrap_err();
+    let record: serde_json::Value =
+        serde_json::from_str(LOGGED.lock().unwrap().last().unwrap()).unwrap();
+    assert_eq!(record["function"], "err_json_deferred_outer");
+    assert_eq!(record["error_type"], "json::CustomError");
+    let deferred_line = DEFERRED_LINE.load(Ordering::Relaxed);
+    assert_eq!(
+        record["chain"],
+        format!("err_json_deferred_inner (line {deferred_line})")
+    );
+
+    // inner errors are probed like the function's error, so boxed errors are looked into and generic ones use the fallbacks
+    ok_flatten_json_boxed().unwrap().unwrap_err();
+    let record: serde_json::Value =
+        serde_json::from_str(LOGGED.lock().unwrap().last().unwrap()).unwrap();
+    assert_eq!(record["function"], "ok_flatten_json_boxed");
+    // `CustomError` isn't registered, so the box is all that can be named
+    assert_eq!(
+        record["error_type"],
+        std::any::type_name::<Box<dyn Error>>()
+    );
+    assert_eq!(record["error"], "NotFound { path: \"d\" }");
+    assert_eq!(record["error_display"], "\"d\" not found");
+
+    let path = "e".to_owned();
+    ok_flatten_json_borrowed(&path).unwrap().unwrap_err();
+    let record: serde_json::Value =
+        serde_json::from_str(LOGGED.lock().unwrap().last().unwrap()).unwrap();
+    assert_eq!(record["function"], "ok_flatten_json_borrowed");
+    assert_eq!(record["error"], "BorrowedError(\"e\")");
+    assert_eq!(record["error_display"], "e");
+
+    ok_flatten_json_generic(InnerError).unwrap().unwrap_err();
+    let record: serde_json::Value =
+        serde_json::from_str(LOGGED.lock().unwrap().last().unwrap()).unwrap();
+    assert_eq!(record["function"], "ok_flatten_json_generic");
+    assert_eq!(record["error_type"], "json::InnerError");
+    assert_eq!(record["error"], serde_json::Value::Null);
+    assert_eq!(record["error_display"], serde_json::Value::Null);
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json")]
+fn err_json() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound {
+        path: "a\\b".to_owned(),
+    })?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json")]
+fn err_json_without_info() -> Result<(), CustomError> {
+    FN_LINE.store(line!() - 1, Ordering::Relaxed);
+    Err(CustomError::NotFound {
+        path: "b".to_owned(),
+    }
+    .into())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", flatten = true)]
+fn ok_flatten_json() -> Result<Result<(), InnerError>, CustomError> {
+    FLATTEN_LINE.store(line!() - 1, Ordering::Relaxed);
+    Ok(Err(InnerError))
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", defer_to_outermost = true)]
+fn err_json_deferred_inner() -> Result<(), CustomError> {
+    DEFERRED_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound {
+        path: "c".to_owned(),
+    })?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", defer_to_outermost = true)]
+fn err_json_deferred_outer() -> Result<(), CustomError> {
+    err_json_deferred_inner()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", flatten = true)]
+fn ok_flatten_json_boxed() -> Result<Result<(), Box<dyn Error>>, CustomError> {
+    Ok(Err(Box::new(CustomError::NotFound {
+        path: "d".to_owned(),
+    })))
+}
+
+#[derive(Debug)]
+struct BorrowedError<'a>(&'a str);
+
+impl fmt::Display for BorrowedError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Error for BorrowedError<'_> {}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", flatten = true)]
+fn ok_flatten_json_borrowed<'a>(
+    path: &'a str,
+) -> Result<Result<(), Box<dyn Error + 'a>>, CustomError> {
+    Ok(Err(Box::new(BorrowedError(path))))
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "json", flatten = true)]
+fn ok_flatten_json_generic<E>(error: E) -> Result<Result<(), E>, CustomError> {
+    Ok(Err(error))
+}
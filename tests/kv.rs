@@ -0,0 +1,102 @@
+#![cfg(all(feature = "kv", not(feature = "tracing")))]
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::{KEY_VALUES, LOGGED};
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+#[test]
+fn log_kv() {
+    common::init(log::LevelFilter::Trace);
+
+    ok_kv().unwrap();
+    err_kv().unwrap_err();
+    err_kv_without_info().unwrap_err();
+    ok_static_fields().unwrap();
+    err_static_fields().unwrap_err();
+
+    let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    };
+    let logged = LOGGED.lock().unwrap();
+    let records: Vec<_> = logged
+        .iter()
+        .cloned()
+        .zip(KEY_VALUES.lock().unwrap().iter().cloned())
+        .collect();
+    assert_eq!(
+        records,
+        [
+            (
+                "ok_kv ran".to_owned(),
+                pairs(&[("function", "ok_kv"), ("owner", "team")])
+            ),
+            (
+                "err_kv failed".to_owned(),
+                pairs(&[
+                    ("function", "err_kv"),
+                    ("line", &ERR_LINE.load(Ordering::Relaxed).to_string()),
+                    ("expr", "Err(CustomError::NotFound)"),
+                    ("error", "NotFound"),
+                ])
+            ),
+            (
+                "err_kv_without_info failed".to_owned(),
+                pairs(&[("function", "err_kv_without_info"), ("error", "NotFound")])
+            ),
+            (
+                "ok_static_fields ran".to_owned(),
+                pairs(&[("component", "importer"), ("shard", "3")])
+            ),
+            (
+                "err_static_fields failed".to_owned(),
+                pairs(&[("component", "importer"), ("shard", "3")])
+            ),
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(log_kv = true, owner = "team", success_message = "{function} ran")]
+fn ok_kv() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(log_kv = true, error_message = "{function} failed")]
+fn err_kv() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(log_kv = true, error_message_without_info = "{function} failed")]
+fn err_kv_without_info() -> Result<(), CustomError> {
+    Err(CustomError::NotFound.into())
+}
+
+#[wrap_match::wrap_match(
+    fields(component = "importer", shard = 3),
+    success_message = "{function} ran"
+)]
+fn ok_static_fields() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    fields(component = "importer", shard = 3),
+    error_message = "{function} failed"
+)]
+fn err_static_fields() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
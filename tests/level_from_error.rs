@@ -0,0 +1,89 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use log::Level;
+
+use common::{LEVELS, LOGGED};
+
+#[derive(Debug, wrap_match::WrapMatchLevel)]
+#[wrap_match(level = "warn")]
+enum CacheError {
+    #[wrap_match(level = "debug")]
+    Miss,
+    #[wrap_match(level = "error")]
+    Corrupt(#[allow(dead_code)] u8),
+    Full {
+        #[allow(dead_code)]
+        size: usize,
+    },
+}
+
+#[derive(Debug, wrap_match::WrapMatchLevel)]
+#[wrap_match(exhaustive = true)]
+enum QueueError {
+    #[wrap_match(level = "info")]
+    Empty,
+    #[wrap_match(level = "warn")]
+    Closed,
+}
+
+#[wrap_match::wrap_match(logger = "log", level_from_error = true, log_success = false)]
+fn from_error(error: CacheError) -> Result<(), CacheError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", level_from_error = true, log_success = false)]
+fn exhaustive(error: QueueError) -> Result<(), QueueError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", log_success = false)]
+fn without_option(error: CacheError) -> Result<(), CacheError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    level_for(Miss = "info"),
+    level_from_error = true,
+    log_success = false
+)]
+fn overridden(error: CacheError) -> Result<(), CacheError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[test]
+fn level_from_error() {
+    common::init(log::LevelFilter::Trace);
+
+    from_error(CacheError::Miss).unwrap_err();
+    from_error(CacheError::Corrupt(1)).unwrap_err();
+    from_error(CacheError::Full { size: 2 }).unwrap_err();
+    exhaustive(QueueError::Empty).unwrap_err();
+    exhaustive(QueueError::Closed).unwrap_err();
+    // without `level_from_error`, the error type's level isn't consulted
+    without_option(CacheError::Miss).unwrap_err();
+    // `level_for` wins over the error type, which is still used for the other variants
+    overridden(CacheError::Miss).unwrap_err();
+    overridden(CacheError::Full { size: 2 }).unwrap_err();
+
+    assert_eq!(
+        *LEVELS.lock().unwrap(),
+        [
+            Level::Debug,
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+            Level::Info,
+            Level::Warn,
+        ]
+    );
+    assert_eq!(LOGGED.lock().unwrap().len(), 8);
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use log::Level;
+
+use common::{LEVELS, LOGGED};
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[derive(Debug)]
+pub enum LeveledError {
+    NotFound,
+    Timeout(#[allow(dead_code)] u64),
+    Other {
+        #[allow(dead_code)]
+        code: u8,
+    },
+}
+
+#[derive(Debug)]
+pub enum DbError {
+    NotFound,
+    Disconnected,
+}
+
+impl wrap_match::WrapMatchLevel for DbError {
+    fn level(&self) -> wrap_match::Level {
+        match self {
+            DbError::NotFound => wrap_match::Level::Info,
+            DbError::Disconnected => wrap_match::Level::Error,
+        }
+    }
+}
+
+#[test]
+fn levels() {
+    common::init(log::LevelFilter::Trace);
+
+    ok_custom_levels().unwrap();
+    err_custom_levels().unwrap_err();
+    err_level_fn().unwrap_err();
+    err_level_for(LeveledError::NotFound).unwrap_err();
+    err_level_for(LeveledError::Timeout(3)).unwrap_err();
+    err_level_for(LeveledError::Other { code: 1 }).unwrap_err();
+    err_level_for_exhaustive(LeveledError::NotFound).unwrap_err();
+    err_level_for_exhaustive(LeveledError::Timeout(3)).unwrap_err();
+    err_level_for_exhaustive(LeveledError::Other { code: 1 }).unwrap_err();
+    err_level_trait(DbError::NotFound).unwrap_err();
+    err_level_trait(DbError::Disconnected).unwrap_err();
+    err_level_trait_overridden(DbError::NotFound).unwrap_err();
+    err_level_trait_overridden(DbError::Disconnected).unwrap_err();
+    err_bridge_safe().unwrap_err();
+
+    assert_eq!(
+        *LEVELS.lock().unwrap(),
+        [
+            Level::Debug,
+            Level::Warn,
+            Level::Warn,
+            Level::Warn,
+            Level::Info,
+            Level::Error,
+            Level::Debug,
+            Level::Warn,
+            Level::Trace,
+            Level::Info,
+            Level::Error,
+            Level::Debug,
+            Level::Error,
+            Level::Warn,
+        ]
+    );
+    assert_eq!(
+        LOGGED.lock().unwrap()[..2],
+        [
+            "Successfully ran ok_custom_levels",
+            "err_custom_levels failed: Error",
+        ]
+    );
+    // `bridge_safe` logs through `log` even with `tracing`, which leaves out structured fields like the owner
+    assert_eq!(
+        LOGGED.lock().unwrap().last().unwrap(),
+        "err_bridge_safe failed: Error"
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", success_level = "debug", error_level = "warn")]
+fn ok_custom_levels() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_level = "debug",
+    error_level = "warn",
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_custom_levels() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn warn_level(_error: &CustomError) -> log::Level {
+    log::Level::Warn
+}
+
+#[wrap_match::wrap_match(logger = "log", level_fn = warn_level)]
+fn err_level_fn() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    level_for(NotFound = "warn", LeveledError::Timeout = "info"),
+    log_success = false
+)]
+fn err_level_for(error: LeveledError) -> Result<(), LeveledError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    level_for(NotFound = "debug", Timeout = "warn", Other = "trace"),
+    exhaustive = true,
+    log_success = false
+)]
+fn err_level_for_exhaustive(error: LeveledError) -> Result<(), LeveledError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", level_from_error = true, log_success = false)]
+fn err_level_trait(error: DbError) -> Result<(), DbError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    level_for(NotFound = "debug"),
+    level_from_error = true,
+    log_success = false
+)]
+fn err_level_trait_overridden(error: DbError) -> Result<(), DbError> {
+    Err(error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    bridge_safe = true,
+    owner = "bridge",
+    level_fn = warn_level,
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_bridge_safe() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
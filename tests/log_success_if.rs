@@ -0,0 +1,80 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::LOGGED;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn log_success_if() {
+    common::init(log::LevelFilter::Info);
+
+    assert_eq!(migrate(0).unwrap(), 0);
+    assert_eq!(migrate(3).unwrap(), 3);
+    migrate_path(0).unwrap();
+    migrate_path(1).unwrap();
+    migrate_flatten(0).unwrap().unwrap();
+    migrate_flatten(2).unwrap().unwrap();
+    migrate_err().unwrap_err();
+
+    let err = format!(
+        "An error occurred when running migrate_err (caused by `Err(CustomError::Error)` on line {}): Error",
+        ERR_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "migrated 3 rows",
+            "migrated 1 rows with a path",
+            "migrated 2 rows",
+            err.as_str(),
+        ]
+    );
+}
+
+fn any_rows(rows: &u64) -> bool {
+    *rows > 0
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "migrated {ok} rows",
+    log_success_if = |rows| *rows > 0
+)]
+fn migrate(rows: u64) -> Result<u64, CustomError> {
+    Ok(rows)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "migrated {ok} rows with a path",
+    log_success_if = any_rows
+)]
+fn migrate_path(rows: u64) -> Result<u64, CustomError> {
+    Ok(rows)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    flatten = true,
+    success_message = "migrated {ok} rows",
+    log_success_if = |rows| *rows > 0
+)]
+fn migrate_flatten(rows: u64) -> Result<Result<u64, CustomError>, CustomError> {
+    Ok(Ok(rows))
+}
+
+#[wrap_match::wrap_match(logger = "log", log_success_if = |_| true)]
+fn migrate_err() -> Result<u64, CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(0)
+}
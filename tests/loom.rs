@@ -0,0 +1,74 @@
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom`
+#![cfg(loom)]
+
+use loom::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use wrap_match::runtime::{Counter, Flag};
+
+#[test]
+fn counter_gives_every_increment_its_own_count() {
+    loom::model(|| {
+        let counter = Arc::new(Counter::new());
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || counter.increment())
+            })
+            .collect();
+        let mut counts: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .chain([counter.increment()])
+            .collect();
+        counts.sort_unstable();
+        assert_eq!(counts, [1, 2, 3]);
+        assert_eq!(counter.get(), 3);
+    });
+}
+
+#[test]
+fn flag_is_set_by_exactly_one_thread() {
+    loom::model(|| {
+        let flag = Arc::new(Flag::new());
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let flag = flag.clone();
+                thread::spawn(move || flag.set())
+            })
+            .collect();
+        let first = flag.set();
+        let set = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .chain([first])
+            .filter(|&first| first)
+            .count();
+        assert_eq!(set, 1);
+        assert!(flag.is_set());
+    });
+}
+
+#[test]
+fn flag_publishes_writes_made_before_it_was_set() {
+    loom::model(|| {
+        let flag = Arc::new(Flag::new());
+        let data = Arc::new(AtomicUsize::new(0));
+        let writer = {
+            let flag = flag.clone();
+            let data = data.clone();
+            thread::spawn(move || {
+                data.store(1, Ordering::Relaxed);
+                flag.set();
+            })
+        };
+        if flag.is_set() {
+            assert_eq!(data.load(Ordering::Relaxed), 1);
+        }
+        writer.join().unwrap();
+    });
+}
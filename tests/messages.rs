@@ -0,0 +1,492 @@
+//! The format parameters and the options that change messages
+
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    error::Error,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use common::LOGGED;
+
+static CALLER_LINE: AtomicU32 = AtomicU32::new(0);
+static LOCATION_LINE: AtomicU32 = AtomicU32::new(0);
+static TRACK_CALLER_LINE: AtomicU32 = AtomicU32::new(0);
+static PREFIX_LINE: AtomicU32 = AtomicU32::new(0);
+static SECTIONS_LINE: AtomicU32 = AtomicU32::new(0);
+static DERIVED_LINE: AtomicU32 = AtomicU32::new(0);
+static COLLIDING_LINE: AtomicU32 = AtomicU32::new(0);
+static DUAL_OUTPUT_LINE: AtomicU32 = AtomicU32::new(0);
+static WITHOUT_INFO_LINE: AtomicU32 = AtomicU32::new(0);
+#[cfg(feature = "catalog")]
+static CATALOG_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, wrap_match::VariantName)]
+pub enum CustomError {
+    Error,
+}
+
+impl std::fmt::Display for CustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomError")
+    }
+}
+
+impl Error for CustomError {}
+
+#[derive(Debug)]
+pub struct SourcedError(CustomError);
+
+impl std::fmt::Display for SourcedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SourcedError")
+    }
+}
+
+impl Error for SourcedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[test]
+fn messages() {
+    common::init(log::LevelFilter::Info);
+
+    ok_arg(1).unwrap();
+    ok_silent().unwrap();
+    err_args(1, "two").unwrap_err();
+    err_arg_fields(&Request {
+        user_id: 42,
+        path: ("/", 1),
+    })
+    .unwrap_err();
+    err_owner().unwrap_err();
+    Worker { id: 3 }.err_self_fields().unwrap_err();
+    err_error_type().unwrap_err();
+    err_error_type_boxed().unwrap_err();
+    wrap_match::register_error_type::<CustomError>();
+    err_error_type_boxed().unwrap_err();
+    err_variant().unwrap_err();
+    err_sources().unwrap_err();
+    err_root_cause().unwrap_err();
+    std::thread::Builder::new()
+        .name("worker-1".to_owned())
+        .spawn(|| err_thread().unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+    CALLER_LINE.store(line!() + 1, Ordering::Relaxed);
+    err_caller().unwrap_err();
+    err_location().unwrap_err();
+    err_location_without_info().unwrap_err();
+    err_colliding_args("x", 1, 2).unwrap_err();
+    ok_concat_message().unwrap();
+    err_included_message().unwrap_err();
+    TRACK_CALLER_LINE.store(line!() + 1, Ordering::Relaxed);
+    err_track_caller().unwrap_err();
+    err_derived_without_info(false).unwrap_err();
+    err_derived_without_info(true).unwrap_err();
+    err_sections(true).unwrap_err();
+    err_sections(false).unwrap_err();
+    ok_locale().unwrap();
+    err_locale().unwrap_err();
+    ok_flatten(false).unwrap().unwrap();
+    ok_flatten(true).unwrap().unwrap_err();
+    ok_prefix_suffix().unwrap();
+    err_prefix_suffix().unwrap_err();
+    for _ in 0..3 {
+        err_cold_start().unwrap_err();
+    }
+    err_redact_literals().unwrap_err();
+    err_hashed_expr().unwrap_err();
+    err_dual_output().unwrap_err();
+    err_dual_output_without_info().unwrap_err();
+    #[cfg(feature = "catalog")]
+    {
+        err_catalog().unwrap_err();
+        ok_catalog_table().unwrap();
+    }
+    err_backtrace().unwrap_err();
+
+    let file = file!();
+    let caller_line = CALLER_LINE.load(Ordering::Relaxed);
+    let location_line = LOCATION_LINE.load(Ordering::Relaxed);
+    let track_caller_line = TRACK_CALLER_LINE.load(Ordering::Relaxed);
+    let prefix_line = PREFIX_LINE.load(Ordering::Relaxed);
+    let sections_line = SECTIONS_LINE.load(Ordering::Relaxed);
+    let derived_line = DERIVED_LINE.load(Ordering::Relaxed);
+    let colliding_line = COLLIDING_LINE.load(Ordering::Relaxed);
+    let dual_output_line = DUAL_OUTPUT_LINE.load(Ordering::Relaxed);
+    let without_info_line = WITHOUT_INFO_LINE.load(Ordering::Relaxed);
+    #[cfg_attr(not(feature = "catalog"), allow(unused_mut))]
+    let mut expected = vec![
+        "success 1".to_owned(),
+        "failed with args (_a: 1, _b: \"two\"): Error".to_owned(),
+        "request from 42 to \"/\" failed: Error".to_owned(),
+        "[payments-team] err_owner failed: Error".to_owned(),
+        "worker 3 failed: Error".to_owned(),
+        format!("{}: Error", std::any::type_name::<CustomError>()),
+        // the boxed error's type is only known once it's registered
+        format!("{}: Error", std::any::type_name::<Box<dyn Error>>()),
+        format!("{}: Error", std::any::type_name::<CustomError>()),
+        "failed with Error".to_owned(),
+        "SourcedError (caused by: CustomError)".to_owned(),
+        "SourcedError (root cause: CustomError)".to_owned(),
+        "err_thread failed on worker-1 (\"worker-1\")".to_owned(),
+        format!("err_caller (called from {file}:{caller_line}:5) failed"),
+        format!("err_location failed at {file}:{location_line}:5"),
+        // errors that weren't caused by a try expression are located at the function's name
+        format!("err_location_without_info failed at \"{file}:{without_info_line}:4\""),
+        // the arguments that have the names of format parameters are still available with `args.`
+        format!("line {colliding_line} (x), error ParseIntError {{ kind: InvalidDigit }} (1), e 2"),
+        "[db] Successfully ran ok_concat_message 1!".to_owned(),
+        "err_included_message failed to sync: Error".to_owned(),
+        format!("err_track_caller was called from {file}:{track_caller_line}:5"),
+        // `derive_without_info` removes the clauses with the expression and the line
+        "err_derived_without_info failed for false: Error".to_owned(),
+        format!("err_derived_without_info failed (caused by `Err(CustomError::Error)` (on line {derived_line})) for true: Error"),
+        format!("err_sections failed (caused by `Err(CustomError::Error)` on line {sections_line}): Error"),
+        "err_sections failed: Error".to_owned(),
+        "ok_locale erfolgreich ausgeführt".to_owned(),
+        "Une erreur s'est produite lors de l'exécution de err_locale : Error".to_owned(),
+        "ok_flatten returned 3".to_owned(),
+        "ok_flatten a été exécutée, mais a renvoyé une erreur interne : Error".to_owned(),
+        format!(
+            "[ingest] Successfully ran ok_prefix_suffix ({})",
+            std::thread::current().name().unwrap()
+        ),
+        format!("[ingest] err_prefix_suffix failed on line {prefix_line}: Error"),
+        "err_cold_start failed on call 1 (first call: true)".to_owned(),
+        "err_cold_start failed on call 2 (first call: true)".to_owned(),
+        "err_cold_start failed on call 3 (first call: false)".to_owned(),
+        "err_redact_literals failed: `login(\"***\", \"***\", -_)`".to_owned(),
+        // the hash only changes if the function's name or the expression does
+        "err_hashed_expr failed: `7a78b2cd`".to_owned(),
+        "err_dual_output failed: Error".to_owned(),
+        format!("function=err_dual_output line={dual_output_line} expr=\"Err(CustomError::Error)\" error=Error"),
+        "err_dual_output_without_info failed: Error".to_owned(),
+        "function=err_dual_output_without_info error=Error".to_owned(),
+    ];
+    #[cfg(feature = "catalog")]
+    expected.extend([
+        format!(
+            "err_catalog failed to sync the database (caused by `Err(CustomError::Error)` on line {}): Error",
+            CATALOG_LINE.load(Ordering::Relaxed)
+        ),
+        "ok_catalog_table migrated the database".to_owned(),
+    ]);
+    let mut logged = LOGGED.lock().unwrap();
+    // the frames of the backtrace depend on the platform and the build
+    let backtrace = logged.pop().unwrap();
+    assert!(
+        backtrace.starts_with("Error at "),
+        "{backtrace:?} should start with the error"
+    );
+    assert_eq!(*logged, expected);
+}
+
+#[wrap_match::wrap_match(logger = "log", success_message = "success {_a}")]
+fn ok_arg(_a: i64) -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", log_success = false)]
+fn ok_silent() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "failed with args {args:?}: {error:?}",
+    error_message_without_info = "failed with args {args:#?}"
+)]
+fn err_args(_a: i64, _b: &str) -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+pub struct Request {
+    user_id: u64,
+    path: (&'static str, u8),
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "request from {_request.user_id} to {_request.path.0:?} failed: {error:?}"
+)]
+fn err_arg_fields(_request: &Request) -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    owner = "payments-team",
+    error_message = "[{owner}] {function} failed: {error:?}"
+)]
+fn err_owner() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+struct Worker {
+    id: u32,
+}
+
+impl Worker {
+    #[wrap_match::wrap_match(logger = "log", error_message = "worker {self.id} failed: {error:?}")]
+    fn err_self_fields(&self) -> Result<(), CustomError> {
+        Err(CustomError::Error)?;
+        Ok(())
+    }
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{error_type}: {error:?}")]
+fn err_error_type() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{error_type}: {error:?}")]
+fn err_error_type_boxed() -> Result<(), Box<dyn Error>> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "failed with {variant}")]
+fn err_variant() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{error} ({sources})")]
+fn err_sources() -> Result<(), SourcedError> {
+    Err(SourcedError(CustomError::Error))?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{error} (root cause: {root_cause})")]
+fn err_root_cause() -> Result<(), SourcedError> {
+    Err(SourcedError(CustomError::Error))?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function} failed on {thread} ({thread:?})"
+)]
+fn err_thread() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function} (called from {caller}) failed"
+)]
+fn err_caller() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function} failed at {location}")]
+fn err_location() -> Result<(), CustomError> {
+    LOCATION_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message_without_info = "{function} failed at {location:?}"
+)]
+fn err_location_without_info() -> Result<(), CustomError> {
+    // the line of the function's name, above this comment
+    WITHOUT_INFO_LINE.store(line!() - 2, Ordering::Relaxed);
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "line {line} ({args.line}), error {error:?} ({args.error}), e {e}"
+)]
+fn err_colliding_args(line: &str, error: u8, e: u8) -> Result<(), std::num::ParseIntError> {
+    let _ = (error, e);
+    COLLIDING_LINE.store(line!() + 1, Ordering::Relaxed);
+    line.parse::<u8>()?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", success_message = concat!("[db] ", "Successfully ran {function} ", 1, '!'))]
+fn ok_concat_message() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = include_str!("messages/sync-failed.txt"))]
+fn err_included_message() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message_without_info = "{function} was called from {error}"
+)]
+#[track_caller]
+fn err_track_caller() -> Result<(), String> {
+    // this should be where `err_track_caller` was called, not somewhere in the generated code
+    Err(std::panic::Location::caller().to_string().into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function} failed (caused by `{expr}` (on line {line})) for {fail:?}: {error:?}",
+    derive_without_info = true
+)]
+fn err_derived_without_info(fail: bool) -> Result<(), CustomError> {
+    if !fail {
+        return Err(CustomError::Error.into());
+    }
+    DERIVED_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function} failed{?line} (caused by `{expr}` on line {line}){/line}: {error:?}"
+)]
+fn err_sections(with_info: bool) -> Result<(), CustomError> {
+    if with_info {
+        SECTIONS_LINE.store(line!() + 1, Ordering::Relaxed);
+        Err(CustomError::Error)?;
+    }
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(logger = "log", locale = "de")]
+fn ok_locale() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", locale = "fr")]
+fn err_locale() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    flatten = true,
+    locale = "fr",
+    success_message = "{function} returned {ok}"
+)]
+fn ok_flatten(fail: bool) -> Result<Result<u8, CustomError>, CustomError> {
+    let handle = std::thread::spawn(move || if fail { Err(CustomError::Error) } else { Ok(3) });
+    let result = handle.join().map_err(|_| CustomError::Error)?;
+    Ok(result)
+}
+
+#[wrap_match::wrap_match(logger = "log", message_prefix = "[ingest] ", message_suffix = concat!(" (", "{thread}", ")"))]
+fn ok_prefix_suffix() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    message_prefix = "[ingest] ",
+    error_message = "{function} failed on line {line}: {error:?}"
+)]
+fn err_prefix_suffix() -> Result<(), CustomError> {
+    PREFIX_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    cold_start = 2,
+    error_message = "{function} failed on call {invocation} (first call: {first_call})"
+)]
+fn err_cold_start() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn login(_user: &str, _password: &str, _attempt: i8) -> Result<(), CustomError> {
+    Err(CustomError::Error)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    redact_literals = true,
+    error_message = "{function} failed: `{expr}`"
+)]
+fn err_redact_literals() -> Result<(), CustomError> {
+    login("user", r"hunter2", -1)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    expr_privacy = "hash",
+    error_message = "{function} failed: `{expr}`"
+)]
+fn err_hashed_expr() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    dual_output = true,
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_dual_output() -> Result<(), CustomError> {
+    DUAL_OUTPUT_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    dual_output = true,
+    error_message_without_info = "{function} failed: {error:?}"
+)]
+fn err_dual_output_without_info() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    backtrace = true,
+    error_message = "{error:?} at {backtrace}"
+)]
+fn err_backtrace() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[cfg(feature = "catalog")]
+#[wrap_match::wrap_match(
+    logger = "log",
+    message_key = "db.sync.failed",
+    catalog = "tests/messages/catalog.toml"
+)]
+fn err_catalog() -> Result<(), CustomError> {
+    CATALOG_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[cfg(feature = "catalog")]
+#[wrap_match::wrap_match(
+    logger = "log",
+    message_key = "db.migrate",
+    catalog = "tests/messages/catalog.toml"
+)]
+fn ok_catalog_table() -> Result<(), CustomError> {
+    Ok(())
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "metrics")]
+
+use metrics::{Key, Label};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+    Panicked,
+    #[cfg(feature = "tokio")]
+    TimedOut,
+}
+
+impl From<wrap_match::Panicked> for CustomError {
+    fn from(_: wrap_match::Panicked) -> Self {
+        Self::Panicked
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<wrap_match::TimedOut> for CustomError {
+    fn from(_: wrap_match::TimedOut) -> Self {
+        Self::TimedOut
+    }
+}
+
+/// The counters that were recorded, sorted by name and labels
+fn counters(snapshotter: &Snapshotter) -> Vec<(Key, u64)> {
+    let mut counters: Vec<_> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let DebugValue::Counter(value) = value else {
+                panic!("expected a counter, got {value:?}");
+            };
+            (key.key().clone(), value)
+        })
+        .collect();
+    counters.sort_by(|(a, _), (b, _)| a.name().cmp(b.name()).then(a.labels().cmp(b.labels())));
+    counters
+}
+
+#[derive(Debug)]
+struct InnerError;
+
+#[test]
+fn metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        ok_metrics().unwrap();
+        ok_metrics().unwrap();
+        err_metrics().unwrap_err();
+        ok_flatten_metrics().unwrap().unwrap_err();
+        err_without_metrics().unwrap_err();
+        // caught panics are counted like the function's own errors
+        std::panic::set_hook(Box::new(|_| {}));
+        err_panic_metrics().unwrap_err();
+        let _ = std::panic::take_hook();
+    });
+
+    assert_eq!(
+        counters(&snapshotter),
+        [
+            (
+                Key::from_parts(
+                    "wrap_match_error_total",
+                    vec![
+                        Label::new("function", "err_metrics"),
+                        Label::new("error_type", "metrics::CustomError"),
+                    ]
+                ),
+                1
+            ),
+            (
+                Key::from_parts(
+                    "wrap_match_error_total",
+                    vec![
+                        Label::new("function", "err_panic_metrics"),
+                        Label::new("error_type", "metrics::CustomError"),
+                    ]
+                ),
+                1
+            ),
+            (
+                Key::from_parts(
+                    "wrap_match_error_total",
+                    vec![
+                        Label::new("function", "ok_flatten_metrics"),
+                        Label::new("error_type", "metrics::InnerError"),
+                    ]
+                ),
+                1
+            ),
+            (
+                Key::from_parts(
+                    "wrap_match_success_total",
+                    vec![Label::new("function", "ok_metrics")]
+                ),
+                2
+            ),
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(metrics = true)]
+fn ok_metrics() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(metrics = true)]
+fn err_metrics() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(metrics = true, flatten = true)]
+fn ok_flatten_metrics() -> Result<Result<(), InnerError>, CustomError> {
+    Ok(Err(InnerError))
+}
+
+#[wrap_match::wrap_match]
+fn err_without_metrics() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(metrics = true, catch_panics = true)]
+fn err_panic_metrics() -> Result<(), CustomError> {
+    panic!("no config");
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn timeout_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    metrics::with_local_recorder(&recorder, || {
+        runtime.block_on(err_timeout_metrics()).unwrap_err();
+    });
+
+    // timeouts are counted like the function's own errors
+    assert_eq!(
+        counters(&snapshotter),
+        [(
+            Key::from_parts(
+                "wrap_match_error_total",
+                vec![
+                    Label::new("function", "err_timeout_metrics"),
+                    Label::new("error_type", "metrics::CustomError"),
+                ]
+            ),
+            1
+        )]
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[wrap_match::wrap_match(metrics = true, timeout = "10ms")]
+async fn err_timeout_metrics() -> Result<(), CustomError> {
+    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// without a logging feature, the messages of these functions go to stderr, which is fine for these tests
+#![cfg_attr(not(any(feature = "log", feature = "tracing")), allow(deprecated))]
+
+use mockall::automock;
+
+#[derive(Debug, PartialEq)]
+enum DbError {
+    NotFound,
+}
+
+#[automock]
+trait Store {
+    fn get(&self, id: u32) -> Result<String, DbError>;
+}
+
+struct Database;
+
+impl Store for Database {
+    // an inner method next to `get` wouldn't be a member of the trait
+    #[wrap_match::wrap_match(inline = true)]
+    fn get(&self, id: u32) -> Result<String, DbError> {
+        if id > 10 {
+            Err(DbError::NotFound)?;
+        }
+        Ok(format!("row {id}"))
+    }
+}
+
+fn describe(store: &dyn Store) -> String {
+    store.get(1).unwrap_or_else(|e| format!("{e:?}"))
+}
+
+#[test]
+fn mockall() {
+    assert_eq!(describe(&Database), "row 1");
+    assert_eq!(Database.get(11), Err(DbError::NotFound));
+
+    let mut mock = MockStore::new();
+    mock.expect_get().returning(|_| Err(DbError::NotFound));
+    assert_eq!(describe(&mock), "NotFound");
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wrap_match::Panicked;
+
+use common::LOGGED;
+
+static COUNT_LINE: AtomicU32 = AtomicU32::new(0);
+static ASYNC_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+impl From<Panicked> for CustomError {
+    fn from(_: Panicked) -> Self {
+        Self::Error
+    }
+}
+
+#[test]
+fn ok_or_default() {
+    common::init(log::LevelFilter::Info);
+
+    assert_eq!(ok_count(), 3);
+    assert!(err_count().is_empty());
+    assert_eq!(pollster::block_on(err_async()), "");
+    // the panic is expected, so it doesn't need to be printed
+    std::panic::set_hook(Box::new(|_| {}));
+    assert_eq!(err_panic(), None);
+    let _ = std::panic::take_hook();
+
+    let count = format!(
+        "An error occurred when running err_count (caused by `Err(CustomError::Error)` on line {}): Error",
+        COUNT_LINE.load(Ordering::Relaxed)
+    );
+    let async_ = format!(
+        "An error occurred when running err_async (caused by `Err(CustomError::Error)` on line {}): Error",
+        ASYNC_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_count",
+            count.as_str(),
+            async_.as_str(),
+            "err_panic panicked: oh no",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_default = true)]
+fn ok_count() -> Result<u32, CustomError> {
+    Ok(3)
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_default = true)]
+fn err_count() -> Result<Vec<u32>, CustomError> {
+    COUNT_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(vec![3])
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_default = true)]
+async fn err_async() -> Result<String, CustomError> {
+    ASYNC_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok("done".to_owned())
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_default = true, catch_panics = true)]
+fn err_panic() -> Result<Option<u8>, CustomError> {
+    panic!("oh no");
+}
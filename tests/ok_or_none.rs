@@ -0,0 +1,54 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::LOGGED;
+
+static PARSE_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn ok_or_none() {
+    common::init(log::LevelFilter::Info);
+
+    assert_eq!(ok_parse("42"), Some(42));
+    assert_eq!(ok_parse("nope"), None);
+    assert_eq!(err_unit(), None);
+    assert_eq!(pollster::block_on(ok_async()), Some("done".to_owned()));
+
+    let parse = format!(
+        "An error occurred when running ok_parse (caused by `input.parse()` on line {}): ParseIntError {{ kind: InvalidDigit }}",
+        PARSE_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_parse",
+            parse.as_str(),
+            "An error occurred when running err_unit: Error",
+            "Successfully ran ok_async",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_none = true)]
+fn ok_parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    PARSE_LINE.store(line!() + 1, Ordering::Relaxed);
+    Ok(input.parse()?)
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_none = true)]
+fn err_unit() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(logger = "log", ok_or_none = true)]
+async fn ok_async() -> Result<String, CustomError> {
+    Ok("done".to_owned())
+}
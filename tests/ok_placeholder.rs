@@ -0,0 +1,65 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use common::LOGGED;
+
+pub struct Commit {
+    id: u64,
+    rows: (usize, usize),
+}
+
+#[test]
+fn ok_placeholder() {
+    common::init(log::LevelFilter::Info);
+
+    ok_value().unwrap();
+    ok_fields().unwrap();
+    ok_return().unwrap();
+    ok_return_fields().unwrap();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "loaded 3 records (3)",
+            "committed txn 7 with 3 rows ((3, 4) total)",
+            "loaded 3 records (3)",
+            "committed txn 7 with 3 rows ((3, 4) total)",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", success_message = "loaded {ok} records ({ok:?})")]
+fn ok_value() -> Result<usize, ()> {
+    Ok(3)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "committed txn {ok.id} with {ok.rows.0} rows ({ok.rows:?} total)"
+)]
+fn ok_fields() -> Result<Commit, ()> {
+    Ok(Commit {
+        id: 7,
+        rows: (3, 4),
+    })
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "loaded {return} records ({return:?})"
+)]
+fn ok_return() -> Result<usize, ()> {
+    Ok(3)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "committed txn {return.id} with {return.rows.0} rows ({return.rows:?} total)"
+)]
+fn ok_return_fields() -> Result<Commit, ()> {
+    Ok(Commit {
+        id: 7,
+        rows: (3, 4),
+    })
+}
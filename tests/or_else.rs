@@ -0,0 +1,93 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wrap_match::Panicked;
+
+use common::LOGGED;
+
+static VALUE_LINE: AtomicU32 = AtomicU32::new(0);
+static CLOSURE_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+    Panicked(Panicked),
+}
+
+impl From<Panicked> for CustomError {
+    fn from(panicked: Panicked) -> Self {
+        Self::Panicked(panicked)
+    }
+}
+
+#[test]
+fn or_else() {
+    common::init(log::LevelFilter::Info);
+
+    assert_eq!(ok_value().unwrap(), 1);
+    assert_eq!(err_value().unwrap(), 0);
+    assert_eq!(err_closure().unwrap(), "unavailable (Error)");
+    assert_eq!(err_closure_without_error().unwrap(), [0]);
+    // the panic is expected, so it doesn't need to be printed
+    std::panic::set_hook(Box::new(|_| {}));
+    assert_eq!(err_panic().unwrap(), "panicked: oh no");
+    let _ = std::panic::take_hook();
+
+    let value = format!(
+        "An error occurred when running err_value (caused by `Err(CustomError::Error)` on line {}): Error",
+        VALUE_LINE.load(Ordering::Relaxed)
+    );
+    let closure = format!(
+        "An error occurred when running err_closure (caused by `Err(CustomError::Error)` on line {}): Error",
+        CLOSURE_LINE.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_value",
+            value.as_str(),
+            closure.as_str(),
+            "An error occurred when running err_closure_without_error: Error",
+            "err_panic panicked: oh no",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", or_else = 0)]
+fn ok_value() -> Result<u8, CustomError> {
+    Ok(1)
+}
+
+#[wrap_match::wrap_match(logger = "log", or_else = 0)]
+fn err_value() -> Result<u8, CustomError> {
+    VALUE_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(1)
+}
+
+#[wrap_match::wrap_match(logger = "log", or_else = |error| format!("unavailable ({error:?})"))]
+fn err_closure() -> Result<String, CustomError> {
+    CLOSURE_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok("available".to_owned())
+}
+
+#[wrap_match::wrap_match(logger = "log", or_else = || vec![0])]
+fn err_closure_without_error() -> Result<Vec<u8>, CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    catch_panics = true,
+    or_else = |error| match error {
+        CustomError::Panicked(panicked) => format!("panicked: {}", panicked.message()),
+        CustomError::Error => "error".to_owned(),
+    }
+)]
+fn err_panic() -> Result<String, CustomError> {
+    panic!("oh no");
+}
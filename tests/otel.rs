@@ -0,0 +1,67 @@
+#![cfg(feature = "otel")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use opentelemetry::{
+    trace::{Status, Tracer, TracerProvider},
+    KeyValue,
+};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+#[test]
+fn otel() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let tracer = provider.tracer("wrap-match");
+
+    tracer.in_span("request", |_| err_otel().unwrap_err());
+    tracer.in_span("ok", |_| ok_otel().unwrap());
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let [request, ok] = &spans[..] else {
+        panic!("expected two spans, got {spans:?}");
+    };
+
+    assert_eq!(request.status, Status::error("NotFound"));
+    let [event] = &request.events.events[..] else {
+        panic!("expected one event, got {:?}", request.events);
+    };
+    assert_eq!(event.name, "exception");
+    assert_eq!(
+        event.attributes,
+        [
+            KeyValue::new("exception.type", "otel::CustomError"),
+            KeyValue::new("code.function.name", "err_otel"),
+            KeyValue::new("exception.message", "NotFound"),
+            KeyValue::new(
+                "code.line.number",
+                i64::from(ERR_LINE.load(Ordering::Relaxed))
+            ),
+            KeyValue::new("wrap_match.expr", "Err(CustomError::NotFound)"),
+        ]
+    );
+
+    assert_eq!(ok.status, Status::Unset);
+    assert!(ok.events.is_empty());
+}
+
+#[wrap_match::wrap_match]
+fn err_otel() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match]
+fn ok_otel() -> Result<(), CustomError> {
+    Ok(())
+}
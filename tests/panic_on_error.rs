@@ -0,0 +1,100 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    panic,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use common::LOGGED;
+
+static WITH_INFO_LINE: AtomicU32 = AtomicU32::new(0);
+static DISREGARDED_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+/// Runs a function that should panic, returning its panic message
+fn panic_message(f: impl FnOnce() + panic::UnwindSafe) -> String {
+    let payload = panic::catch_unwind(f).unwrap_err();
+    payload
+        .downcast::<String>()
+        .map(|message| *message)
+        .unwrap()
+}
+
+#[test]
+fn panic_on_error() {
+    common::init(log::LevelFilter::Info);
+    // the panics are expected, so they don't need to be printed
+    panic::set_hook(Box::new(|_| {}));
+
+    assert_eq!(ok_no_error().unwrap(), 1);
+    let with_info = panic_message(|| {
+        err_with_info(7).ok();
+    });
+    let without_info = panic_message(|| {
+        err_without_info().ok();
+    });
+    let disregarded = panic_message(err_disregarded);
+
+    let _ = panic::take_hook();
+    assert_eq!(
+        with_info,
+        format!(
+            "user 7 is invalid: Error (caused by `Err(CustomError::Error)` on line {})",
+            WITH_INFO_LINE.load(Ordering::Relaxed)
+        )
+    );
+    assert_eq!(
+        without_info,
+        "An error occurred when running err_without_info: Error"
+    );
+    assert_eq!(
+        disregarded,
+        format!(
+            "An error occurred when running err_disregarded (caused by `Err(CustomError::Error)` on line {}): Error",
+            DISREGARDED_LINE.load(Ordering::Relaxed)
+        )
+    );
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_no_error",
+            with_info.as_str(),
+            without_info.as_str(),
+            disregarded.as_str(),
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", panic_on_error = true)]
+fn ok_no_error() -> Result<u8, CustomError> {
+    Ok(1)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    panic_on_error = true,
+    error_message = "user {user_id} is invalid: {error:?} (caused by `{expr}` on line {line})"
+)]
+fn err_with_info(user_id: u64) -> Result<u64, CustomError> {
+    WITH_INFO_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(user_id)
+}
+
+#[wrap_match::wrap_match(logger = "log", panic_on_error = true)]
+fn err_without_info() -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(logger = "log", panic_on_error = true, disregard_result = true)]
+fn err_disregarded() -> Result<(), CustomError> {
+    DISREGARDED_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::{env, process::Command};
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+/// Set when the test runs itself again, so that what it writes to stderr can be checked
+const CHILD: &str = "WRAP_MATCH_PLAIN_CHILD";
+
+#[test]
+fn plain_logger() {
+    if env::var_os(CHILD).is_some() {
+        ok_plain_logger().unwrap();
+        err_plain_logger(2).unwrap_err();
+        return;
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "plain_logger", "--nocapture"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Successfully ran ok_plain_logger\n"));
+    // the duration and the line aren't checked, since they can't be passed back from the child process
+    assert!(stderr.lines().any(|line| {
+        line.starts_with("plain err_plain_logger failed with 2: Error outcome=failure duration=")
+            && line.contains(" error=Error line=")
+            && line.ends_with(" expr=\"Err(CustomError::Error)\" args=(_id: 2)")
+    }));
+}
+
+#[wrap_match::wrap_match(logger = "plain")]
+fn ok_plain_logger() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "plain",
+    error_level = "warn",
+    wide_event = true,
+    error_message = "plain {function} failed with {_id}: {error:?}"
+)]
+fn err_plain_logger(_id: u8) -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
+
+use wrap_match::{ErrorInfo, Reporter, SuccessInfo};
+
+use common::LOGGED;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+static REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+#[cfg(feature = "serde")]
+static SERIALIZED: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+
+struct Collector;
+
+impl Reporter for Collector {
+    fn on_success(&self, info: &SuccessInfo) {
+        REPORTS
+            .lock()
+            .unwrap()
+            .push(format!("{} in {} succeeded", info.function, info.module));
+    }
+
+    fn on_error(&self, info: &ErrorInfo<'_>) {
+        REPORTS.lock().unwrap().push(format!(
+            "{} failed with {}: {:?} (error: {:?}, at {:?}:{:?}:{:?}, expr {:?})",
+            info.function,
+            info.error_type,
+            info.debug,
+            info.error.map(ToString::to_string),
+            info.file,
+            info.line,
+            info.column,
+            info.expr,
+        ));
+        #[cfg(feature = "serde")]
+        SERIALIZED
+            .lock()
+            .unwrap()
+            .push(serde_json::to_value(info).unwrap());
+    }
+}
+
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+impl Error for CustomError {}
+
+#[derive(Debug)]
+struct InnerError;
+
+#[test]
+fn reporter() {
+    common::init(log::LevelFilter::Trace);
+    wrap_match::set_reporter(&Collector);
+
+    ok_reported().unwrap();
+    err_reported().unwrap_err();
+    err_reported_instead().unwrap_err();
+    ok_flatten_reported().unwrap().unwrap_err();
+    // caught panics go through the same pipeline as errors, so they're reported too
+    std::panic::set_hook(Box::new(|_| {}));
+    err_panic_reported().unwrap_err();
+    let _ = std::panic::take_hook();
+    err_not_reported().unwrap_err();
+
+    wrap_match::set_reporter(&());
+    err_reported().unwrap_err();
+
+    let line = ERR_LINE.load(Ordering::Relaxed);
+    assert_eq!(
+        *REPORTS.lock().unwrap(),
+        [
+            "ok_reported in reporter succeeded".to_owned(),
+            format!("err_reported failed with reporter::CustomError: Some(\"NotFound\") (error: Some(\"not found\"), at Some(\"tests/reporter.rs\"):Some({line}):Some(5), expr Some(\"Err(CustomError::NotFound)\"))"),
+            "err_reported_instead failed with reporter::CustomError: Some(\"NotFound\") (error: Some(\"not found\"), at None:None:None, expr None)".to_owned(),
+            "ok_flatten_reported failed with reporter::InnerError: Some(\"InnerError\") (error: None, at None:None:None, expr None)".to_owned(),
+            "err_panic_reported failed with alloc::boxed::Box<dyn core::error::Error + core::marker::Send + core::marker::Sync>: Some(\"Panicked { function: \\\"err_panic_reported\\\", message: \\\"no config\\\" }\") (error: Some(\"err_panic_reported panicked: no config\"), at None:None:None, expr None)".to_owned(),
+        ]
+    );
+    #[cfg(feature = "serde")]
+    {
+        let serialized = SERIALIZED.lock().unwrap();
+        assert_eq!(
+            serialized[0],
+            serde_json::json!({
+                "function": "err_reported",
+                "module": "reporter",
+                "error_type": "reporter::CustomError",
+                "error": "not found",
+                "debug": "NotFound",
+                "file": "tests/reporter.rs",
+                "line": line,
+                "column": 5,
+                "expr": "Err(CustomError::NotFound)",
+                "duration": serialized[0]["duration"],
+            })
+        );
+        assert_eq!(serialized[2]["error"], serde_json::Value::Null);
+    }
+    let logged = LOGGED.lock().unwrap();
+    assert!(logged
+        .iter()
+        .any(|message| message.starts_with("An error occurred when running err_reported ")));
+    assert!(!logged
+        .iter()
+        .any(|message| message.contains("err_reported_instead")));
+    assert!(logged
+        .iter()
+        .any(|message| message == "err_panic_reported panicked: no config"));
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "also")]
+fn ok_reported() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "also")]
+fn err_reported() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "instead")]
+fn err_reported_instead() -> Result<(), CustomError> {
+    Err(CustomError::NotFound.into())
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "also", flatten = true)]
+fn ok_flatten_reported() -> Result<Result<(), InnerError>, CustomError> {
+    Ok(Err(InnerError))
+}
+
+#[wrap_match::wrap_match(logger = "log")]
+fn err_not_reported() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", reporter = "also", catch_panics = true)]
+fn err_panic_reported() -> Result<(), Box<dyn Error + Send + Sync>> {
+    panic!("no config");
+}
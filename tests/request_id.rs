@@ -0,0 +1,65 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use common::LOGGED;
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn request_id() {
+    common::init(log::LevelFilter::Trace);
+
+    pollster::block_on(wrap_match::with_request_id("req-1", async {
+        ok_request_id().await.unwrap();
+        err_request_id().unwrap_err();
+        // the innermost request ID wins, and the outer one comes back afterwards
+        wrap_match::with_request_id("req-2", err_request_id_async())
+            .await
+            .unwrap_err();
+        err_request_id_async().await.unwrap_err();
+        assert_eq!(wrap_match::request_id().as_deref(), Some("req-1"));
+    }));
+    err_request_id().unwrap_err();
+    assert_eq!(wrap_match::request_id(), None);
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "[req-1] ok_request_id succeeded (Some(\"req-1\"))",
+            "[req-1] err_request_id failed: Error",
+            "[req-2] err_request_id_async failed: Error",
+            "[req-1] err_request_id_async failed: Error",
+            "[-] err_request_id failed: Error",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    success_message = "[{request_id}] {function} succeeded ({request_id:?})"
+)]
+async fn ok_request_id() -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "[{request_id}] {function} failed: {error:?}"
+)]
+fn err_request_id() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "[{request_id}] {function} failed: {error:?}"
+)]
+async fn err_request_id_async() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use common::LOGGED;
+
+#[derive(Debug)]
+struct TenantError {
+    tenant: &'static str,
+}
+
+#[test]
+fn self_check() {
+    common::init(log::LevelFilter::Error);
+
+    wrap_match::register_extractor("tenant", |e| {
+        e.downcast_ref::<TenantError>().map(|e| e.tenant.to_owned())
+    });
+    // the check only runs on the first call, but every call is logged
+    err_self_check().unwrap_err();
+    err_self_check().unwrap_err();
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "tenant acme failed: TenantError { tenant: \"acme\" }",
+            "tenant acme failed: TenantError { tenant: \"acme\" }",
+        ]
+    );
+}
+
+// this doesn't use `{tenant}`, which the other test registers an extractor for
+#[test]
+#[should_panic(
+    expected = "the error messages of err_self_check_missing use `{zone}`, `{region}`, but no extractor is registered for them"
+)]
+fn self_check_missing_extractor() {
+    err_self_check_missing().unwrap_err();
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    self_check = true,
+    extract(tenant),
+    error_message = "tenant {tenant} failed: {error:?}",
+    error_message_without_info = "tenant {tenant} failed ({function}): {error:?}"
+)]
+fn err_self_check() -> Result<(), TenantError> {
+    Err(TenantError { tenant: "acme" })?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    self_check = true,
+    extract(zone, region),
+    error_message = "failed in {zone}: {error:?}",
+    error_message_without_info = "failed in {region}: {error:?}"
+)]
+fn err_self_check_missing() -> Result<(), TenantError> {
+    Err(TenantError { tenant: "acme" })?;
+    Ok(())
+}
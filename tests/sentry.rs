@@ -0,0 +1,106 @@
+#![cfg(feature = "sentry")]
+
+use std::{
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use sentry_core::protocol::Level;
+
+static ERR_LINE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+impl Error for CustomError {}
+
+#[derive(Debug)]
+struct NotAnError;
+
+#[test]
+fn sentry() {
+    let events = sentry_core::test::with_captured_events(|| {
+        err_sentry().unwrap_err();
+        err_sentry_not_an_error().unwrap_err();
+        err_sentry_boxed().unwrap_err();
+        err_sentry_off().unwrap_err();
+        err_sentry_breadcrumb().unwrap_err();
+        sentry_core::capture_message("after the breadcrumb", Level::Info);
+        ok_sentry().unwrap();
+    });
+    let [error, not_an_error, boxed, message] = &events[..] else {
+        panic!("expected four events, got {events:?}");
+    };
+
+    let exception = &error.exception[0];
+    assert_eq!(exception.ty, "NotFound");
+    assert_eq!(exception.value.as_deref(), Some("not found"));
+    assert_eq!(error.extra["function"], "err_sentry");
+    assert_eq!(error.extra["line"], ERR_LINE.load(Ordering::Relaxed));
+    assert_eq!(error.extra["expr"], "Err(CustomError::NotFound)");
+
+    assert!(not_an_error.exception.is_empty());
+    assert_eq!(
+        not_an_error.message.as_deref(),
+        Some("An error occurred when running err_sentry_not_an_error: NotAnError")
+    );
+    assert_eq!(not_an_error.level, Level::Error);
+
+    assert_eq!(boxed.exception[0].value.as_deref(), Some("not found"));
+
+    assert_eq!(message.message.as_deref(), Some("after the breadcrumb"));
+    let [breadcrumb] = &message.breadcrumbs[..] else {
+        panic!("expected one breadcrumb, got {:?}", message.breadcrumbs);
+    };
+    assert_eq!(breadcrumb.category.as_deref(), Some("wrap_match"));
+    assert_eq!(
+        breadcrumb.message.as_deref(),
+        Some("An error occurred when running err_sentry_breadcrumb: NotFound")
+    );
+    assert_eq!(breadcrumb.data["function"], "err_sentry_breadcrumb");
+}
+
+#[wrap_match::wrap_match]
+fn err_sentry() -> Result<(), CustomError> {
+    ERR_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match]
+fn err_sentry_not_an_error() -> Result<(), NotAnError> {
+    Err(NotAnError)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match]
+fn err_sentry_boxed() -> Result<(), Box<dyn Error>> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(sentry = "off")]
+fn err_sentry_off() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(sentry = "breadcrumb")]
+fn err_sentry_breadcrumb() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match]
+fn ok_sentry() -> Result<(), CustomError> {
+    Ok(())
+}
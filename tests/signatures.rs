@@ -0,0 +1,405 @@
+//! Functions of every shape can be wrapped: methods, generics, lifetimes, async and unsafe functions, and functions with attributes that have to go on
+//! the right function
+
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::{error::Error, fmt::Debug};
+
+use common::LOGGED;
+
+#[derive(Debug)]
+pub enum CustomError {
+    Error,
+}
+
+impl std::fmt::Display for CustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomError")
+    }
+}
+
+impl Error for CustomError {}
+
+#[test]
+fn signatures() {
+    common::init(log::LevelFilter::Error);
+
+    dyn_error().unwrap_err();
+    err_into(&false).unwrap_err();
+    pollster::block_on(unsafe { err_async_unsafe(false) }).unwrap_err();
+    generic::err_generic(1u8).unwrap_err();
+    Test::err().unwrap_err();
+    Test.err_self().unwrap_err();
+    Test.err_self_attributes().unwrap_err();
+    let mut items = vec![1];
+    assert!(Test.err_self_inline(&mut items).is_err());
+    assert_eq!(items, [1, 2]);
+    assert_eq!(Test.ok_trait_inline().unwrap(), 2);
+    err_mut_arg(false).unwrap_err();
+    err_disregard_result();
+    err_lifetime().unwrap_err();
+    err_lifetime_generics("").unwrap_err();
+    err_generic_error::<CustomError>().unwrap_err();
+    err_require_error_trait().unwrap_err();
+    err_require_error_trait_boxed().unwrap_err();
+    err_inline(3).unwrap_err();
+    assert_eq!(err_inline(1).unwrap(), 1);
+    err_closure("x").unwrap_err();
+    err_strict().unwrap_err();
+    assert_eq!(ok_infallible(2).unwrap(), 4);
+    err_tail_result(true).unwrap_err();
+    assert_eq!(err_tail_result(false).unwrap(), 1);
+    err_ffi_wrapper().unwrap_err();
+    // SAFETY: sse2 is always available on x86_64
+    #[cfg(target_arch = "x86_64")]
+    unsafe { err_target_feature() }.unwrap_err();
+    err_enabled_when().unwrap_err();
+    Test.err_self_enabled_when().unwrap_err();
+
+    let mut repo = Repo { items: vec![1, 2] };
+    assert_eq!(pollster::block_on(repo.get(1)).unwrap(), 2);
+    pollster::block_on(repo.get(5)).unwrap_err();
+    pollster::block_on(repo.push_generic("three")).unwrap_err();
+    assert_eq!(
+        pollster::block_on(repo.into_items(3)).unwrap(),
+        [1, 2, 0, 3]
+    );
+
+    let mut expected = vec![
+        // the inner functions log their errors too
+        "err_into: `Err(CustomError::Error)`: Error",
+        "dyn_error: `err_into(&true)`: CustomError",
+        "err_generic: Error",
+        "err_into: `generic::err_generic(false)`: Error",
+        "err_async_unsafe: Error",
+        "err_generic: Error",
+        "err: `Err(CustomError::Error)`: Error",
+        "err_self: `Err(CustomError::Error)`: Error",
+        "err_self_attributes: `Err(CustomError::Error)`: Error",
+        "err_self_inline failed for [1, 2]: Error",
+        "err_mut_arg: `Err(CustomError::Error)`: Error",
+        "err_disregard_result: `Err(CustomError::Error)`: Error",
+        "err_lifetime: `Err(CustomError::Error)`: Error",
+        "err_lifetime_generics: `Err(CustomError::Error)`: Error",
+        "err_generic_error: `Err(CustomError::Error)`: CustomError",
+        "err_require_error_trait: `Err(CustomError::Error)`: CustomError",
+        // boxed errors implement `Error` too
+        "err_require_error_trait_boxed: `Err(CustomError::Error)`: CustomError",
+        "err_inline failed for 3: Error",
+        // only the function's own try expression gets the expression, since the others return from the closure and the nested function
+        "err_closure: `parse_twice(input)`: ParseIntError { kind: InvalidDigit }",
+        "err_strict: `Err(CustomError::Error)`: Error",
+        "err_tail_result: `last_step(fail)`: Error",
+        "err_ffi_wrapper: `Err(CustomError::Error)`: Error",
+    ];
+    #[cfg(target_arch = "x86_64")]
+    expected.push("err_target_feature: `Err(CustomError::Error)`: Error");
+    expected.extend([
+        "err_enabled_when: `Err(CustomError::Error)`: Error",
+        "err_self_enabled_when: `Err(CustomError::Error)`: Error",
+        "get: `item.ok_or(\"missing item\")`: \"missing item\"",
+        "push_generic: `Err(format!(\"{len} is too many\"))`: \"10 is too many\"",
+    ]);
+    assert_eq!(*LOGGED.lock().unwrap(), expected);
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error}")]
+fn dyn_error() -> Result<(), Box<dyn Error>> {
+    err_into(&true)?;
+    Err(CustomError::Error)?; // this will never be triggered, just to ensure it compiles fine
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function}: `{expr}`: {error:?}",
+    log_success = false
+)]
+#[allow(clippy::let_unit_value)]
+fn err_into(fail: &bool) -> Result<(), CustomError> {
+    if *fail {
+        Err(CustomError::Error)?;
+    }
+    let _ = generic::err_generic(false)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message_without_info = "{function}: {error:?}")]
+async unsafe fn err_async_unsafe(_arg: bool) -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+mod generic {
+    use super::*;
+
+    #[wrap_match::wrap_match(logger = "log", error_message_without_info = "{function}: {error:?}")]
+    pub fn err_generic<T>(_arg: T) -> Result<(), CustomError> {
+        Err(CustomError::Error.into())
+    }
+}
+
+struct Test;
+
+impl Test {
+    #[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+    pub fn err() -> Result<(), CustomError> {
+        Err(CustomError::Error)?;
+        Ok(())
+    }
+
+    #[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+    pub fn err_self(&self) -> Result<(), CustomError> {
+        Err(CustomError::Error)?;
+        Ok(())
+    }
+
+    // the inner function is next to this one, so it needs the lint level and `cfg` too
+    #[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+    #[cfg(test)]
+    #[deny(unused_variables)]
+    pub fn err_self_attributes(&self) -> Result<(), CustomError> {
+        Err(CustomError::Error)?;
+        Ok(())
+    }
+
+    #[wrap_match::wrap_match(
+        logger = "log",
+        inline = true,
+        error_message = "{function} failed for {items:?}: {error:?}"
+    )]
+    pub fn err_self_inline(&self, items: &mut Vec<u8>) -> Result<&Self, CustomError> {
+        items.push(2);
+        Err(CustomError::Error)?;
+        Ok(self)
+    }
+
+    // only wrapped with the `log` feature, but has to work either way
+    #[wrap_match::wrap_match(
+        logger = "log",
+        enabled_when = feature = "log",
+        error_message = "{function}: `{expr}`: {error:?}"
+    )]
+    pub fn err_self_enabled_when(&self) -> Result<(), CustomError> {
+        Err(CustomError::Error)?;
+        Ok(())
+    }
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+#[allow(unused_mut)]
+fn err_mut_arg(mut arg: bool) -> Result<(), CustomError> {
+    ok_ref_mut_arg(&mut arg)?;
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", log_success = false)]
+fn ok_ref_mut_arg(_arg: &mut bool) -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    disregard_result = true,
+    error_message = "{function}: `{expr}`: {error:?}"
+)]
+fn err_disregard_result() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+fn err_lifetime<'lt>() -> Result<&'lt str, CustomError> {
+    Err(CustomError::Error)?;
+    Ok("")
+}
+
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+fn err_lifetime_generics<'lt, ANY>(_any: ANY) -> Result<&'lt str, CustomError> {
+    Err(CustomError::Error)?;
+    Ok("")
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function}: `{expr}`: {error}",
+    require_error_trait = true
+)]
+fn err_generic_error<E: From<CustomError> + Error>() -> Result<(), E> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function}: `{expr}`: {error}",
+    require_error_trait = true
+)]
+fn err_require_error_trait() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    error_message = "{function}: `{expr}`: {error}",
+    require_error_trait = true
+)]
+fn err_require_error_trait_boxed() -> Result<(), Box<dyn Error + Send + Sync>> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    inline = true,
+    error_message = "{function} failed for {value}: {error:?}"
+)]
+fn err_inline(value: u8) -> Result<u8, CustomError> {
+    if value > 2 {
+        Err(CustomError::Error)?;
+    }
+    Ok(value)
+}
+
+trait Doubler {
+    fn double(&self, value: u8) -> Result<u8, CustomError>;
+
+    fn ok_trait_inline(&self) -> Result<u8, CustomError> {
+        self.double(1)
+    }
+}
+
+// an inner method next to `double` wouldn't be a member of the trait
+impl Doubler for Test {
+    #[wrap_match::wrap_match(logger = "log", inline = true)]
+    fn double(&self, value: u8) -> Result<u8, CustomError> {
+        let doubled = value.checked_mul(2).ok_or(CustomError::Error)?;
+        Ok(doubled)
+    }
+}
+
+// try expressions in closures and nested functions return from them, so they have to be left alone
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+fn err_closure(input: &str) -> Result<u8, std::num::ParseIntError> {
+    fn parse(input: &str) -> Result<u8, std::num::ParseIntError> {
+        Ok(input.parse::<u8>()? + 1)
+    }
+    let parse_twice =
+        |input: &str| -> Result<u8, std::num::ParseIntError> { Ok(parse(input)? * 2) };
+    let value = parse_twice(input)?;
+    Ok(value)
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    strict = true,
+    error_message = "{function}: `{expr}`: {error:?}"
+)]
+fn err_strict() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log")]
+fn ok_infallible(value: u8) -> Result<u8, std::convert::Infallible> {
+    let doubled: Result<u8, std::convert::Infallible> = Ok(value.saturating_mul(2));
+    let doubled = doubled?;
+    Ok(doubled)
+}
+
+fn last_step(fail: bool) -> Result<u8, CustomError> {
+    if fail {
+        Err(CustomError::Error)
+    } else {
+        Ok(1)
+    }
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    tail_result = true,
+    error_message = "{function}: `{expr}`: {error:?}"
+)]
+fn err_tail_result(fail: bool) -> Result<u8, CustomError> {
+    if fail {
+        last_step(fail)
+    } else {
+        match fail {
+            true => Err(CustomError::Error.into()),
+            false => last_step(fail),
+        }
+    }
+}
+
+// a thin wrapper exported over FFI: the symbol attributes must only be on the function wrap-match makes, or the symbol would be defined twice
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+#[export_name = "wrap_match_test_ffi_wrapper"]
+#[link_section = ".text.wrap_match_test"]
+#[allow(improper_ctypes_definitions)]
+pub extern "C" fn err_ffi_wrapper() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "x86_64")]
+#[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+#[target_feature(enable = "sse2")]
+fn err_target_feature() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    enabled_when = feature = "log",
+    error_message = "{function}: `{expr}`: {error:?}"
+)]
+fn err_enabled_when() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+pub struct Repo<T> {
+    items: Vec<T>,
+}
+
+trait Store<T> {
+    async fn get(&self, index: usize) -> Result<T, String>;
+}
+
+// async methods capture the implementation's generics (and can be in trait implementations)
+impl<T: Debug + Clone> Store<T> for Repo<T> {
+    #[wrap_match::wrap_match(
+        logger = "log",
+        error_message = "{function}: `{expr}`: {error:?}",
+        log_success = false
+    )]
+    async fn get(&self, index: usize) -> Result<T, String> {
+        let item = async move { self.items.get(index).cloned() }.await;
+        let item = item.ok_or("missing item")?;
+        Ok(item)
+    }
+}
+
+impl<T: Debug + From<u8>> Repo<T> {
+    #[wrap_match::wrap_match(logger = "log", error_message = "{function}: `{expr}`: {error:?}")]
+    async fn push_generic<U: Debug>(&mut self, value: U) -> Result<usize, String> {
+        let len = async move {
+            self.items.push(T::from(0));
+            format!("{value:?}").len() + self.items.len()
+        }
+        .await;
+        Err(format!("{len} is too many"))?;
+        Ok(len)
+    }
+
+    #[wrap_match::wrap_match(logger = "log")]
+    async fn into_items(self, extra: u8) -> Result<Vec<T>, String> {
+        let mut items = self.items;
+        items.push(T::from(extra));
+        Ok(items)
+    }
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "span_trace")]
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use tracing_error::ErrorLayer;
+use tracing_subscriber::prelude::*;
+
+/// Collects everything the `fmt` layer writes
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn span_trace() {
+    let output = Output::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::registry()
+        .with(ErrorLayer::default())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone()),
+        );
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info_span!("request", id = 7).in_scope(|| err_span_trace().unwrap_err());
+        err_without_span().unwrap_err();
+    });
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("err_span_trace failed: Error\n"));
+    assert!(output.contains("span_trace::request\n           with id=7"));
+    assert!(output.contains("err_without_span failed: Error\n\n"));
+}
+
+#[wrap_match::wrap_match(
+    span_trace = true,
+    error_message = "{function} failed: {error:?}\n{span_trace}"
+)]
+fn err_span_trace() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    span_trace = true,
+    error_message = "{function} failed: {error:?}\n{span_trace}"
+)]
+fn err_without_span() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
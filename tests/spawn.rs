@@ -0,0 +1,43 @@
+#![cfg(all(feature = "tokio", not(feature = "tracing")))]
+
+mod common;
+
+use common::LOGGED;
+
+#[test]
+fn spawn_logged() {
+    common::init(log::LevelFilter::Trace);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        assert_eq!(
+            wrap_match::spawn_logged(async { Ok::<_, ()>(1) })
+                .await
+                .unwrap(),
+            Ok(1)
+        );
+        wrap_match::spawn_logged(async { Err::<(), _>("oh no") })
+            .await
+            .unwrap()
+            .unwrap_err();
+        let error = wrap_match::spawn_logged(async {
+            panic!("boom");
+            #[allow(unreachable_code)]
+            Ok::<(), ()>(())
+        })
+        .await
+        .unwrap_err();
+        assert!(error.is_panic());
+
+        let messages = LOGGED.lock().unwrap();
+        let spawned_at = format!("spawned at {}:", file!());
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].starts_with(&format!("Successfully ran the task {spawned_at}")));
+        assert!(messages[1].starts_with(&format!("An error occurred in the task {spawned_at}")));
+        assert!(messages[1].ends_with(": \"oh no\""));
+        assert!(messages[2].starts_with(&format!("The task {spawned_at}")));
+        assert!(messages[2].ends_with(" panicked: boom"));
+    });
+}
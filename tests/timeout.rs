@@ -0,0 +1,87 @@
+#![cfg(all(feature = "log", feature = "tokio"))]
+
+mod common;
+
+use std::{error::Error, time::Duration};
+
+use wrap_match::TimedOut;
+
+use common::LOGGED;
+
+#[derive(Debug)]
+enum CustomError {
+    TimedOut(TimedOut),
+}
+
+impl From<TimedOut> for CustomError {
+    fn from(timed_out: TimedOut) -> Self {
+        Self::TimedOut(timed_out)
+    }
+}
+
+#[test]
+fn timeout() {
+    common::init(log::LevelFilter::Info);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        assert_eq!(ok_in_time().await.unwrap(), 1);
+        let CustomError::TimedOut(timed_out) = err_too_slow().await.unwrap_err();
+        assert_eq!(timed_out.function(), "err_too_slow");
+        assert_eq!(timed_out.after(), Duration::from_millis(10));
+        let error = err_too_slow_boxed(7).await.unwrap_err();
+        assert_eq!(error.to_string(), "err_too_slow_boxed timed out after 10ms");
+    });
+
+    // timeouts go through the same pipeline as errors, so `panic_on_error` panics with the timeout message
+    std::panic::set_hook(Box::new(|_| {}));
+    let payload = std::panic::catch_unwind(|| {
+        runtime.block_on(err_too_slow_panicking()).ok();
+    })
+    .unwrap_err();
+    let _ = std::panic::take_hook();
+    assert_eq!(
+        payload.downcast_ref::<String>().unwrap(),
+        "err_too_slow_panicking timed out after 10ms"
+    );
+
+    assert_eq!(
+        *LOGGED.lock().unwrap(),
+        [
+            "Successfully ran ok_in_time",
+            "err_too_slow timed out after 10ms",
+            "syncing 7 took longer than 10ms",
+            "err_too_slow_panicking timed out after 10ms",
+        ]
+    );
+}
+
+#[wrap_match::wrap_match(logger = "log", timeout = "1h")]
+async fn ok_in_time() -> Result<u32, CustomError> {
+    Ok(1)
+}
+
+#[wrap_match::wrap_match(logger = "log", timeout = "10ms")]
+async fn err_too_slow() -> Result<(), CustomError> {
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "log",
+    timeout = "10ms",
+    timeout_message = "syncing {id} took longer than {timeout:?}"
+)]
+async fn err_too_slow_boxed(id: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+    tokio::time::sleep(Duration::from_secs(u64::from(id))).await;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", timeout = "10ms", panic_on_error = true)]
+async fn err_too_slow_panicking() -> Result<(), CustomError> {
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    Ok(())
+}
@@ -0,0 +1,205 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+static FIELDS_LINE: AtomicU32 = AtomicU32::new(0);
+static WIDE_EVENT_LINE: AtomicU32 = AtomicU32::new(0);
+static TARGET_LINE: AtomicU32 = AtomicU32::new(0);
+
+/// Collects everything the `fmt` layer writes
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Collects the names of the events, which the `fmt` layer doesn't write
+#[derive(Clone, Default)]
+struct EventNames(Arc<Mutex<Vec<&'static str>>>);
+
+impl<S: tracing::Subscriber> Layer<S> for EventNames {
+    fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+        self.0.lock().unwrap().push(event.metadata().name());
+    }
+}
+
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[test]
+fn tracing() {
+    let output = Output::default();
+    let writer = output.clone();
+    let event_names = EventNames::default();
+    let subscriber = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .without_time()
+                .with_writer(move || writer.clone()),
+        )
+        .with(event_names.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        ok_instrumented(1).unwrap();
+        err_event_name().unwrap_err();
+        err_error_fields().unwrap_err();
+        err_instrument(7, "unused").unwrap_err();
+        assert_eq!(pollster::block_on(ok_instrument_async(7)).unwrap(), 7);
+        err_static_fields().unwrap_err();
+        ok_wide_event(1).unwrap();
+        err_wide_event(1).unwrap_err();
+        err_wide_event_without_info(1).unwrap_err();
+        err_target().unwrap_err();
+    });
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    // durations change from run to run
+    let output: Vec<_> = output
+        .lines()
+        .map(|line| match line.split_once(" duration=") {
+            Some((start, rest)) => {
+                let (_, end) = rest.split_once(' ').unwrap();
+                format!("{start} duration=_ {end}")
+            }
+            None => line.to_owned(),
+        })
+        .collect();
+    let fields_line = FIELDS_LINE.load(Ordering::Relaxed);
+    let wide_event_line = WIDE_EVENT_LINE.load(Ordering::Relaxed);
+    let target_line = TARGET_LINE.load(Ordering::Relaxed);
+    assert_eq!(
+        output,
+        [
+            // the success message is logged in the span of `#[tracing::instrument]` too
+            " INFO ok_instrumented{_a=1}: tracing: hello from tracing!".to_owned(),
+            " INFO ok_instrumented{_a=1}: tracing: success 1".to_owned(),
+            "ERROR tracing: err_event_name failed: Error owner=\"events\"".to_owned(),
+            format!(
+                "ERROR tracing: Fehler beim Ausführen von err_error_fields error=Error error.line={fields_line} error.expr=\"Err(CustomError::Error)\""
+            ),
+            " INFO err_instrument{user_id=7}: tracing: hello from the span of 7!".to_owned(),
+            "ERROR err_instrument{user_id=7}: tracing: err_instrument failed: Error".to_owned(),
+            " INFO ok_instrument_async: tracing: hello from the async span!".to_owned(),
+            " INFO ok_instrument_async: tracing: Successfully ran ok_instrument_async".to_owned(),
+            "ERROR tracing: err_static_fields failed: Error component=\"importer\" tenant=\"acme\"".to_owned(),
+            " INFO tracing: Successfully ran ok_wide_event outcome=\"success\" duration=_ args=(_user_id: 1)".to_owned(),
+            format!(
+                "ERROR tracing: err_wide_event failed outcome=\"failure\" duration=_ error=Error line={wide_event_line} expr=\"Err(CustomError::Error)\" args=(_user_id: 1)"
+            ),
+            "ERROR tracing: An error occurred when running err_wide_event_without_info: Error outcome=\"failure\" duration=_ error=Error args=(_user_id: 1)".to_owned(),
+            "ERROR audit::tracing::err_target: err_target failed: Error owner=\"team\"".to_owned(),
+            format!(
+                "ERROR wrap_match::machine: function=err_target line={target_line} expr=\"Err(CustomError::Error)\" error=Error owner=\"team\""
+            ),
+        ]
+    );
+    assert_eq!(
+        event_names.0.lock().unwrap()[2],
+        "events.err_event_name.failed"
+    );
+}
+
+#[wrap_match::wrap_match(logger = "tracing", success_message = "success {_a}")]
+#[tracing::instrument]
+fn ok_instrumented(_a: i64) -> Result<(), ()> {
+    tracing::info!("hello from tracing!");
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "tracing",
+    event_name = "{owner}.{function}.failed",
+    owner = "events",
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_event_name() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "tracing", error_style = "fields", locale = "de")]
+fn err_error_fields() -> Result<(), CustomError> {
+    FIELDS_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "tracing",
+    instrument(user_id),
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_instrument(user_id: u64, _name: &str) -> Result<(), CustomError> {
+    tracing::info!("hello from the span of {user_id}!");
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "tracing", instrument = true)]
+async fn ok_instrument_async(user_id: u64) -> Result<u64, CustomError> {
+    async {}.await;
+    tracing::info!("hello from the async span!");
+    Ok(user_id)
+}
+
+#[wrap_match::wrap_match(
+    logger = "tracing",
+    fields(component = "importer", tenant = "acme"),
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_static_fields() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "tracing", wide_event = true)]
+fn ok_wide_event(_user_id: u64) -> Result<(), CustomError> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(
+    logger = "tracing",
+    wide_event = true,
+    error_message = "{function} failed"
+)]
+fn err_wide_event(_user_id: u64) -> Result<(), CustomError> {
+    WIDE_EVENT_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "tracing", wide_event = true)]
+fn err_wide_event_without_info(_user_id: u64) -> Result<(), CustomError> {
+    Err(CustomError::Error.into())
+}
+
+#[wrap_match::wrap_match(
+    logger = "tracing",
+    target = "audit::{module}::{function}",
+    owner = "team",
+    dual_output = true,
+    error_message = "{function} failed: {error:?}"
+)]
+fn err_target() -> Result<(), CustomError> {
+    TARGET_LINE.store(line!() + 1, Ordering::Relaxed);
+    Err(CustomError::Error)?;
+    Ok(())
+}
@@ -1,5 +1,50 @@
 #[test]
 fn ui() {
     let t = trybuild::TestCases::new();
+    // these use the default logger, which is `log` with the default features (the errors mention its macros, and without a logging feature, every
+    // function also gets the warning about writing to stderr)
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
     t.compile_fail("tests/ui/*.rs");
+    // these errors depend on logging through `log`, even when another logger is the default
+    #[cfg(feature = "log")]
+    t.compile_fail("tests/ui/log/*.rs");
+
+    #[cfg(feature = "policy")]
+    {
+        std::env::set_var(
+            "WRAP_MATCH_POLICY",
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/ui/policy/wrap-match-policy.toml"
+            ),
+        );
+        t.compile_fail("tests/ui/policy/*.rs");
+    }
+
+    #[cfg(feature = "catalog")]
+    {
+        std::env::set_var(
+            "WRAP_MATCH_CATALOG",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/messages/catalog.toml"),
+        );
+        t.compile_fail("tests/ui/catalog/*.rs");
+    }
+    #[cfg(not(feature = "catalog"))]
+    t.compile_fail("tests/ui/no-catalog/*.rs");
+    #[cfg(all(feature = "log", not(feature = "kv")))]
+    t.compile_fail("tests/ui/no-kv/*.rs");
+    #[cfg(not(feature = "log"))]
+    t.compile_fail("tests/ui/no-log/*.rs");
+    #[cfg(not(feature = "tracing"))]
+    t.compile_fail("tests/ui/no-tracing/*.rs");
+    #[cfg(not(any(feature = "log", feature = "tracing")))]
+    t.compile_fail("tests/ui/no-logger/*.rs");
+    #[cfg(not(feature = "span_trace"))]
+    t.compile_fail("tests/ui/no-span-trace/*.rs");
+    #[cfg(not(feature = "sentry"))]
+    t.compile_fail("tests/ui/no-sentry/*.rs");
+    #[cfg(not(feature = "metrics"))]
+    t.compile_fail("tests/ui/no-metrics/*.rs");
+    #[cfg(not(feature = "tokio"))]
+    t.compile_fail("tests/ui/no-tokio/*.rs");
 }
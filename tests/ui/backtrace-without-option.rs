@@ -0,0 +1,7 @@
+#[wrap_match::wrap_match(error_message = "{error:?}\n{backtrace}")]
+fn my_function() -> Result<(), ()> {
+    Err(())?;
+    Ok(())
+}
+
+fn main() {}
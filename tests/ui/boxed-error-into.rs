@@ -0,0 +1,9 @@
+use std::error::Error;
+
+// only strings can be converted into boxed errors with `.into()`, other errors have to use `?`
+#[wrap_match::wrap_match]
+fn my_function() -> Result<(), Box<dyn Error + Send + Sync>> {
+    Err(std::fmt::Error.into())
+}
+
+fn main() {}
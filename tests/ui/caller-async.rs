@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(error_message = "called from {caller}")]
+async fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
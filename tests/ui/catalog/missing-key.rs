@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(message_key = "db.missing")]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(chain_order = "outermost_first")]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
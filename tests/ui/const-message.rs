@@ -0,0 +1,10 @@
+const fn message() -> &'static str {
+    "{function} failed"
+}
+
+#[wrap_match::wrap_match(error_message = message())]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(
+    error_message = "{function} failed on line {line}: {error:?}",
+    derive_without_info = true
+)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(emit_order(log, hook))]
+fn ordered() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
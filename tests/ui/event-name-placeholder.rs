@@ -0,0 +1,7 @@
+#[wrap_match::wrap_match(event_name = "{function}.{error}")]
+fn my_function() -> Result<(), ()> {
+    Err(())?;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+    Timeout,
+}
+
+#[wrap_match::wrap_match(level_for(NotFound = "warn"), exhaustive = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Timeout)?;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+#[wrap_match::wrap_match(error_level = "warn", exhaustive = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+fn main() {}
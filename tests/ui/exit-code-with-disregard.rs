@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(disregard_result = true, exit_code = true)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn main() {}
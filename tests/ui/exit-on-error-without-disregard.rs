@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(exit_on_error = 1)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn main() {}
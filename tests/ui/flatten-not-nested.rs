@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(flatten = true)]
+fn not_nested() -> Result<u8, ()> {
+    Ok(1)
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[wrap_match::wrap_match(duration_format = "minutes")]
+fn invalid_format() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(duration_precision = 2)]
+fn precision_without_format() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
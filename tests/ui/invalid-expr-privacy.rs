@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(expr_privacy = "encrypt")]
+fn unknown_privacy() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+fn level(_: &()) -> log::Level {
+    log::Level::Warn
+}
+
+#[wrap_match::wrap_match(success_level = "notice")]
+fn invalid_level() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(error_level = "warn", level_fn = level)]
+fn error_level_with_level_fn() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#[wrap_match::wrap_match(logger = "slog")]
+fn unknown_logger() -> Result<(), ()> {
+    Ok(())
+}
+
+fn level(_: &()) -> log::Level {
+    log::Level::Warn
+}
+
+#[wrap_match::wrap_match(logger = "plain", level_fn = level)]
+fn plain_level_fn() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "plain", bridge_safe = true)]
+fn plain_bridge_safe() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
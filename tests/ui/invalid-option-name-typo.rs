@@ -0,0 +1,7 @@
+fn main() {}
+
+#[wrap_match::wrap_match(sucess_message = "done")]
+fn invalid_option_name_typo() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
@@ -0,0 +1,12 @@
+#[wrap_match::wrap_match(error_message = "{function} failed{?line} on line {line}: {error:?}")]
+fn unclosed() -> Result<(), ()> {
+    Err(())?;
+    Ok(())
+}
+
+#[wrap_match::wrap_match(success_message = "{function} ran{?line}{/expr}")]
+fn mismatched() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#[derive(Debug)]
+enum CustomError {
+    NotFound,
+}
+
+fn level(_: &CustomError) -> log::Level {
+    log::Level::Warn
+}
+
+#[wrap_match::wrap_match(level_for(NotFound = "warn"), level_fn = level)]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::NotFound)?;
+    Ok(())
+}
+
+fn main() {}
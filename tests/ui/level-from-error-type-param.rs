@@ -0,0 +1,7 @@
+fn main() {}
+
+#[wrap_match::wrap_match(level_from_error = true)]
+fn level_from_error_type_param<E: std::fmt::Debug>(error: E) -> Result<(), E> {
+    Err(error)?;
+    Ok(())
+}
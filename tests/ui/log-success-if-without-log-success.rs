@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(log_success = false, log_success_if = |rows| *rows > 0)]
+fn my_function() -> Result<u64, CustomError> {
+    Err(CustomError::Error)?;
+    Ok(0)
+}
+
+fn main() {}
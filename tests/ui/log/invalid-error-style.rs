@@ -0,0 +1,21 @@
+#[wrap_match::wrap_match(error_style = "yaml")]
+fn unknown_style() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "log", error_style = "fields")]
+fn log_fields() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "plain", error_style = "fields")]
+fn plain_fields() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(error_style = "json", error_message = "{function} failed")]
+fn json_message() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
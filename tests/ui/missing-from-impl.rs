@@ -0,0 +1,22 @@
+#[derive(Debug)]
+struct ParseError;
+
+#[derive(Debug)]
+struct ConfigError;
+
+fn parse() -> Result<u8, ParseError> {
+    Err(ParseError)
+}
+
+#[wrap_match::wrap_match]
+fn load_config() -> Result<u8, ConfigError> {
+    let value = parse()?;
+    Ok(value)
+}
+
+#[wrap_match::wrap_match(tail_result = true)]
+fn load_config_tail() -> Result<u8, ConfigError> {
+    parse()
+}
+
+fn main() {}
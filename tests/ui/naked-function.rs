@@ -0,0 +1,7 @@
+#[wrap_match::wrap_match]
+#[unsafe(naked)]
+extern "C" fn my_function() -> Result<(), ()> {
+    core::arch::naked_asm!("ret")
+}
+
+fn main() {}
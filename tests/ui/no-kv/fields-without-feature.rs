@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(logger = "log", fields(component = "importer"))]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
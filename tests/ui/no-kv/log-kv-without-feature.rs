@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(log_kv = true)]
+fn log_kv() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(bridge_safe = true)]
+fn bridged() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
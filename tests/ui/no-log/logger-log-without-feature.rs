@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(logger = "log")]
+fn logged() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
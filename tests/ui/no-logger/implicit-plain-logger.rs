@@ -0,0 +1,14 @@
+#![deny(deprecated)]
+
+// without the `log` and `tracing` features, messages go to stderr, which has to be asked for with `logger = "plain"`
+#[wrap_match::wrap_match]
+fn implicit() -> Result<(), ()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(logger = "plain")]
+fn explicit() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
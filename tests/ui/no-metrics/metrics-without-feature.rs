@@ -0,0 +1,7 @@
+#[wrap_match::wrap_match(metrics = true)]
+fn my_function() -> Result<(), ()> {
+    Err(())?;
+    Ok(())
+}
+
+fn main() {}
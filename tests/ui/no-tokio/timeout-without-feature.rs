@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(timeout = "30s")]
+async fn my_function() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    Ok(())
+}
+
+fn main() {}
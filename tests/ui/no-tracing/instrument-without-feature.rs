@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(instrument = true)]
+fn instrumented() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
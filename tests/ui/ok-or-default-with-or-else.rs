@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(or_else = 1, ok_or_default = true)]
+fn my_function() -> Result<u8, CustomError> {
+    Err(CustomError::Error)?;
+    Ok(0)
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+#[wrap_match::wrap_match(disregard_result = true, ok_or_none = true)]
+fn my_function() -> Result<u8, CustomError> {
+    Err(CustomError::Error)?;
+    Ok(0)
+}
+
+fn main() {}
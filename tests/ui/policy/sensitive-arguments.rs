@@ -0,0 +1,17 @@
+fn main() {}
+
+#[wrap_match::wrap_match(error_message_without_info = "failed to log in with {password}")]
+fn login(_user: &str, password: &str) -> Result<(), ()> {
+    Err(().into())
+}
+
+#[wrap_match::wrap_match(success_message = "called with {args:?}")]
+fn request(api_key: &str) -> Result<(), ()> {
+    Ok(())
+}
+
+// not logged, so this is fine
+#[wrap_match::wrap_match]
+fn login_without_logging(_user: &str, _password: &str) -> Result<(), ()> {
+    Ok(())
+}
@@ -0,0 +1,7 @@
+fn main() {}
+
+#[wrap_match::wrap_match(require_error_trait = true)]
+fn string_error() -> Result<(), String> {
+    Err("oh no".to_owned())?;
+    Ok(())
+}
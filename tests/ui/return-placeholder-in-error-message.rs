@@ -0,0 +1,13 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+// `{return}` is only another name for `{ok}`, which isn't available in error messages
+#[wrap_match::wrap_match(error_message = "{function} failed: {return:?}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+type AppResult<T> = Result<T, std::num::ParseIntError>;
+
+#[wrap_match::wrap_match(strict = true)]
+fn alias() -> AppResult<()> {
+    Ok(())
+}
+
+#[wrap_match::wrap_match(strict = true)]
+fn skipped(input: &str) -> Result<u8, std::num::ParseIntError> {
+    let parse = |input: &str| -> Result<u8, std::num::ParseIntError> { Ok(input.parse::<u8>()?) };
+    println!("{}", input.parse::<u8>()?);
+    parse(input)
+}
+
+fn main() {}
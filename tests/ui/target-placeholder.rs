@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(target = "audit::{line}")]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
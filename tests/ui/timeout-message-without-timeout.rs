@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(timeout_message = "{function} took too long")]
+async fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
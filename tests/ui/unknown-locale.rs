@@ -0,0 +1,6 @@
+#[wrap_match::wrap_match(locale = "xx")]
+fn my_function() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[derive(Debug)]
+enum CustomError {
+    Error,
+}
+
+// only placeholders declared with `extract` are filled in by extractors, so typos are still caught
+#[wrap_match::wrap_match(extract(tenant), error_message = "{function} failed for {tenant}: {eror:?}")]
+fn my_function() -> Result<(), CustomError> {
+    Err(CustomError::Error)?;
+    Ok(())
+}
+
+fn main() {}
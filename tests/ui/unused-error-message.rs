@@ -0,0 +1,8 @@
+#![deny(deprecated)]
+
+#[wrap_match::wrap_match(error_message = "{function} failed on line {line}: {error:?}")]
+fn my_function() -> Result<(), ()> {
+    Err(().into())
+}
+
+fn main() {}
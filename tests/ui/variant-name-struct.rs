@@ -0,0 +1,4 @@
+fn main() {}
+
+#[derive(wrap_match::VariantName)]
+struct NotAnEnum;
@@ -0,0 +1,18 @@
+fn main() {}
+
+#[derive(wrap_match::WrapMatchLevel)]
+struct NotAnEnum;
+
+#[derive(wrap_match::WrapMatchLevel)]
+enum UnknownLevel {
+    #[wrap_match(level = "fatal")]
+    Variant,
+}
+
+#[derive(wrap_match::WrapMatchLevel)]
+#[wrap_match(exhaustive = true)]
+enum MissingLevel {
+    #[wrap_match(level = "warn")]
+    WithLevel,
+    WithoutLevel,
+}